@@ -1,8 +1,8 @@
+use crate::linux::{lookup_ids, read_optional, read_pci_ids};
+use crate::source::{CIMV2, DataSource, Platform};
 use crate::{ComputerInfoExt, VariantExt};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use wmi::Variant;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AudioInfo {
@@ -15,13 +15,35 @@ pub struct AudioDevice {
 	pub manufacturer: String,
 	pub status: String,
 	pub device_id: String,
+	/// Kernel driver bound to the card, e.g. `snd_hda_intel`. Linux only.
+	pub driver: String,
+	/// HD Audio codecs on the card, e.g. `Realtek ALC1220`. Linux only.
+	pub codecs: Vec<String>,
+	pub playback_devices: u32,
+	pub capture_devices: u32,
+}
+
+/// One entry of `/proc/asound/cards`.
+struct AlsaCard {
+	index: u32,
+	/// ALSA driver name, e.g. `HDA-Intel` or `USB-Audio`.
+	driver: String,
+	short_name: String,
 }
 
 impl ComputerInfoExt for AudioInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
-		let results: Vec<HashMap<String, Variant>> =
-			com.raw_query("SELECT * FROM Win32_SoundDevice")?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Audio device collection")),
+		}
+	}
+}
+
+impl AudioInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let results = source.wmi_query(CIMV2, "SELECT * FROM Win32_SoundDevice")?;
 
 		let devices = results
 			.iter()
@@ -30,9 +52,119 @@ impl ComputerInfoExt for AudioInfo {
 				manufacturer: data.get_string("Manufacturer").unwrap_or_default(),
 				status: data.get_string("Status").unwrap_or_default(),
 				device_id: data.get_string("DeviceID").unwrap_or_default(),
+				..AudioDevice::default()
 			})
 			.collect();
 
 		Ok(AudioInfo { devices })
 	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		// Without the ALSA core loaded there are no sound cards to report
+		let Ok(cards) = source.read_to_string("/proc/asound/cards") else {
+			return Ok(AudioInfo::default());
+		};
+		let sound_entries = source.read_dir("/sys/class/sound").unwrap_or_default();
+		let pci_ids = read_pci_ids(source);
+
+		let devices = parse_asound_cards(&cards)
+			.iter()
+			.map(|card| Self::read_alsa_card(source, card, &sound_entries, pci_ids.as_deref()))
+			.collect();
+
+		Ok(AudioInfo { devices })
+	}
+
+	/// Describes a sound card from the PCI or USB device behind `/sys/class/sound/card<N>`.
+	fn read_alsa_card(source: &dyn DataSource, card: &AlsaCard, sound_entries: &[String], pci_ids: Option<&str>) -> AudioDevice {
+		let dir = format!("/sys/class/sound/card{}/device", card.index);
+		let link_name = |path: String| {
+			source
+				.read_link(&path)
+				.ok()
+				.and_then(|target| target.rsplit('/').next().map(String::from))
+		};
+
+		let mut device = AudioDevice {
+			name: card.short_name.clone(),
+			status: "OK".to_string(),
+			// Cards without a parent device, such as snd-aloop, only have the ALSA driver name
+			driver: link_name(format!("{}/driver", dir)).unwrap_or_else(|| card.driver.clone()),
+			..AudioDevice::default()
+		};
+
+		match link_name(format!("{}/subsystem", dir)).as_deref() {
+			Some("pci") => {
+				let pci_hex =
+					|file: &str| read_optional(source, &format!("{}/{}", dir, file)).map(|id| id.trim_start_matches("0x").to_lowercase());
+				if let (Some(vendor), Some(product)) = (pci_hex("vendor"), pci_hex("device")) {
+					let (vendor_name, device_name) = pci_ids.map(|ids| lookup_ids(ids, &vendor, &product)).unwrap_or_default();
+					device.manufacturer = vendor_name.unwrap_or_else(|| format!("PCI vendor {}", vendor.to_uppercase()));
+					if let Some(device_name) = device_name {
+						device.name = device_name;
+					}
+				}
+				device.device_id = link_name(dir.clone()).unwrap_or_default();
+			}
+			Some("usb") => {
+				// The card hangs off a USB interface; the descriptor strings live on its parent device
+				let usb_device = format!("{}/..", dir);
+				device.manufacturer = read_optional(source, &format!("{}/manufacturer", usb_device))
+					.or_else(|| {
+						read_optional(source, &format!("{}/idVendor", usb_device)).map(|id| format!("USB vendor {}", id.to_uppercase()))
+					})
+					.unwrap_or_default();
+				if let Some(product) = read_optional(source, &format!("{}/product", usb_device)) {
+					device.name = product;
+				}
+				device.device_id = link_name(dir.clone())
+					.map(|interface| interface.split(':').next().unwrap_or_default().to_string())
+					.unwrap_or_default();
+			}
+			_ => {}
+		}
+
+		let proc_dir = format!("/proc/asound/card{}", card.index);
+		device.codecs = source
+			.read_dir(&proc_dir)
+			.unwrap_or_default()
+			.iter()
+			.filter(|entry| entry.starts_with("codec#"))
+			.filter_map(|entry| {
+				let text = source.read_to_string(&format!("{}/{}", proc_dir, entry)).ok()?;
+				text.lines().find_map(|line| line.strip_prefix("Codec: ")).map(|codec| codec.trim().to_string())
+			})
+			.collect();
+
+		// PCM devices are named pcmC<card>D<device><p|c>
+		let prefix = format!("pcmC{}D", card.index);
+		let pcms = sound_entries.iter().filter_map(|entry| entry.strip_prefix(&prefix));
+		for pcm in pcms {
+			match pcm.chars().last() {
+				Some('p') => device.playback_devices += 1,
+				Some('c') => device.capture_devices += 1,
+				_ => {}
+			}
+		}
+
+		device
+	}
+}
+
+/// Parses `/proc/asound/cards`, where each card takes two lines:
+/// ` 0 [Generic        ]: HDA-Intel - HD-Audio Generic` followed by the long name.
+fn parse_asound_cards(text: &str) -> Vec<AlsaCard> {
+	text.lines()
+		.filter_map(|line| {
+			let (index, rest) = line.trim_start().split_once(' ')?;
+			let index = index.parse().ok()?;
+			let (_, description) = rest.split_once("]: ")?;
+			let (driver, short_name) = description.split_once(" - ").unwrap_or((description, description));
+			Some(AlsaCard {
+				index,
+				driver: driver.trim().to_string(),
+				short_name: short_name.trim().to_string(),
+			})
+		})
+		.collect()
 }
@@ -0,0 +1,216 @@
+//! Recurring schedules from crontab entries and systemd `OnCalendar=` expressions, and the next
+//! time one fires.
+//!
+//! Times are naive local times. Calendar expressions with a time zone suffix and the `~` syntax for
+//! days counted from the end of the month are rejected rather than approximated.
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// How far `next_after` looks ahead; February 29th falls on any given weekday within 28 years.
+const HORIZON_DAYS: i64 = 28 * 366;
+
+const MONTHS: &[&str] = &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+/// Cron numbers weekdays from Sunday, as 0 or 7.
+const CRON_WEEKDAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+/// systemd and chrono number them from Monday.
+const WEEKDAYS: &[&str] = &["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// The times a schedule fires at, as bit masks of the seconds, minutes, hours, days of the month
+/// (bit 1 for the 1st), months (bit 1 for January) and weekdays (bit 0 for Monday) that match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+	seconds: u64,
+	minutes: u64,
+	hours: u64,
+	days: u64,
+	months: u64,
+	weekdays: u64,
+	/// Every year when None.
+	years: Option<Vec<u32>>,
+	/// Cron runs a job on either a matching day of the month or a matching weekday when both
+	/// fields are restricted.
+	either_day: bool,
+}
+
+impl Schedule {
+	/// Parses the five time fields of a crontab entry, or one of the `@daily` style shorthands.
+	/// `@reboot` is an error, as it has no next run.
+	pub fn from_cron(fields: &str) -> Result<Self> {
+		let fields = match fields.trim() {
+			"@yearly" | "@annually" => "0 0 1 1 *",
+			"@monthly" => "0 0 1 * *",
+			"@weekly" => "0 0 * * 0",
+			"@daily" | "@midnight" => "0 0 * * *",
+			"@hourly" => "0 * * * *",
+			"@reboot" => bail!("@reboot runs once at startup"),
+			fields if fields.starts_with('@') => bail!("Unknown cron shorthand {}", fields),
+			fields => fields,
+		};
+		let [minute, hour, day, month, weekday] = fields.split_whitespace().collect::<Vec<_>>()[..] else {
+			bail!("Expected five cron fields in '{}'", fields);
+		};
+
+		// Sunday is both 0 and 7; shift to count from Monday
+		let weekdays: Vec<u32> = parse_values(weekday, 0, 7, "-", CRON_WEEKDAYS, 0)?
+			.into_iter()
+			.map(|day| (day + 6) % 7)
+			.collect();
+
+		Ok(Schedule {
+			seconds: 1,
+			minutes: mask(&parse_values(minute, 0, 59, "-", &[], 0)?),
+			hours: mask(&parse_values(hour, 0, 23, "-", &[], 0)?),
+			days: mask(&parse_values(day, 1, 31, "-", &[], 0)?),
+			months: mask(&parse_values(month, 1, 12, "-", MONTHS, 1)?),
+			weekdays: mask(&weekdays),
+			years: None,
+			either_day: !day.starts_with('*') && !weekday.starts_with('*'),
+		})
+	}
+
+	/// Parses a systemd calendar expression such as `Mon..Fri *-*-* 06:00`, `*:0/15` or `weekly`.
+	pub fn from_calendar(expression: &str) -> Result<Self> {
+		let expression = match expression.trim().to_ascii_lowercase().as_str() {
+			"minutely" => "*-*-* *:*:00".to_string(),
+			"hourly" => "*-*-* *:00:00".to_string(),
+			"daily" => "*-*-* 00:00:00".to_string(),
+			"weekly" => "Mon *-*-* 00:00:00".to_string(),
+			"monthly" => "*-*-01 00:00:00".to_string(),
+			"yearly" | "annually" => "*-01-01 00:00:00".to_string(),
+			"quarterly" => "*-01,04,07,10-01 00:00:00".to_string(),
+			"semiannually" => "*-01,07-01 00:00:00".to_string(),
+			_ => expression.trim().to_string(),
+		};
+
+		let mut schedule = Schedule {
+			seconds: 1,
+			minutes: 1,
+			hours: 1,
+			days: mask(&(1..=31).collect::<Vec<_>>()),
+			months: mask(&(1..=12).collect::<Vec<_>>()),
+			weekdays: 0x7f,
+			years: None,
+			either_day: false,
+		};
+		let mut words = expression.split_whitespace().peekable();
+		if let Some(weekdays) = words.next_if(|word| word.starts_with(|c: char| c.is_ascii_alphabetic())) {
+			// Mon..Fri, and the Mon-Fri systemd also accepts
+			let weekdays = weekdays.replace('-', "..");
+			schedule.weekdays = mask(&parse_values(&weekdays, 0, 6, "..", WEEKDAYS, 0)?);
+		}
+		for word in words {
+			if word.contains(':') {
+				let parts: Vec<&str> = word.split(':').collect();
+				let (hour, minute, second) = match parts[..] {
+					[hour, minute] => (hour, minute, "00"),
+					[hour, minute, second] => (hour, minute, second),
+					_ => bail!("Invalid time '{}'", word),
+				};
+				schedule.hours = mask(&parse_values(hour, 0, 23, "..", &[], 0)?);
+				schedule.minutes = mask(&parse_values(minute, 0, 59, "..", &[], 0)?);
+				// Fractions of a second do not change which second it fires in
+				let second = second.split(',').map(whole_seconds).collect::<Vec<_>>().join(",");
+				schedule.seconds = mask(&parse_values(&second, 0, 59, "..", &[], 0)?);
+			} else if word.contains('-') {
+				if word.contains('~') {
+					bail!("Days from the end of the month are not supported in '{}'", word);
+				}
+				let parts: Vec<&str> = word.split('-').collect();
+				let (year, month, day) = match parts[..] {
+					[month, day] => ("*", month, day),
+					[year, month, day] => (year, month, day),
+					_ => bail!("Invalid date '{}'", word),
+				};
+				if year != "*" {
+					schedule.years = Some(parse_values(year, 1970, 2199, "..", &[], 0)?);
+				}
+				schedule.months = mask(&parse_values(month, 1, 12, "..", &[], 0)?);
+				schedule.days = mask(&parse_values(day, 1, 31, "..", &[], 0)?);
+			} else {
+				bail!("Unsupported calendar component '{}'", word);
+			}
+		}
+		Ok(schedule)
+	}
+
+	/// The first time after `after` the schedule fires, or None if it never does.
+	pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+		let mut time = after.with_nanosecond(0)? + Duration::seconds(1);
+		let horizon = after + Duration::days(HORIZON_DAYS);
+		while time <= horizon {
+			if !self.matches_date(time.date()) {
+				time = time.date().succ_opt()?.and_time(NaiveTime::MIN);
+			} else if self.hours & (1 << time.hour()) == 0 {
+				time = time.with_minute(0)?.with_second(0)? + Duration::hours(1);
+			} else if self.minutes & (1 << time.minute()) == 0 {
+				time = time.with_second(0)? + Duration::minutes(1);
+			} else if self.seconds & (1 << time.second()) == 0 {
+				time += Duration::seconds(1);
+			} else {
+				return Some(time);
+			}
+		}
+		None
+	}
+
+	fn matches_date(&self, date: NaiveDate) -> bool {
+		let day = self.days & (1 << date.day()) != 0;
+		let weekday = self.weekdays & (1 << date.weekday().num_days_from_monday()) != 0;
+		let day_matches = if self.either_day { day || weekday } else { day && weekday };
+		day_matches
+			&& self.months & (1 << date.month()) != 0
+			&& self.years.as_ref().is_none_or(|years| years.contains(&(date.year() as u32)))
+	}
+}
+
+/// Values of a comma separated list of `*`, single values, ranges and `/` repetitions between `min`
+/// and `max`. `names` are accepted for the values from `first_name` up.
+fn parse_values(field: &str, min: u32, max: u32, range: &str, names: &[&str], first_name: u32) -> Result<Vec<u32>> {
+	let value = |text: &str| -> Result<u32> {
+		let lower = text.to_ascii_lowercase();
+		// mon and monday, jan and january
+		let value = match names.iter().position(|name| lower.starts_with(name)) {
+			Some(index) => index as u32 + first_name,
+			None => text.parse().map_err(|_| anyhow!("Invalid value '{}' in '{}'", text, field))?,
+		};
+		if !(min..=max).contains(&value) {
+			bail!("{} is outside {}-{} in '{}'", value, min, max, field);
+		}
+		Ok(value)
+	};
+
+	let mut values = Vec::new();
+	for item in field.split(',') {
+		let (base, step) = match item.split_once('/') {
+			Some((base, step)) => (base, Some(step.parse::<u32>().map_err(|_| anyhow!("Invalid step in '{}'", field))?)),
+			None => (item, None),
+		};
+		let (start, end) = match (base, base.split_once(range)) {
+			("*", _) => (min, max),
+			(_, Some((start, end))) => (value(start)?, value(end)?),
+			// 0/15 repeats from 0 to the end of the range
+			(single, None) => (value(single)?, if step.is_some() { max } else { value(single)? }),
+		};
+		if start > end || step == Some(0) {
+			bail!("Invalid range '{}'", item);
+		}
+		values.extend((start..=end).step_by(step.unwrap_or(1) as usize));
+	}
+	Ok(values)
+}
+
+/// `05.500000` -> `05`, leaving `..` ranges alone.
+fn whole_seconds(item: &str) -> String {
+	if item.contains("..") {
+		return item.to_string();
+	}
+	match item.split_once('/') {
+		Some((base, step)) => format!("{}/{}", base.split('.').next().unwrap_or(base), step.split('.').next().unwrap_or(step)),
+		None => item.split('.').next().unwrap_or(item).to_string(),
+	}
+}
+
+fn mask(values: &[u32]) -> u64 {
+	values.iter().fold(0, |bits, value| bits | 1 << value)
+}
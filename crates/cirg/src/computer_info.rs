@@ -1,16 +1,18 @@
+use crate::linux;
+use crate::report;
+use crate::smbios::{self, SmbiosTable};
+use crate::source::{CIMV2, DataSource, Hive, Platform, Row, Variant};
 use crate::{ComputerInfoExt, VariantExt};
 use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use winreg::RegKey;
-use winreg::enums::*;
-use wmi::Variant;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ComputerInfo {
     pub name: String,
     pub domain: String,
     pub manufacturer: String,
+    pub model: String,
     pub system_type: String,
     pub operating_system: OSInfo,
     pub bios: BIOSInfo,
@@ -32,13 +34,41 @@ pub struct BIOSInfo {
     pub manufacturer: String,
     pub version: String,
     pub release_date: chrono::NaiveDate,
+    /// System serial number, as printed on the case label.
+    pub serial_number: String,
+    pub uuid: String,
+    pub board: BoardInfo,
+    pub chassis: ChassisInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BoardInfo {
+    pub manufacturer: String,
+    pub product: String,
+    pub version: String,
+    pub serial_number: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ChassisInfo {
+    pub manufacturer: String,
+    pub chassis_type: String,
+    pub serial_number: String,
 }
 
 impl ComputerInfoExt for ComputerInfo {
-    fn fetch() -> Result<Self> {
-        let com = wmi::WMIConnection::new()?;
-        let results: Vec<HashMap<String, Variant>> =
-            com.raw_query(r#"select * from Win32_ComputerSystem"#)?;
+    fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+        match source.platform() {
+            Platform::Windows => Self::fetch_windows(source),
+            Platform::Linux => Self::fetch_linux(source),
+            platform => Err(platform.unsupported("Computer information")),
+        }
+    }
+}
+
+impl ComputerInfo {
+    fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+        let results = source.wmi_query(CIMV2, r#"select * from Win32_ComputerSystem"#)?;
         let data = results
             .first()
             .ok_or_else(|| anyhow!("No OS info query data found"))?;
@@ -51,19 +81,56 @@ impl ComputerInfoExt for ComputerInfo {
                 format!("{} (Workgroup)", data.get_string("Workgroup")?)
             },
             manufacturer: data.get_string("Manufacturer")?,
+            model: data.get_string("Model").unwrap_or_default(),
             system_type: data.get_string("SystemType")?,
-            operating_system: OSInfo::fetch()?,
-            bios: BIOSInfo::fetch()?,
+            operating_system: OSInfo::fetch_from(source)?,
+            bios: BIOSInfo::fetch_from(source)?,
+        })
+    }
+
+    fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+        let domain = linux::read_optional(source, "/proc/sys/kernel/domainname")
+            .filter(|domain| domain != "(none)")
+            .unwrap_or_else(|| "N/A".to_string());
+
+        let smbios = read_smbios(source);
+        let system = smbios.as_ref().and_then(|table| table.system());
+
+        Ok(ComputerInfo {
+            name: linux::read_value(source, "/proc/sys/kernel/hostname")?,
+            domain,
+            manufacturer: match &system {
+                Some(system) => or_na(&system.manufacturer),
+                None => read_dmi(source, "sys_vendor"),
+            },
+            model: match &system {
+                Some(system) => or_na(&system.product_name),
+                None => read_dmi(source, "product_name"),
+            },
+            system_type: format!("{}-based PC", linux::kernel_arch(source)),
+            operating_system: OSInfo::fetch_from(source)?,
+            bios: match &smbios {
+                Some(table) => BIOSInfo::from_smbios(table),
+                None => BIOSInfo::from_dmi_files(source),
+            },
         })
     }
 }
 
 impl ComputerInfoExt for OSInfo {
-    fn fetch() -> Result<Self> {
+    fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+        match source.platform() {
+            Platform::Windows => Self::fetch_windows(source),
+            Platform::Linux => Self::fetch_linux(source),
+            platform => Err(platform.unsupported("Operating system information")),
+        }
+    }
+}
+
+impl OSInfo {
+    fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
         let mut os_info = OSInfo::default();
-        let com = wmi::WMIConnection::new()?;
-        let results: Vec<HashMap<String, Variant>> =
-            com.raw_query(r#"select * from Win32_OperatingSystem"#)?;
+        let results = source.wmi_query(CIMV2, r#"select * from Win32_OperatingSystem"#)?;
         let data = results
             .first()
             .ok_or_else(|| anyhow!("No OS info query data found"))?;
@@ -71,17 +138,18 @@ impl ComputerInfoExt for OSInfo {
         os_info.name = data.get_string("Caption")?;
 
         // Get Windows version and build info from registry
-        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-        let cur_ver = hklm.open_subkey(r"SOFTWARE\Microsoft\Windows NT\CurrentVersion")?;
+        let cur_ver = source
+            .registry_key(Hive::LocalMachine, r"SOFTWARE\Microsoft\Windows NT\CurrentVersion")?
+            .values;
 
         // Get display version (e.g., "22H2", "23H2")
         os_info.version = cur_ver
-            .get_value("DisplayVersion")
+            .get_string("DisplayVersion")
             .unwrap_or_else(|_| data.get_string("Version").unwrap_or_default());
 
         // Get build lab (e.g., "19041.1.amd64fre.vb_release.191206-1406")
         os_info.build_lab = cur_ver
-            .get_value("BuildLabEx")
+            .get_string("BuildLabEx")
             .unwrap_or_else(|_| "N/A".to_string());
 
         os_info.architecture = data.get_string("OSArchitecture")?;
@@ -99,22 +167,81 @@ impl ComputerInfoExt for OSInfo {
         let uptime_duration = now.signed_duration_since(os_info.last_boot_date);
         os_info.uptime = uptime_duration.num_seconds() as u64;
 
-        let tz_data: Vec<HashMap<String, Variant>> =
-            com.raw_query(r#"select * from Win32_TimeZone"#)?;
+        let tz_data = source.wmi_query(CIMV2, r#"select * from Win32_TimeZone"#)?;
         let tz_info = tz_data
             .first()
             .ok_or_else(|| anyhow!("No timezone info query data found"))?;
         os_info.timezone = tz_info.get_string("Caption")?;
         Ok(os_info)
     }
+
+    fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+        let os_release = source
+            .read_to_string("/etc/os-release")
+            .or_else(|_| source.read_to_string("/usr/lib/os-release"))?;
+        let os_release = linux::parse_key_values(&os_release);
+        let field = |key: &str| os_release.get(key).filter(|v| !v.is_empty()).cloned();
+
+        let uptime: f64 = linux::read_value(source, "/proc/uptime")?
+            .split_whitespace()
+            .next()
+            .ok_or_else(|| anyhow!("/proc/uptime is empty"))?
+            .parse()?;
+        let last_boot_date = DateTime::from_timestamp(Utc::now().timestamp() - uptime as i64, 0)
+            .ok_or_else(|| anyhow!("Uptime out of range: {}", uptime))?
+            .with_timezone(&Local)
+            .naive_local();
+
+        // There is no install timestamp on Linux; the installer log or the files written
+        // on first boot are the closest thing to it
+        let install_date = ["/var/log/installer", "/etc/machine-id", "/etc/hostname"]
+            .iter()
+            .find_map(|path| source.modified(path).ok())
+            .map(|date| date.with_timezone(&Local).naive_local())
+            .unwrap_or_else(|| {
+                report::warn("Could not determine the installation date");
+                chrono::NaiveDateTime::default()
+            });
+
+        let kernel = linux::read_value(source, "/proc/sys/kernel/osrelease")?;
+        let kernel_version = linux::read_optional(source, "/proc/sys/kernel/version").unwrap_or_default();
+        let arch = linux::kernel_arch(source);
+
+        Ok(OSInfo {
+            name: field("PRETTY_NAME")
+                .or_else(|| field("NAME"))
+                .unwrap_or_else(|| "Linux".to_string()),
+            version: field("VERSION_ID")
+                .or_else(|| field("BUILD_ID"))
+                .unwrap_or_else(|| "N/A".to_string()),
+            build_lab: format!("{} {}", kernel, kernel_version).trim_end().to_string(),
+            architecture: if arch.contains("64") || arch == "s390x" {
+                "64-bit".to_string()
+            } else {
+                "32-bit".to_string()
+            },
+            install_date,
+            last_boot_date,
+            uptime: uptime as u64,
+            timezone: fetch_timezone(source),
+        })
+    }
 }
 
 impl ComputerInfoExt for BIOSInfo {
-    fn fetch() -> Result<Self> {
+    fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+        match source.platform() {
+            Platform::Windows => Self::fetch_windows(source),
+            Platform::Linux => Self::fetch_linux(source),
+            platform => Err(platform.unsupported("BIOS information")),
+        }
+    }
+}
+
+impl BIOSInfo {
+    fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
         let mut bios_info: Self = BIOSInfo::default();
-        let com = wmi::WMIConnection::new()?;
-        let results: Vec<HashMap<String, Variant>> =
-            com.raw_query(r#"select * from Win32_BIOS"#)?;
+        let results = source.wmi_query(CIMV2, r#"select * from Win32_BIOS"#)?;
         let data = results
             .first()
             .ok_or_else(|| anyhow!("BIOS information not found in WMI query results"))?;
@@ -126,7 +253,140 @@ impl ComputerInfoExt for BIOSInfo {
             bios_info.release_date =
                 chrono::NaiveDate::parse_from_str(&release_date[..8], "%Y%m%d")?;
         }
+        bios_info.serial_number = data.get_string("SerialNumber").unwrap_or_default();
+
+        // The remaining SMBIOS structures are exposed as separate classes
+        if let Some(product) = query_first(source, "SELECT UUID FROM Win32_ComputerSystemProduct") {
+            bios_info.uuid = product.get_string("UUID").unwrap_or_default();
+        }
+        if let Some(board) = query_first(source, "SELECT * FROM Win32_BaseBoard") {
+            bios_info.board = BoardInfo {
+                manufacturer: board.get_string("Manufacturer").unwrap_or_default(),
+                product: board.get_string("Product").unwrap_or_default(),
+                version: board.get_string("Version").unwrap_or_default(),
+                serial_number: board.get_string("SerialNumber").unwrap_or_default(),
+            };
+        }
+        if let Some(enclosure) = query_first(source, "SELECT * FROM Win32_SystemEnclosure") {
+            let chassis_type = match enclosure.get("ChassisTypes") {
+                Some(Variant::Array(types)) => types.first().and_then(|code| match code {
+                    Variant::UI2(code) => u8::try_from(*code).ok(),
+                    Variant::I4(code) => u8::try_from(*code).ok(),
+                    _ => None,
+                }),
+                _ => None,
+            };
+            bios_info.chassis = ChassisInfo {
+                manufacturer: enclosure.get_string("Manufacturer").unwrap_or_default(),
+                chassis_type: smbios::chassis_type(chassis_type.unwrap_or(0)).to_string(),
+                serial_number: enclosure.get_string("SerialNumber").unwrap_or_default(),
+            };
+        }
 
         Ok(bios_info)
     }
+
+    fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+        Ok(match read_smbios(source) {
+            Some(table) => Self::from_smbios(&table),
+            None => Self::from_dmi_files(source),
+        })
+    }
+
+    /// Decodes the BIOS, system, baseboard and chassis structures of a raw SMBIOS table, e.g.
+    /// one loaded from a `dmidecode --dump-bin` file.
+    pub fn from_smbios(table: &SmbiosTable) -> Self {
+        let bios = table.bios().unwrap_or_default();
+        let system = table.system().unwrap_or_default();
+        let board = table.baseboard().unwrap_or_default();
+        let chassis = table.chassis().unwrap_or_default();
+
+        BIOSInfo {
+            manufacturer: or_na(&bios.vendor),
+            version: or_na(&bios.version),
+            release_date: bios.release_date.unwrap_or_default(),
+            serial_number: or_na(&system.serial_number),
+            uuid: system.uuid.unwrap_or_else(|| "N/A".to_string()),
+            board: BoardInfo {
+                manufacturer: or_na(&board.manufacturer),
+                product: or_na(&board.product),
+                version: or_na(&board.version),
+                serial_number: or_na(&board.serial_number),
+            },
+            chassis: ChassisInfo {
+                manufacturer: or_na(&chassis.manufacturer),
+                chassis_type: chassis.chassis_type.to_string(),
+                serial_number: or_na(&chassis.serial_number),
+            },
+        }
+    }
+
+    /// The kernel's decoded copy of the same fields. Serial numbers and the UUID are only
+    /// readable by root, like the raw table.
+    fn from_dmi_files(source: &dyn DataSource) -> Self {
+        let chassis_type = linux::read_optional(source, "/sys/class/dmi/id/chassis_type")
+            .and_then(|code| code.parse().ok())
+            .unwrap_or(0);
+
+        BIOSInfo {
+            manufacturer: read_dmi(source, "bios_vendor"),
+            version: read_dmi(source, "bios_version"),
+            release_date: linux::read_optional(source, "/sys/class/dmi/id/bios_date")
+                .and_then(|date| smbios::parse_date(&date))
+                .unwrap_or_default(),
+            serial_number: read_dmi(source, "product_serial"),
+            uuid: read_dmi(source, "product_uuid").to_uppercase(),
+            board: BoardInfo {
+                manufacturer: read_dmi(source, "board_vendor"),
+                product: read_dmi(source, "board_name"),
+                version: read_dmi(source, "board_version"),
+                serial_number: read_dmi(source, "board_serial"),
+            },
+            chassis: ChassisInfo {
+                manufacturer: read_dmi(source, "chassis_vendor"),
+                chassis_type: smbios::chassis_type(chassis_type).to_string(),
+                serial_number: read_dmi(source, "chassis_serial"),
+            },
+        }
+    }
+}
+
+/// First row of a query whose failure only degrades the section.
+fn query_first(source: &dyn DataSource, query: &str) -> Option<Row> {
+    source
+        .wmi_query(CIMV2, query)
+        .inspect_err(|e| report::warn(format!("{} failed: {}", query, e)))
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+fn read_smbios(source: &dyn DataSource) -> Option<SmbiosTable> {
+    SmbiosTable::read(source)
+        .inspect_err(|e| report::warn(format!("SMBIOS table unavailable, using /sys/class/dmi/id: {:#}", e)))
+        .ok()
+}
+
+fn or_na(value: &str) -> String {
+    if value.is_empty() {
+        "N/A".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Reads a field exported by the kernel's DMI driver. Machines without SMBIOS (most ARM
+/// boards) have no `/sys/class/dmi`, so a missing field is reported as "N/A".
+fn read_dmi(source: &dyn DataSource, field: &str) -> String {
+    linux::read_optional(source, &format!("/sys/class/dmi/id/{}", field)).unwrap_or_else(|| "N/A".to_string())
+}
+
+/// IANA zone name from the `/etc/localtime` symlink, or `/etc/timezone` on older Debian systems.
+fn fetch_timezone(source: &dyn DataSource) -> String {
+    source
+        .read_link("/etc/localtime")
+        .ok()
+        .and_then(|target| target.split_once("zoneinfo/").map(|(_, zone)| zone.to_string()))
+        .or_else(|| linux::read_optional(source, "/etc/timezone"))
+        .unwrap_or_else(|| "UTC".to_string())
 }
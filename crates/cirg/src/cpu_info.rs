@@ -1,9 +1,16 @@
+use crate::linux;
+use crate::report;
+use crate::smbios::SmbiosTable;
+use crate::source::{CIMV2, DataSource, Platform};
 use crate::{ComputerInfoExt, VariantExt};
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use sysinfo::System;
-use wmi::Variant;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+const SYSFS_CPU: &str = "/sys/devices/system/cpu";
+/// Time between the two reads of `/proc/stat` that the load is taken over.
+const LOAD_SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CpuInfo {
@@ -18,14 +25,23 @@ pub struct CpuInfo {
 	pub architecture: String,
 	pub virtualization: bool,
 	pub status: String,
-	pub load_pct: f32,
+	/// None when it cannot be sampled, such as from a captured tree.
+	pub load_pct: Option<f32>,
 }
 
 impl ComputerInfoExt for CpuInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
-		let results: Vec<HashMap<String, Variant>> =
-			com.raw_query("SELECT * FROM Win32_Processor")?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("CPU collection")),
+		}
+	}
+}
+
+impl CpuInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let results = source.wmi_query(CIMV2, "SELECT * FROM Win32_Processor")?;
 		let data = results
 			.first()
 			.ok_or_else(|| anyhow!("No CPU info found"))?;
@@ -41,13 +57,6 @@ impl ComputerInfoExt for CpuInfo {
 		}
 		.to_string();
 
-		// Use sysinfo for accurate CPU load (two refreshes with a delay for delta)
-		let mut sys = System::new();
-		sys.refresh_cpu_usage();
-		std::thread::sleep(std::time::Duration::from_millis(200));
-		sys.refresh_cpu_usage();
-		let load_pct = sys.global_cpu_usage();
-
 		Ok(CpuInfo {
 			name: data.get_string("Name").unwrap_or_default(),
 			cores: data.get_u32("NumberOfCores").unwrap_or(0),
@@ -62,7 +71,233 @@ impl ComputerInfoExt for CpuInfo {
 				.get_bool("VirtualizationFirmwareEnabled")
 				.unwrap_or(false),
 			status: data.get_string("Status").unwrap_or_default(),
-			load_pct,
+			load_pct: windows_load(source),
+		})
+	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let processors = parse_cpuinfo(&source.read_to_string("/proc/cpuinfo")?);
+		let first = processors
+			.first()
+			.ok_or_else(|| anyhow!("No processors listed in /proc/cpuinfo"))?;
+		let field = |key: &str| first.get(key).filter(|v| !v.is_empty()).cloned();
+
+		// x86 names the model per processor; ARM kernels often only report the SoC
+		let name = field("model name")
+			.or_else(|| field("Processor"))
+			.or_else(|| field("cpu model"))
+			.or_else(|| processors.iter().find_map(|p| p.get("Hardware").cloned()))
+			.unwrap_or_else(|| "Unknown".to_string());
+
+		let cpus = fetch_cpu_numbers(source);
+		let logical_processors = match cpus.len() {
+			0 => processors.iter().filter(|p| p.contains_key("processor")).count() as u32,
+			n => n as u32,
+		};
+
+		// Physical cores are the distinct (package, core) pairs; cpuinfo carries the same ids on x86
+		let mut cores = cpus
+			.iter()
+			.filter_map(|cpu| {
+				let topology = format!("{}/cpu{}/topology", SYSFS_CPU, cpu);
+				let package = linux::read_value(source, &format!("{}/physical_package_id", topology)).ok()?;
+				let core = linux::read_value(source, &format!("{}/core_id", topology)).ok()?;
+				Some((package, core))
+			})
+			.collect::<BTreeSet<_>>();
+		if cores.is_empty() {
+			cores = processors
+				.iter()
+				.filter_map(|p| Some((p.get("physical id")?.clone(), p.get("core id")?.clone())))
+				.collect();
+		}
+		let cores = match cores.len() {
+			0 => logical_processors,
+			n => n as u32,
+		};
+
+		let (max_clock_mhz, current_clock_mhz) = fetch_clocks(source, &cpus, &processors);
+		let (l2_cache_kb, l3_cache_kb) = fetch_cache_sizes(source, &cpus);
+
+		let flags = field("flags").or_else(|| field("Features")).unwrap_or_default();
+		let virtualization = flags.split_whitespace().any(|flag| flag == "vmx" || flag == "svm");
+
+		Ok(CpuInfo {
+			name,
+			cores,
+			logical_processors,
+			max_clock_mhz,
+			current_clock_mhz,
+			// Socket designations only exist in SMBIOS, which usually needs root to read
+			socket: SmbiosTable::read(source)
+				.ok()
+				.and_then(|table| table.processors().into_iter().find(|p| p.populated))
+				.map(|processor| processor.socket_designation)
+				.filter(|socket| !socket.is_empty())
+				.unwrap_or_else(|| "N/A".to_string()),
+			l2_cache_kb,
+			l3_cache_kb,
+			architecture: architecture_name(&linux::kernel_arch(source)),
+			virtualization,
+			status: "OK".to_string(),
+			load_pct: linux_load(source),
+		})
+	}
+}
+
+/// Load across all processors as the performance counters average it, so it replays like any
+/// other query.
+fn windows_load(source: &dyn DataSource) -> Option<f32> {
+	let query = "SELECT PercentProcessorTime FROM Win32_PerfFormattedData_PerfOS_Processor WHERE Name = '_Total'";
+	match source.wmi_query(CIMV2, query) {
+		Ok(results) => results.first().and_then(|data| data.get_u64("PercentProcessorTime").ok()).map(|pct| pct as f32),
+		Err(e) => {
+			report::warn(format!("CPU load counter unavailable: {}", e));
+			None
+		}
+	}
+}
+
+/// Busy share of the time all CPUs spent between two reads of `/proc/stat`. A captured tree or a
+/// replayed one holds a single snapshot, so only the live machine is sampled.
+fn linux_load(source: &dyn DataSource) -> Option<f32> {
+	if !source.is_live() {
+		report::warn("CPU load is only sampled on the live machine");
+		return None;
+	}
+	let read = || source.read_to_string("/proc/stat").ok().and_then(|text| cpu_times(&text));
+	let Some((busy_before, total_before)) = read() else {
+		report::warn("CPU load unavailable without a readable /proc/stat");
+		return None;
+	};
+	std::thread::sleep(LOAD_SAMPLE_INTERVAL);
+	let (busy_after, total_after) = read()?;
+	let total = total_after.saturating_sub(total_before);
+	(total > 0).then(|| busy_after.saturating_sub(busy_before) as f32 / total as f32 * 100.0)
+}
+
+/// Busy and total ticks of the aggregate `cpu` line of `/proc/stat`. Guest time is already part of
+/// user time, so only the first eight columns count.
+fn cpu_times(stat: &str) -> Option<(u64, u64)> {
+	let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+	let ticks: Vec<u64> = line.split_whitespace().skip(1).take(8).filter_map(|tick| tick.parse().ok()).collect();
+	// Idle and iowait
+	let idle = ticks.get(3)? + ticks.get(4).unwrap_or(&0);
+	let total: u64 = ticks.iter().sum();
+	Some((total.saturating_sub(idle), total))
+}
+
+/// Splits `/proc/cpuinfo` into its blank-line separated blocks of `key : value` pairs.
+fn parse_cpuinfo(text: &str) -> Vec<BTreeMap<String, String>> {
+	text.split("\n\n")
+		.map(|block| {
+			block
+				.lines()
+				.filter_map(|line| line.split_once(':'))
+				.map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+				.collect::<BTreeMap<_, _>>()
 		})
+		.filter(|block| !block.is_empty())
+		.collect()
+}
+
+/// Online CPU numbers, falling back to the `cpuN` directories when `online` is missing.
+fn fetch_cpu_numbers(source: &dyn DataSource) -> Vec<u32> {
+	if let Some(online) = linux::read_optional(source, &format!("{}/online", SYSFS_CPU)) {
+		return linux::parse_cpu_list(&online);
+	}
+	source
+		.read_dir(SYSFS_CPU)
+		.unwrap_or_default()
+		.iter()
+		.filter_map(|name| name.strip_prefix("cpu")?.parse().ok())
+		.collect()
+}
+
+/// Highest maximum and average current frequency across CPUs, in MHz. Hybrid parts report a
+/// different maximum per core type, so the fastest core is used like `MaxClockSpeed` does.
+fn fetch_clocks(source: &dyn DataSource, cpus: &[u32], processors: &[BTreeMap<String, String>]) -> (u32, u32) {
+	let read_khz = |cpu: u32, file: &str| {
+		linux::read_value(source, &format!("{}/cpu{}/cpufreq/{}", SYSFS_CPU, cpu, file))
+			.ok()?
+			.parse::<u64>()
+			.ok()
+	};
+	let average = |values: Vec<u64>| match values.len() {
+		0 => None,
+		n => Some((values.iter().sum::<u64>() / n as u64) as u32),
+	};
+
+	let max_khz = cpus.iter().filter_map(|&cpu| read_khz(cpu, "cpuinfo_max_freq")).max();
+	let current_khz = average(cpus.iter().filter_map(|&cpu| read_khz(cpu, "scaling_cur_freq")).collect());
+
+	// Virtual machines usually have no cpufreq driver; cpuinfo still has the nominal clock
+	let cpuinfo_mhz = average(
+		processors
+			.iter()
+			.filter_map(|p| p.get("cpu MHz")?.parse::<f64>().ok())
+			.map(|mhz| mhz.round() as u64)
+			.collect(),
+	);
+	let current_mhz = current_khz.map(|khz| khz / 1000).or(cpuinfo_mhz).unwrap_or(0);
+	let max_mhz = match max_khz {
+		Some(khz) => (khz / 1000) as u32,
+		None => {
+			report::warn("No cpufreq data, using the current clock as the maximum");
+			current_mhz
+		}
+	};
+
+	(max_mhz, current_mhz)
+}
+
+/// Total L2 and L3 size in KB. Each cache instance is listed under every CPU that shares it,
+/// so instances are counted once per distinct set of sharing CPUs.
+fn fetch_cache_sizes(source: &dyn DataSource, cpus: &[u32]) -> (u32, u32) {
+	let mut instances = BTreeMap::new();
+	for cpu in cpus {
+		let cache = format!("{}/cpu{}/cache", SYSFS_CPU, cpu);
+		for index in source.read_dir(&cache).unwrap_or_default() {
+			if !index.starts_with("index") {
+				continue;
+			}
+			let read = |file: &str| linux::read_optional(source, &format!("{}/{}/{}", cache, index, file));
+			let (Some(level), Some(size)) = (read("level"), read("size").and_then(|s| parse_cache_size(&s))) else {
+				continue;
+			};
+			if read("type").as_deref() == Some("Instruction") {
+				continue;
+			}
+			let shared = read("shared_cpu_list").unwrap_or_else(|| cpu.to_string());
+			instances.insert((level, shared), size);
+		}
+	}
+
+	let total = |level: &str| instances.iter().filter(|((l, _), _)| l == level).map(|(_, size)| size).sum();
+	(total("2"), total("3"))
+}
+
+/// Parses sysfs cache sizes such as `512K` or `32M` into KB.
+fn parse_cache_size(size: &str) -> Option<u32> {
+	let size = size.trim();
+	if let Some(kb) = size.strip_suffix('K') {
+		kb.parse().ok()
+	} else if let Some(mb) = size.strip_suffix('M') {
+		mb.parse::<u32>().ok().map(|mb| mb * 1024)
+	} else {
+		size.parse::<u32>().ok().map(|bytes| bytes / 1024)
+	}
+}
+
+/// Maps `uname -m` onto the names `Win32_Processor.Architecture` decodes to.
+fn architecture_name(arch: &str) -> String {
+	match arch {
+		"x86_64" => "x64",
+		"i386" | "i486" | "i586" | "i686" => "x86",
+		"aarch64" | "arm64" => "ARM64",
+		"ia64" => "ia64",
+		arch if arch.starts_with("arm") => "ARM",
+		arch => arch,
 	}
+	.to_string()
 }
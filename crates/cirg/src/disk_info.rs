@@ -1,8 +1,14 @@
-use crate::{ComputerInfoExt, VariantExt};
+use crate::linux::{read_optional, read_value};
+use crate::source::{CIMV2, DataSource, Platform};
+use crate::{ComputerInfoExt, VariantExt, report};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use wmi::Variant;
+use std::collections::{HashMap, HashSet};
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Filesystems listed as `nodev` in `/proc/filesystems` that still live on a local disk.
+const DISK_BACKED_NODEV: &[&str] = &["zfs"];
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DiskInfo {
@@ -18,6 +24,7 @@ pub struct PhysicalDisk {
 	pub disk_type: String,
 	pub size_gb: f64,
 	pub status: String,
+	pub removable: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -32,14 +39,21 @@ pub struct LogicalDisk {
 }
 
 impl ComputerInfoExt for DiskInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Disk collection")),
+		}
+	}
+}
 
+impl DiskInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
 		// Query MSFT_PhysicalDisk for disk type (SSD/HDD), keyed by serial and device id
-		let (serial_map, devid_map) = Self::fetch_msft_disk_types();
+		let (serial_map, devid_map) = Self::fetch_msft_disk_types(source);
 
-		let phys_results: Vec<HashMap<String, Variant>> =
-			com.raw_query("SELECT * FROM Win32_DiskDrive")?;
+		let phys_results = source.wmi_query(CIMV2, "SELECT * FROM Win32_DiskDrive")?;
 		let physical_disks = phys_results
 			.iter()
 			.map(|data| {
@@ -61,43 +75,31 @@ impl ComputerInfoExt for DiskInfo {
 					.cloned()
 					.unwrap_or_else(|| Self::guess_disk_type(&model));
 
+				let media_type = data.get_string("MediaType").unwrap_or_default();
 				PhysicalDisk {
 					model,
 					interface_type: data.get_string("InterfaceType").unwrap_or_default(),
-					media_type: data.get_string("MediaType").unwrap_or_default(),
+					removable: media_type.starts_with("Removable"),
+					media_type,
 					disk_type,
-					size_gb: size_bytes as f64 / (1024.0 * 1024.0 * 1024.0),
+					size_gb: size_bytes as f64 / GB,
 					status: data.get_string("Status").unwrap_or_default(),
 				}
 			})
 			.collect();
 
-		let log_results: Vec<HashMap<String, Variant>> =
-			com.raw_query("SELECT * FROM Win32_LogicalDisk WHERE DriveType=3")?;
+		let log_results =
+			source.wmi_query(CIMV2, "SELECT * FROM Win32_LogicalDisk WHERE DriveType=3")?;
 		let logical_disks = log_results
 			.iter()
 			.map(|data| {
-				let total = data.get_u64("Size").unwrap_or(0) as f64;
-				let free = data.get_u64("FreeSpace").unwrap_or(0) as f64;
-				let used = total - free;
-				let total_gb = total / (1024.0 * 1024.0 * 1024.0);
-				let free_gb = free / (1024.0 * 1024.0 * 1024.0);
-				let used_gb = used / (1024.0 * 1024.0 * 1024.0);
-				let usage_pct = if total > 0.0 {
-					(used / total) * 100.0
-				} else {
-					0.0
-				};
-
-				LogicalDisk {
-					device_id: data.get_string("DeviceID").unwrap_or_default(),
-					volume_name: data.get_string("VolumeName").unwrap_or_default(),
-					file_system: data.get_string("FileSystem").unwrap_or_default(),
-					total_gb,
-					free_gb,
-					used_gb,
-					usage_pct,
-				}
+				LogicalDisk::new(
+					data.get_string("DeviceID").unwrap_or_default(),
+					data.get_string("VolumeName").unwrap_or_default(),
+					data.get_string("FileSystem").unwrap_or_default(),
+					data.get_u64("Size").unwrap_or(0),
+					data.get_u64("FreeSpace").unwrap_or(0),
+				)
 			})
 			.collect();
 
@@ -106,21 +108,198 @@ impl ComputerInfoExt for DiskInfo {
 			logical_disks,
 		})
 	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let physical_disks = source
+			.read_dir("/sys/block")?
+			.iter()
+			.filter_map(|name| Self::read_block_device(source, name))
+			.collect();
+
+		Ok(DiskInfo {
+			physical_disks,
+			logical_disks: Self::read_mounts(source)?,
+		})
+	}
+
+	/// Describes `/sys/block/<name>`, or `None` for loop, RAM, device-mapper and optical devices
+	/// and empty drives, none of which Windows lists as disk drives either.
+	fn read_block_device(source: &dyn DataSource, name: &str) -> Option<PhysicalDisk> {
+		let dir = format!("/sys/block/{}", name);
+		// The link target is the device's place in the bus hierarchy, e.g.
+		// ../devices/pci0000:00/0000:00:14.0/usb2/2-1/.../block/sdb
+		let device_path = source.read_link(&dir).unwrap_or_default();
+		if device_path.contains("/virtual/") {
+			return None;
+		}
+		// SCSI peripheral type 5 is a CD/DVD drive
+		if read_optional(source, &format!("{}/device/type", dir)).as_deref() == Some("5") {
+			return None;
+		}
+		let sectors: u64 = read_optional(source, &format!("{}/size", dir))?.parse().ok()?;
+		if sectors == 0 {
+			return None;
+		}
+
+		let model = read_optional(source, &format!("{}/device/model", dir)).unwrap_or_else(|| name.to_string());
+		let model = match read_optional(source, &format!("{}/device/vendor", dir)) {
+			// libata reports every SATA disk with the vendor "ATA", virtio-blk the PCI vendor id
+			Some(vendor) if vendor != "ATA" && !vendor.starts_with("0x") && !model.starts_with(&vendor) => {
+				format!("{} {}", vendor, model)
+			}
+			_ => model,
+		};
+
+		let interface_type = if device_path.contains("/usb") {
+			"USB"
+		} else if name.starts_with("nvme") {
+			"NVMe"
+		} else if name.starts_with("mmcblk") {
+			"MMC"
+		} else if device_path.contains("/virtio") {
+			"VirtIO"
+		} else if device_path.contains("/ata") {
+			"SATA"
+		} else {
+			"SCSI"
+		};
+
+		let removable = read_optional(source, &format!("{}/removable", dir)).as_deref() == Some("1");
+		let media_type = if removable {
+			"Removable Media"
+		} else if interface_type == "USB" {
+			"External hard disk media"
+		} else {
+			"Fixed hard disk media"
+		};
+
+		let disk_type = match read_optional(source, &format!("{}/queue/rotational", dir)).as_deref() {
+			Some("1") => "HDD".to_string(),
+			Some("0") => "SSD".to_string(),
+			_ => Self::guess_disk_type(&model),
+		};
+
+		// SCSI devices report "running", NVMe controllers "live"
+		let status = match read_optional(source, &format!("{}/device/state", dir)) {
+			Some(state) if state == "running" || state == "live" => "OK".to_string(),
+			Some(state) => state,
+			None => "Unknown".to_string(),
+		};
+
+		Some(PhysicalDisk {
+			model,
+			interface_type: interface_type.to_string(),
+			media_type: media_type.to_string(),
+			disk_type,
+			size_gb: (sectors * 512) as f64 / GB,
+			status,
+			removable,
+		})
+	}
+
+	/// Mounted disk filesystems from `/proc/self/mountinfo`, one entry per filesystem.
+	fn read_mounts(source: &dyn DataSource) -> Result<Vec<LogicalDisk>> {
+		let mountinfo = read_value(source, "/proc/self/mountinfo")?;
+		let nodev = Self::nodev_filesystems(source);
+		let labels = Self::volume_labels(source);
+
+		let mut seen = HashSet::new();
+		let mut disks = Vec::new();
+		for line in mountinfo.lines() {
+			// 36 35 98:0 /root /mnt rw,noatime master:1 - ext3 /dev/root rw,errors=continue
+			let Some((mount, filesystem)) = line.split_once(" - ") else {
+				continue;
+			};
+			let mount: Vec<&str> = mount.split(' ').collect();
+			let mut filesystem = filesystem.split(' ');
+			let (Some(fs_type), Some(device)) = (filesystem.next(), filesystem.next()) else {
+				continue;
+			};
+			if mount.len() < 5 {
+				continue;
+			}
+
+			let pseudo = match &nodev {
+				Some(nodev) => nodev.contains(fs_type) && !DISK_BACKED_NODEV.contains(&fs_type),
+				None => !device.starts_with("/dev/"),
+			};
+			// Snap and AppImage squashfs images are read-only and always full
+			if pseudo || fs_type == "squashfs" {
+				continue;
+			}
+			// Bind mounts and btrfs subvolumes mounted twice share the device number
+			if !seen.insert(mount[2]) {
+				continue;
+			}
+
+			let mount_point = unescape_mount(mount[4]);
+			let (total, available) = match source.filesystem_usage(&mount_point) {
+				Ok(usage) => (usage.total_bytes, usage.available_bytes),
+				Err(e) => {
+					report::warn(format!("Failed to stat {}: {}", mount_point, e));
+					(0, 0)
+				}
+			};
+
+			let device_name = source
+				.read_link(device)
+				.unwrap_or_else(|_| device.to_string())
+				.rsplit('/')
+				.next()
+				.unwrap_or_default()
+				.to_string();
+			let volume_name = labels.get(&device_name).cloned().unwrap_or_default();
+
+			disks.push(LogicalDisk::new(mount_point, volume_name, fs_type.to_string(), total, available));
+		}
+		Ok(disks)
+	}
+
+	/// Filesystem types the kernel marks `nodev`, i.e. not backed by a block device.
+	fn nodev_filesystems(source: &dyn DataSource) -> Option<HashSet<String>> {
+		let text = read_value(source, "/proc/filesystems").ok()?;
+		Some(
+			text.lines()
+				.filter_map(|line| line.strip_prefix("nodev"))
+				.map(|fs_type| fs_type.trim().to_string())
+				.collect(),
+		)
+	}
+
+	/// Block device name -> filesystem label, from the links udev keeps in `/dev/disk/by-label`.
+	fn volume_labels(source: &dyn DataSource) -> HashMap<String, String> {
+		let Ok(labels) = source.read_dir("/dev/disk/by-label") else {
+			return HashMap::new();
+		};
+		labels
+			.iter()
+			.filter_map(|label| {
+				let target = source.read_link(&format!("/dev/disk/by-label/{}", label)).ok()?;
+				let device = target.rsplit('/').next()?.to_string();
+				Some((device, unescape_udev(label)))
+			})
+			.collect()
+	}
 }
 
 impl DiskInfo {
 	/// Returns (serial_number -> disk_type, device_id -> disk_type) maps.
 	/// On failure, returns empty maps so callers fall back to heuristics.
-	fn fetch_msft_disk_types() -> (HashMap<String, String>, HashMap<String, String>) {
-		let Ok(com) =
-			wmi::WMIConnection::with_namespace_path(r"root\Microsoft\Windows\Storage")
-		else {
-			return (HashMap::new(), HashMap::new());
-		};
-		let Ok(results): Result<Vec<HashMap<String, Variant>>, _> = com
-			.raw_query("SELECT MediaType, SerialNumber, DeviceId FROM MSFT_PhysicalDisk")
-		else {
-			return (HashMap::new(), HashMap::new());
+	fn fetch_msft_disk_types(
+		source: &dyn DataSource,
+	) -> (HashMap<String, String>, HashMap<String, String>) {
+		let results = match source.wmi_query(
+			r"root\Microsoft\Windows\Storage",
+			"SELECT MediaType, SerialNumber, DeviceId FROM MSFT_PhysicalDisk",
+		) {
+			Ok(results) => results,
+			Err(e) => {
+				report::warn(format!(
+					"MSFT_PhysicalDisk query failed, disk types guessed from model names: {}",
+					e
+				));
+				return (HashMap::new(), HashMap::new());
+			}
 		};
 
 		let mut serial_map = HashMap::new();
@@ -159,3 +338,55 @@ impl DiskInfo {
 		}
 	}
 }
+
+impl LogicalDisk {
+	fn new(device_id: String, volume_name: String, file_system: String, total: u64, free: u64) -> Self {
+		let used = total.saturating_sub(free);
+		let usage_pct = if total > 0 {
+			(used as f64 / total as f64) * 100.0
+		} else {
+			0.0
+		};
+
+		LogicalDisk {
+			device_id,
+			volume_name,
+			file_system,
+			total_gb: total as f64 / GB,
+			free_gb: free as f64 / GB,
+			used_gb: used as f64 / GB,
+			usage_pct,
+		}
+	}
+}
+
+/// Undoes the octal escapes mountinfo uses for spaces, tabs, newlines and backslashes.
+pub(crate) fn unescape_mount(path: &str) -> String {
+	unescape(path, "\\", 3, 8)
+}
+
+/// Undoes udev's `\xNN` escapes in link names, e.g. `My\x20Disk`.
+fn unescape_udev(name: &str) -> String {
+	unescape(name, "\\x", 2, 16)
+}
+
+fn unescape(text: &str, prefix: &str, digits: usize, radix: u32) -> String {
+	let mut bytes = Vec::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(index) = rest.find(prefix) {
+		bytes.extend_from_slice(&rest.as_bytes()[..index]);
+		let start = index + prefix.len();
+		match rest.get(start..start + digits).and_then(|code| u8::from_str_radix(code, radix).ok()) {
+			Some(byte) => {
+				bytes.push(byte);
+				rest = &rest[start + digits..];
+			}
+			None => {
+				bytes.extend_from_slice(prefix.as_bytes());
+				rest = &rest[start..];
+			}
+		}
+	}
+	bytes.extend_from_slice(rest.as_bytes());
+	String::from_utf8_lossy(&bytes).into_owned()
+}
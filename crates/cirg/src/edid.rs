@@ -0,0 +1,473 @@
+//! Decoder for EDID 1.3/1.4 base blocks and CTA-861 extension blocks, as read from
+//! `/sys/class/drm/*/edid` on Linux or `WmiMonitorDescriptorMethods` on Windows. Offsets follow
+//! VESA E-EDID Release A2 and CTA-861-H.
+
+use anyhow::{Result, bail};
+
+const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+const BLOCK_LEN: usize = 128;
+const CTA_EXTENSION: u8 = 0x02;
+
+/// Everything a monitor describes about itself in its EDID.
+#[derive(Debug, Clone, Default)]
+pub struct Edid {
+	/// `(version, revision)`, e.g. `(1, 4)`.
+	pub version: (u8, u8),
+	/// Three-letter PNP id, e.g. `DEL`.
+	pub manufacturer_id: String,
+	pub product_code: u16,
+	/// Binary serial number, when the monitor sets one.
+	pub serial_number: Option<u32>,
+	/// Serial number string descriptor, which most monitors use instead.
+	pub serial_string: Option<String>,
+	/// Display product name descriptor.
+	pub name: Option<String>,
+	pub week: Option<u8>,
+	pub year: u16,
+	/// The year is the model year rather than the year of manufacture.
+	pub model_year: bool,
+	pub digital: bool,
+	/// Bits per primary color, declared by EDID 1.4 or implied by HDMI deep color support.
+	pub bit_depth: Option<u8>,
+	/// Video interface, e.g. `DisplayPort` or `HDMI`.
+	pub interface: Option<&'static str>,
+	/// Screen size in centimetres from the basic display parameters.
+	pub size_cm: Option<(u8, u8)>,
+	/// Image size in millimetres from the preferred detailed timing, which is more precise.
+	pub size_mm: Option<(u16, u16)>,
+	/// First detailed timing, the monitor's native mode.
+	pub preferred: Option<Timing>,
+	/// Every mode the monitor lists, largest first, without duplicates.
+	pub modes: Vec<Timing>,
+	/// HDR transfer functions, e.g. `HDR10` or `HLG`.
+	pub hdr_formats: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timing {
+	pub width: u32,
+	pub height: u32,
+	pub refresh_rate: u32,
+	pub interlaced: bool,
+}
+
+impl std::fmt::Display for Timing {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let scan = if self.interlaced { "i" } else { "" };
+		write!(f, "{}x{}{}@{}", self.width, self.height, scan, self.refresh_rate)
+	}
+}
+
+impl Edid {
+	/// Decodes a base block and any extension blocks that follow it. Extensions that are missing
+	/// or fail their checksum are skipped; a bad base block is an error.
+	pub fn parse(data: &[u8]) -> Result<Self> {
+		let Some(base) = data.get(..BLOCK_LEN) else {
+			bail!("EDID is {} bytes, shorter than a base block", data.len());
+		};
+		if base[..8] != HEADER {
+			bail!("EDID header is missing");
+		}
+		if !checksum_ok(base) {
+			bail!("EDID base block checksum mismatch");
+		}
+
+		let mut edid = Edid {
+			version: (base[18], base[19]),
+			manufacturer_id: manufacturer_id(u16::from_be_bytes([base[8], base[9]])),
+			product_code: u16::from_le_bytes([base[10], base[11]]),
+			serial_number: Some(u32::from_le_bytes([base[12], base[13], base[14], base[15]])).filter(|&s| s != 0),
+			..Edid::default()
+		};
+
+		// Week 0xFF marks a model year; 0 means the week is not given
+		match base[16] {
+			0xFF => edid.model_year = true,
+			0 => {}
+			week => edid.week = Some(week),
+		}
+		edid.year = 1990 + u16::from(base[17]);
+
+		let input = base[20];
+		edid.digital = input & 0x80 != 0;
+		if !edid.digital {
+			edid.interface = Some("VGA");
+		} else if edid.version >= (1, 4) {
+			edid.bit_depth = match (input >> 4) & 0x07 {
+				1 => Some(6),
+				2 => Some(8),
+				3 => Some(10),
+				4 => Some(12),
+				5 => Some(14),
+				6 => Some(16),
+				_ => None,
+			};
+			edid.interface = match input & 0x0F {
+				1 => Some("DVI"),
+				2 | 3 => Some("HDMI"),
+				4 => Some("MDDI"),
+				5 => Some("DisplayPort"),
+				_ => None,
+			};
+		}
+		// Both zero means the size is unknown; one zero gives an aspect ratio in 1.4, not a size
+		if base[21] != 0 && base[22] != 0 {
+			edid.size_cm = Some((base[21], base[22]));
+		}
+
+		let mut modes = established_timings(&base[35..38]);
+		modes.extend(base[38..54].chunks(2).filter_map(|pair| standard_timing(pair[0], pair[1], edid.version)));
+
+		for descriptor in base[54..126].chunks(18) {
+			if let Some(timing) = detailed_timing(descriptor) {
+				if edid.preferred.is_none() {
+					edid.preferred = Some(timing);
+					edid.size_mm = image_size(descriptor);
+				}
+				modes.push(timing);
+				continue;
+			}
+			match descriptor[3] {
+				0xFF => edid.serial_string = descriptor_text(descriptor),
+				0xFC => edid.name = descriptor_text(descriptor),
+				_ => {}
+			}
+		}
+
+		let extensions = usize::from(base[126]);
+		for block in data[BLOCK_LEN..].chunks_exact(BLOCK_LEN).take(extensions) {
+			if block[0] == CTA_EXTENSION && checksum_ok(block) {
+				edid.parse_cta(block, &mut modes);
+			}
+		}
+
+		modes.sort_by(|a, b| b.cmp(a));
+		modes.dedup();
+		edid.modes = modes;
+		Ok(edid)
+	}
+
+	/// Diagonal in inches, from the image size or else the screen size.
+	pub fn diagonal_inches(&self) -> Option<f32> {
+		let (width_mm, height_mm) = match (self.size_mm, self.size_cm) {
+			(Some((w, h)), _) if w > 0 && h > 0 => (f32::from(w), f32::from(h)),
+			(_, Some((w, h))) => (f32::from(w) * 10.0, f32::from(h) * 10.0),
+			_ => return None,
+		};
+		Some((width_mm.hypot(height_mm) / 25.4 * 10.0).round() / 10.0)
+	}
+
+	/// Physical size in millimetres, from the image size or else the screen size.
+	pub fn physical_size_mm(&self) -> Option<(u32, u32)> {
+		match (self.size_mm, self.size_cm) {
+			(Some((w, h)), _) if w > 0 && h > 0 => Some((u32::from(w), u32::from(h))),
+			(_, Some((w, h))) => Some((u32::from(w) * 10, u32::from(h) * 10)),
+			_ => None,
+		}
+	}
+
+	fn parse_cta(&mut self, block: &[u8], modes: &mut Vec<Timing>) {
+		// Byte 2 is where the detailed timings start; the data block collection sits before them
+		let dtd_offset = usize::from(block[2]).min(BLOCK_LEN - 1);
+		if block[1] >= 3 && dtd_offset >= 4 {
+			let mut offset = 4;
+			while offset < dtd_offset {
+				let tag = block[offset] >> 5;
+				let len = usize::from(block[offset] & 0x1F);
+				let Some(payload) = block.get(offset + 1..offset + 1 + len) else {
+					break;
+				};
+				self.parse_data_block(tag, payload, modes);
+				offset += 1 + len;
+			}
+		}
+
+		if dtd_offset >= 4 {
+			for descriptor in block[dtd_offset..BLOCK_LEN - 1].chunks_exact(18) {
+				match detailed_timing(descriptor) {
+					Some(timing) => modes.push(timing),
+					None => break,
+				}
+			}
+		}
+	}
+
+	fn parse_data_block(&mut self, tag: u8, payload: &[u8], modes: &mut Vec<Timing>) {
+		match tag {
+			// Video data block: short video descriptors
+			2 => modes.extend(payload.iter().filter_map(|&svd| {
+				// VICs 1-64 use bit 7 as the native flag
+				let vic = if (129..=192).contains(&svd) { svd & 0x7F } else { svd };
+				cta_video_mode(vic)
+			})),
+			// Vendor-specific data block
+			3 if payload.len() >= 3 => {
+				let oui = u32::from_le_bytes([payload[0], payload[1], payload[2], 0]);
+				if oui == 0x000C03 {
+					// HDMI 1.4 VSDB
+					self.interface.get_or_insert("HDMI");
+					if self.bit_depth.is_none()
+						&& let Some(&flags) = payload.get(5)
+					{
+						self.bit_depth = Some(if flags & 0x40 != 0 {
+							16
+						} else if flags & 0x20 != 0 {
+							12
+						} else if flags & 0x10 != 0 {
+							10
+						} else {
+							8
+						});
+					}
+				}
+			}
+			// Extended tag: the first payload byte says which block it is
+			7 if !payload.is_empty() => match payload[0] {
+				// Vendor-specific video data block: Dolby
+				1 if payload.get(1..4) == Some(&[0x46, 0xD0, 0x00]) => self.add_hdr("Dolby Vision"),
+				// HDR static metadata: supported electro-optical transfer functions
+				6 if payload.len() >= 2 => {
+					if payload[1] & 0x04 != 0 {
+						self.add_hdr("HDR10");
+					}
+					if payload[1] & 0x08 != 0 {
+						self.add_hdr("HLG");
+					}
+				}
+				// HDR dynamic metadata, type 4 is HDR10+
+				7 if payload.get(1..3) == Some(&[0x04, 0x00]) => self.add_hdr("HDR10+"),
+				_ => {}
+			},
+			_ => {}
+		}
+	}
+
+	fn add_hdr(&mut self, format: &'static str) {
+		if !self.hdr_formats.contains(&format) {
+			self.hdr_formats.push(format);
+		}
+	}
+}
+
+fn checksum_ok(block: &[u8]) -> bool {
+	block.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) == 0
+}
+
+/// Three letters, five bits each, `A` = 1.
+fn manufacturer_id(code: u16) -> String {
+	[10, 5, 0]
+		.iter()
+		.map(|shift| match ((code >> shift) & 0x1F) as u8 {
+			letter @ 1..=26 => char::from(b'A' + letter - 1),
+			_ => '?',
+		})
+		.collect()
+}
+
+/// Bitmap of the VESA modes every monitor of the era supported.
+fn established_timings(bytes: &[u8]) -> Vec<Timing> {
+	const MODES: [(u32, u32, u32); 17] = [
+		(720, 400, 70),
+		(720, 400, 88),
+		(640, 480, 60),
+		(640, 480, 67),
+		(640, 480, 72),
+		(640, 480, 75),
+		(800, 600, 56),
+		(800, 600, 60),
+		(800, 600, 72),
+		(800, 600, 75),
+		(832, 624, 75),
+		(1024, 768, 87),
+		(1024, 768, 60),
+		(1024, 768, 70),
+		(1024, 768, 75),
+		(1280, 1024, 75),
+		(1152, 870, 75),
+	];
+	MODES
+		.iter()
+		.enumerate()
+		.filter(|(index, _)| bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+		.map(|(index, &(width, height, refresh_rate))| Timing {
+			width,
+			height,
+			refresh_rate,
+			// 1024x768 at 87 Hz is the interlaced 8514/A mode
+			interlaced: index == 11,
+		})
+		.collect()
+}
+
+fn standard_timing(first: u8, second: u8, version: (u8, u8)) -> Option<Timing> {
+	if first == 0x01 && second == 0x01 || first == 0 {
+		return None;
+	}
+	let width = (u32::from(first) + 31) * 8;
+	let height = match second >> 6 {
+		// EDID before 1.3 used 1:1 here
+		0 if version < (1, 3) => width,
+		0 => width * 10 / 16,
+		1 => width * 3 / 4,
+		2 => width * 4 / 5,
+		_ => width * 9 / 16,
+	};
+	Some(Timing {
+		width,
+		height,
+		refresh_rate: u32::from(second & 0x3F) + 60,
+		interlaced: false,
+	})
+}
+
+fn detailed_timing(d: &[u8]) -> Option<Timing> {
+	let pixel_clock = u64::from(u16::from_le_bytes([d[0], d[1]])) * 10_000;
+	if pixel_clock == 0 {
+		return None;
+	}
+	let h_active = u32::from(d[2]) | (u32::from(d[4] >> 4) << 8);
+	let h_blank = u32::from(d[3]) | (u32::from(d[4] & 0x0F) << 8);
+	let v_active = u32::from(d[5]) | (u32::from(d[7] >> 4) << 8);
+	let v_blank = u32::from(d[6]) | (u32::from(d[7] & 0x0F) << 8);
+	let interlaced = d[17] & 0x80 != 0;
+
+	let pixels_per_frame = u64::from(h_active + h_blank) * u64::from(v_active + v_blank);
+	let refresh_rate = (pixel_clock + pixels_per_frame / 2).checked_div(pixels_per_frame)? as u32;
+	Some(Timing {
+		width: h_active,
+		// Interlaced timings give the height of one field, so the rate is already the field rate
+		// that CTA-861 and DRM name interlaced modes by
+		height: if interlaced { v_active * 2 } else { v_active },
+		refresh_rate,
+		interlaced,
+	})
+}
+
+fn image_size(d: &[u8]) -> Option<(u16, u16)> {
+	let width = u16::from(d[12]) | (u16::from(d[14] >> 4) << 8);
+	let height = u16::from(d[13]) | (u16::from(d[14] & 0x0F) << 8);
+	(width > 0 && height > 0).then_some((width, height))
+}
+
+/// Text of a display descriptor, terminated by a line feed and padded with spaces.
+fn descriptor_text(d: &[u8]) -> Option<String> {
+	let text = &d[5..18];
+	let end = text.iter().position(|&b| b == 0x0A).unwrap_or(text.len());
+	let text = String::from_utf8_lossy(&text[..end]).trim().to_string();
+	(!text.is_empty()).then_some(text)
+}
+
+/// Modes of the CTA-861-H video identification codes 1-127 and 193-219. Pixel-repeated formats
+/// keep the doubled or quadrupled width the standard names them by, and codes that differ only in
+/// picture aspect ratio map to the same mode.
+pub fn cta_video_mode(vic: u8) -> Option<Timing> {
+	let (width, height, refresh_rate, interlaced) = match vic {
+		1 => (640, 480, 60, false),
+		2 | 3 => (720, 480, 60, false),
+		4 | 69 => (1280, 720, 60, false),
+		5 => (1920, 1080, 60, true),
+		6 | 7 => (1440, 480, 60, true),
+		8 | 9 => (1440, 240, 60, false),
+		10 | 11 => (2880, 480, 60, true),
+		12 | 13 => (2880, 240, 60, false),
+		14 | 15 => (1440, 480, 60, false),
+		16 | 76 => (1920, 1080, 60, false),
+		17 | 18 => (720, 576, 50, false),
+		19 | 68 => (1280, 720, 50, false),
+		// 39 is the same format with the 1250-line blanking of older European sets
+		20 | 39 => (1920, 1080, 50, true),
+		21 | 22 => (1440, 576, 50, true),
+		23 | 24 => (1440, 288, 50, false),
+		25 | 26 => (2880, 576, 50, true),
+		27 | 28 => (2880, 288, 50, false),
+		29 | 30 => (1440, 576, 50, false),
+		31 | 75 => (1920, 1080, 50, false),
+		32 | 72 => (1920, 1080, 24, false),
+		33 | 73 => (1920, 1080, 25, false),
+		34 | 74 => (1920, 1080, 30, false),
+		35 | 36 => (2880, 480, 60, false),
+		37 | 38 => (2880, 576, 50, false),
+		40 => (1920, 1080, 100, true),
+		41 | 70 => (1280, 720, 100, false),
+		42 | 43 => (720, 576, 100, false),
+		44 | 45 => (1440, 576, 100, true),
+		46 => (1920, 1080, 120, true),
+		47 | 71 => (1280, 720, 120, false),
+		48 | 49 => (720, 480, 120, false),
+		50 | 51 => (1440, 480, 120, true),
+		52 | 53 => (720, 576, 200, false),
+		54 | 55 => (1440, 576, 200, true),
+		56 | 57 => (720, 480, 240, false),
+		58 | 59 => (1440, 480, 240, true),
+		60 | 65 => (1280, 720, 24, false),
+		61 | 66 => (1280, 720, 25, false),
+		62 | 67 => (1280, 720, 30, false),
+		63 | 78 => (1920, 1080, 120, false),
+		64 | 77 => (1920, 1080, 100, false),
+		79 => (1680, 720, 24, false),
+		80 => (1680, 720, 25, false),
+		81 => (1680, 720, 30, false),
+		82 => (1680, 720, 50, false),
+		83 => (1680, 720, 60, false),
+		84 => (1680, 720, 100, false),
+		85 => (1680, 720, 120, false),
+		86 => (2560, 1080, 24, false),
+		87 => (2560, 1080, 25, false),
+		88 => (2560, 1080, 30, false),
+		89 => (2560, 1080, 50, false),
+		90 => (2560, 1080, 60, false),
+		91 => (2560, 1080, 100, false),
+		92 => (2560, 1080, 120, false),
+		93 | 103 => (3840, 2160, 24, false),
+		94 | 104 => (3840, 2160, 25, false),
+		95 | 105 => (3840, 2160, 30, false),
+		96 | 106 => (3840, 2160, 50, false),
+		97 | 107 => (3840, 2160, 60, false),
+		98 => (4096, 2160, 24, false),
+		99 => (4096, 2160, 25, false),
+		100 => (4096, 2160, 30, false),
+		101 => (4096, 2160, 50, false),
+		102 => (4096, 2160, 60, false),
+		108 | 109 => (1280, 720, 48, false),
+		110 => (1680, 720, 48, false),
+		111 | 112 => (1920, 1080, 48, false),
+		113 => (2560, 1080, 48, false),
+		114 | 116 => (3840, 2160, 48, false),
+		115 => (4096, 2160, 48, false),
+		117 | 119 => (3840, 2160, 100, false),
+		118 | 120 => (3840, 2160, 120, false),
+		121 => (5120, 2160, 24, false),
+		122 => (5120, 2160, 25, false),
+		123 => (5120, 2160, 30, false),
+		124 => (5120, 2160, 48, false),
+		125 => (5120, 2160, 50, false),
+		126 => (5120, 2160, 60, false),
+		127 => (5120, 2160, 100, false),
+		193 => (5120, 2160, 120, false),
+		194 | 202 => (7680, 4320, 24, false),
+		195 | 203 => (7680, 4320, 25, false),
+		196 | 204 => (7680, 4320, 30, false),
+		197 | 205 => (7680, 4320, 48, false),
+		198 | 206 => (7680, 4320, 50, false),
+		199 | 207 => (7680, 4320, 60, false),
+		200 | 208 => (7680, 4320, 100, false),
+		201 | 209 => (7680, 4320, 120, false),
+		210 => (10240, 4320, 24, false),
+		211 => (10240, 4320, 25, false),
+		212 => (10240, 4320, 30, false),
+		213 => (10240, 4320, 48, false),
+		214 => (10240, 4320, 50, false),
+		215 => (10240, 4320, 60, false),
+		216 => (10240, 4320, 100, false),
+		217 => (10240, 4320, 120, false),
+		218 => (4096, 2160, 100, false),
+		219 => (4096, 2160, 120, false),
+		_ => return None,
+	};
+	Some(Timing {
+		width,
+		height,
+		refresh_rate,
+		interlaced,
+	})
+}
@@ -1,29 +1,399 @@
+use crate::linux::{unquote, user_homes};
+use crate::source::{DataSource, Hive, Platform, Variant};
 use crate::ComputerInfoExt;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use winreg::enums::*;
-use winreg::RegKey;
+
+const SESSION_MANAGER_ENVIRONMENT: &str = r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment";
+/// Read by pam_env at login, as plain `NAME=value` lines without expansion.
+const ETC_ENVIRONMENT: &str = "/etc/environment";
+/// Sourced by login shells through /etc/profile.
+const PROFILE_D: &str = "/etc/profile.d";
+/// Read by systemd's environment generator for user managers, highest precedence first; a file
+/// masks files of the same name in later directories.
+const ENVIRONMENT_D: &[&str] = &[
+	"/etc/environment.d",
+	"/run/environment.d",
+	"/usr/local/lib/environment.d",
+	"/usr/lib/environment.d",
+];
+/// `DefaultEnvironment=` of the system manager, used when it cannot be asked for its environment.
+const SYSTEM_CONF: &str = "/etc/systemd/system.conf";
+const SYSTEM_CONF_D: &str = "/etc/systemd/system.conf.d";
+/// Source of the variables `systemctl show-environment` reports.
+const SYSTEMD_MANAGER: &str = "systemd manager";
+/// PATH the system manager sets before `DefaultEnvironment=`, as systemd is built by default.
+const SYSTEMD_DEFAULT_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin";
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct EnvironmentInfo {
+	/// The value each variable ends up with machine-wide.
 	pub variables: BTreeMap<String, String>,
+	/// Every definition in the order it is applied, so a later entry for the same name wins.
+	#[serde(default)]
+	pub entries: Vec<EnvironmentEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EnvironmentEntry {
+	pub name: String,
+	/// With `$NAME` references to earlier definitions expanded.
+	pub value: String,
+	/// File the definition is in, the registry key on Windows, or `systemd manager`.
+	pub source: String,
+	/// A later source sets the variable again for everyone this one applies to.
+	pub overridden: bool,
 }
 
 impl ComputerInfoExt for EnvironmentInfo {
-	fn fetch() -> Result<Self> {
-		let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-		let key = hklm.open_subkey(
-			r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment",
-		)?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Environment collection")),
+		}
+	}
+}
+
+impl EnvironmentInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let key = source.registry_key(Hive::LocalMachine, SESSION_MANAGER_ENVIRONMENT)?;
 
 		let mut variables = BTreeMap::new();
-		for result in key.enum_values() {
-			let (name, _) = result?;
-			let value: String = key.get_value(&name).unwrap_or_default();
+		let mut entries = Vec::new();
+		for (name, value) in key.values {
+			let value = match value {
+				Variant::String(s) => s,
+				_ => String::new(),
+			};
+			entries.push(EnvironmentEntry {
+				name: name.clone(),
+				value: value.clone(),
+				source: format!(r"HKLM\{}", SESSION_MANAGER_ENVIRONMENT),
+				overridden: false,
+			});
 			variables.insert(name, value);
 		}
 
-		Ok(EnvironmentInfo { variables })
+		Ok(EnvironmentInfo { variables, entries })
+	}
+
+	/// Sources in the order a graphical login applies them: the system manager's environment,
+	/// which user managers inherit, environment.d, then pam_env and the login shell's profile.d
+	/// scripts. Each user's `~/.config/environment.d` is searched first for that user, so its files
+	/// take their place in file name order and mask system files of the same name.
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let mut environment = Environment::default();
+		environment.apply(manager_environment(source), &EVERYONE);
+
+		let system = environment_d_files(source, ENVIRONMENT_D);
+		let users: Vec<(String, Vec<String>)> = user_homes(source)
+			.into_iter()
+			.map(|(user, home)| {
+				let own = format!("{}/.config/environment.d", home);
+				let directories: Vec<&str> = std::iter::once(own.as_str()).chain(ENVIRONMENT_D.iter().copied()).collect();
+				(user, environment_d_files(source, &directories))
+			})
+			.collect();
+		let file_name = |path: &str| path.rsplit('/').next().unwrap_or_default().to_string();
+		let mut files: BTreeMap<String, Vec<(Scope, &String)>> = BTreeMap::new();
+		for path in &system {
+			let masked_for = users.iter().filter(|(_, paths)| !paths.contains(path)).map(|(user, _)| user.clone()).collect();
+			files.entry(file_name(path)).or_default().push((Scope::Everyone(masked_for), path));
+		}
+		for (user, paths) in &users {
+			for path in paths.iter().filter(|path| !system.contains(path)) {
+				files.entry(file_name(path)).or_default().push((Scope::User(user.clone()), path));
+			}
+		}
+		for (scope, path) in files.into_values().flatten() {
+			let text = source.read_to_string(path).unwrap_or_default();
+			environment.apply(assignments(&text).into_iter().map(|(name, value)| (name, value, true, path.clone())), &scope);
+		}
+
+		if let Ok(text) = source.read_to_string(ETC_ENVIRONMENT) {
+			let definitions = assignments(&text).into_iter().map(|(name, value)| (name, value, false, ETC_ENVIRONMENT.to_string()));
+			environment.apply(definitions, &EVERYONE);
+		}
+		let scripts = source.read_dir(PROFILE_D).unwrap_or_default();
+		for name in scripts.iter().filter(|name| name.ends_with(".sh")) {
+			let path = format!("{}/{}", PROFILE_D, name);
+			let text = source.read_to_string(&path).unwrap_or_default();
+			environment.apply(exports(&text).into_iter().map(|(name, value, expand)| (name, value, expand, path.clone())), &EVERYONE);
+		}
+
+		Ok(environment.finish())
+	}
+}
+
+/// Who a definition applies to.
+#[derive(Clone, PartialEq)]
+enum Scope {
+	/// Everyone but the listed users, whose own environment.d file of the same name masks it.
+	Everyone(Vec<String>),
+	User(String),
+}
+
+const EVERYONE: Scope = Scope::Everyone(Vec::new());
+
+impl Scope {
+	/// Whether the definition applies to `user`, or machine-wide for None.
+	fn includes(&self, user: Option<&str>) -> bool {
+		match self {
+			Scope::Everyone(except) => user.is_none_or(|user| !except.iter().any(|name| name == user)),
+			Scope::User(owner) => user == Some(owner.as_str()),
+		}
+	}
+
+	/// Whether a later definition in this scope reaches everyone one in `earlier` applies to.
+	fn covers(&self, earlier: &Scope) -> bool {
+		match (self, earlier) {
+			(Scope::Everyone(except), Scope::Everyone(earlier_except)) => except.iter().all(|user| earlier_except.contains(user)),
+			(_, Scope::User(user)) => self.includes(Some(user)),
+			(Scope::User(_), Scope::Everyone(_)) => false,
+		}
+	}
+}
+
+/// Definitions in the order they are applied, each with who it applies to.
+#[derive(Default)]
+struct Environment {
+	entries: Vec<(Scope, EnvironmentEntry)>,
+}
+
+impl Environment {
+	/// Values as `user`, or everyone, sees them after the definitions so far.
+	fn values(&self, user: Option<&str>) -> BTreeMap<String, String> {
+		self.entries
+			.iter()
+			.filter(|(scope, _)| scope.includes(user))
+			.map(|(_, entry)| (entry.name.clone(), entry.value.clone()))
+			.collect()
+	}
+
+	/// Adds (name, value, whether `$NAME` references expand, source) definitions.
+	fn apply(&mut self, definitions: impl IntoIterator<Item = (String, String, bool, String)>, scope: &Scope) {
+		let user = match scope {
+			Scope::Everyone(_) => None,
+			Scope::User(user) => Some(user.as_str()),
+		};
+		for (name, value, expand, source) in definitions {
+			let value = if expand { expand_references(&value, &self.values(user)) } else { value };
+			self.entries.push((scope.clone(), EnvironmentEntry { name, value, source, overridden: false }));
+		}
+	}
+
+	fn finish(mut self) -> EnvironmentInfo {
+		// Machine-wide definitions override everyone's, a user's only their own
+		for index in 0..self.entries.len() {
+			let (scope, entry) = &self.entries[index];
+			let overridden = self.entries[index + 1..]
+				.iter()
+				.any(|(later_scope, later)| later.name == entry.name && later_scope.covers(scope));
+			self.entries[index].1.overridden = overridden;
+		}
+		EnvironmentInfo {
+			variables: self.values(None),
+			entries: self.entries.into_iter().map(|(_, entry)| entry).collect(),
+		}
+	}
+}
+
+/// The running system manager's environment, or the `DefaultEnvironment=` it was configured with
+/// when `systemctl` cannot be run.
+fn manager_environment(source: &dyn DataSource) -> Vec<(String, String, bool, String)> {
+	if let Ok(output) = source.command("systemctl", &["show-environment"])
+		&& output.status == Some(0)
+	{
+		return output
+			.stdout
+			.lines()
+			.filter_map(|line| line.split_once('='))
+			.map(|(name, value)| (name.to_string(), unescape_ansi_c(value), false, SYSTEMD_MANAGER.to_string()))
+			.collect();
+	}
+
+	let mut files = vec![SYSTEM_CONF.to_string()];
+	let drop_ins = source.read_dir(SYSTEM_CONF_D).unwrap_or_default();
+	files.extend(drop_ins.iter().filter(|name| name.ends_with(".conf")).map(|name| format!("{}/{}", SYSTEM_CONF_D, name)));
+	// The manager sets a PATH of its own, which only a DefaultEnvironment= PATH replaces
+	let mut definitions = vec![("PATH".to_string(), SYSTEMD_DEFAULT_PATH.to_string(), false, SYSTEMD_MANAGER.to_string())];
+	for path in files {
+		let text = source.read_to_string(&path).unwrap_or_default();
+		for line in text.lines() {
+			// DefaultEnvironment="VAR1=word1 word2" VAR2=word3 "VAR3=$word 5 6"
+			let Some(value) = line.trim().strip_prefix("DefaultEnvironment=") else {
+				continue;
+			};
+			for word in split_words(value) {
+				if let Some((name, value)) = word.split_once('=') {
+					definitions.push((name.to_string(), value.to_string(), false, path.clone()));
+				}
+			}
+		}
+	}
+	definitions
+}
+
+/// `*.conf` files of environment.d directories, ordered by file name, where a file hides any of
+/// the same name in a later directory.
+fn environment_d_files(source: &dyn DataSource, directories: &[&str]) -> Vec<String> {
+	let mut files = BTreeMap::new();
+	for directory in directories {
+		for name in source.read_dir(directory).unwrap_or_default() {
+			if name.ends_with(".conf") {
+				files.entry(name.clone()).or_insert_with(|| format!("{}/{}", directory, name));
+			}
+		}
+	}
+	files.into_values().collect()
+}
+
+/// `NAME=value` lines in file order, unlike `parse_key_values`, since a later line can refer to an
+/// earlier one.
+fn assignments(text: &str) -> Vec<(String, String)> {
+	text.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.filter_map(|line| line.split_once('='))
+		.map(|(name, value)| (name.trim().to_string(), unquote(value.trim()).to_string()))
+		.filter(|(name, _)| is_name(name))
+		.collect()
+}
+
+/// `export NAME=value` lines of a shell script, with whether the value is subject to expansion,
+/// which single quotes prevent. Conditions around them are not evaluated.
+fn exports(text: &str) -> Vec<(String, String, bool)> {
+	let mut exports = Vec::new();
+	for line in text.lines() {
+		let Some(assignment) = line.trim().strip_prefix("export ") else {
+			continue;
+		};
+		let Some((name, rest)) = assignment.trim_start().split_once('=') else {
+			continue;
+		};
+		if !is_name(name) {
+			continue;
+		}
+		let (value, expand) = match rest.chars().next() {
+			Some(quote @ ('"' | '\'')) => match rest[1..].find(quote) {
+				Some(end) => (&rest[1..end + 1], quote == '"'),
+				None => continue,
+			},
+			// An unquoted value ends at whitespace or a semicolon
+			_ => (rest.split([' ', '\t', ';']).next().unwrap_or_default(), true),
+		};
+		exports.push((name.to_string(), value.to_string(), expand));
+	}
+	exports
+}
+
+fn is_name(name: &str) -> bool {
+	!name.is_empty()
+		&& !name.starts_with(|c: char| c.is_ascii_digit())
+		&& name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Expands `$NAME`, `${NAME}`, `${NAME:-default}` and `${NAME:+alternative}`, the forms both shells
+/// and environment.d accept. Unset names expand to nothing and `\$` is a literal dollar sign.
+fn expand_references(value: &str, variables: &BTreeMap<String, String>) -> String {
+	let lookup = |name: &str| variables.get(name).filter(|value| !value.is_empty());
+	let mut expanded = String::new();
+	let mut rest = value;
+	while let Some(index) = rest.find(['$', '\\']) {
+		expanded.push_str(&rest[..index]);
+		let after = &rest[index + 1..];
+		if rest[index..].starts_with('\\') {
+			// Keep escapes other than \$ for the value as written
+			match after.strip_prefix('$') {
+				Some(tail) => {
+					expanded.push('$');
+					rest = tail;
+				}
+				None => {
+					expanded.push('\\');
+					rest = after;
+				}
+			}
+		} else if let Some(braced) = after.strip_prefix('{')
+			&& let Some(end) = braced.find('}')
+		{
+			let reference = &braced[..end];
+			let value = if let Some((name, default)) = reference.split_once(":-") {
+				lookup(name).map_or(default, String::as_str)
+			} else if let Some((name, alternative)) = reference.split_once(":+") {
+				if lookup(name).is_some() { alternative } else { "" }
+			} else {
+				lookup(reference).map_or("", String::as_str)
+			};
+			expanded.push_str(value);
+			rest = &braced[end + 1..];
+		} else {
+			let length = after.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(after.len());
+			if length == 0 {
+				expanded.push('$');
+			} else {
+				expanded.push_str(lookup(&after[..length]).map_or("", String::as_str));
+			}
+			rest = &after[length..];
+		}
+	}
+	expanded.push_str(rest);
+	expanded
+}
+
+/// Words of a systemd setting, where double or single quotes keep spaces in a word.
+fn split_words(value: &str) -> Vec<String> {
+	let mut words = Vec::new();
+	let mut word = String::new();
+	let mut quote = None;
+	let mut in_word = false;
+	for c in value.chars() {
+		match (quote, c) {
+			(Some(open), c) if c == open => quote = None,
+			(Some(_), c) => word.push(c),
+			(None, '"' | '\'') => {
+				quote = Some(c);
+				in_word = true;
+			}
+			(None, c) if c.is_whitespace() => {
+				if in_word {
+					words.push(std::mem::take(&mut word));
+					in_word = false;
+				}
+			}
+			(None, c) => {
+				word.push(c);
+				in_word = true;
+			}
+		}
+	}
+	if in_word {
+		words.push(word);
+	}
+	words
+}
+
+/// `systemctl show-environment` writes values with special characters as `$'...'` with C escapes.
+fn unescape_ansi_c(value: &str) -> String {
+	let Some(inner) = value.strip_prefix("$'").and_then(|value| value.strip_suffix('\'')) else {
+		return value.to_string();
+	};
+	let mut unescaped = String::new();
+	let mut chars = inner.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			unescaped.push(c);
+			continue;
+		}
+		match chars.next() {
+			Some('n') => unescaped.push('\n'),
+			Some('t') => unescaped.push('\t'),
+			Some('e') => unescaped.push('\x1b'),
+			Some(other) => unescaped.push(other),
+			None => unescaped.push('\\'),
+		}
 	}
+	unescaped
 }
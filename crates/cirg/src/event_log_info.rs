@@ -1,8 +1,19 @@
-use crate::ComputerInfoExt;
+use crate::journal::{self, Entry};
+use crate::linux::uid_range;
+use crate::source::{DataSource, Platform};
+use crate::{ComputerInfoExt, report};
 use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
-use std::os::windows::process::CommandExt;
-use std::process::Command;
+
+/// Events kept per log, newest first.
+const MAX_EVENTS: usize = 15;
+
+/// Persistent journals, then those kept in memory when `/var/log/journal` does not exist.
+const JOURNAL_DIRECTORIES: &[&str] = &["/var/log/journal", "/run/log/journal"];
+
+/// rsyslog's catch-all files on Debian and on Red Hat, for systems without a journal.
+const SYSLOG_FILES: &[&str] = &["/var/log/syslog.1", "/var/log/syslog", "/var/log/messages"];
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct EventLogInfo {
@@ -20,42 +31,232 @@ pub struct EventEntry {
 }
 
 impl ComputerInfoExt for EventLogInfo {
-	fn fetch() -> Result<Self> {
-		let now = chrono::Utc::now();
-		let yesterday = now - chrono::Duration::hours(24);
-		let time_filter = yesterday.format("%Y-%m-%dT%H:%M:%S").to_string();
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		Self::fetch_since(source, Utc::now() - Duration::hours(24))
+	}
+}
+
+impl EventLogInfo {
+	/// Critical, error and warning events logged since `since`; `fetch_from` looks back 24 hours.
+	pub fn fetch_since(source: &dyn DataSource, since: DateTime<Utc>) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source, since),
+			Platform::Linux => Self::fetch_linux(source, since),
+			platform => Err(platform.unsupported("Event log collection")),
+		}
+	}
+
+	fn fetch_windows(source: &dyn DataSource, since: DateTime<Utc>) -> Result<Self> {
+		let time_filter = since.format("%Y-%m-%dT%H:%M:%S").to_string();
 
 		Ok(EventLogInfo {
-			system_events: query_event_log("System", &time_filter),
-			application_events: query_event_log("Application", &time_filter),
+			system_events: query_event_log(source, "System", &time_filter),
+			application_events: query_event_log(source, "Application", &time_filter),
+		})
+	}
+}
+
+impl EventLogInfo {
+	fn fetch_linux(source: &dyn DataSource, since: DateTime<Utc>) -> Result<Self> {
+		let files = journal_files(source);
+		let entries = if files.is_empty() {
+			syslog_entries(source, since)
+		} else {
+			journal_entries(source, &files, since)
+		};
+
+		// Entries from human users' sessions are the closest thing to the Application log
+		let humans = uid_range(source);
+		let (application, system): (Vec<Entry>, Vec<Entry>) = entries.into_iter().partition(|entry| {
+			entry.field("_UID").and_then(|uid| uid.parse::<u32>().ok()).is_some_and(|uid| humans.contains(&uid))
+		});
+		Ok(EventLogInfo {
+			system_events: newest_events(system),
+			application_events: newest_events(application),
+		})
+	}
+}
+
+/// Journal files of every machine id under the journal directories, online and archived.
+fn journal_files(source: &dyn DataSource) -> Vec<String> {
+	let mut files = Vec::new();
+	for directory in JOURNAL_DIRECTORIES {
+		for machine in source.read_dir(directory).unwrap_or_default() {
+			let machine = format!("{}/{}", directory, machine);
+			for file in source.read_dir(&machine).unwrap_or_default() {
+				// journal~ files are ones journald found dirty at startup and renamed
+				if file.ends_with(".journal") || file.ends_with(".journal~") {
+					files.push(format!("{}/{}", machine, file));
+				}
+			}
+		}
+	}
+	files
+}
+
+fn journal_entries(source: &dyn DataSource, files: &[String], since: DateTime<Utc>) -> Vec<Entry> {
+	let since_usec = since.timestamp_micros().max(0) as u64;
+	let mut entries = Vec::new();
+	let mut failures = Vec::new();
+	for file in files {
+		// Archived files last written before the window are not worth reading in full
+		if source.modified(file).is_ok_and(|modified| modified < since) {
+			continue;
+		}
+		match source.read_file(file).and_then(|data| journal::read_entries(&data, since_usec)) {
+			Ok(file_entries) => entries.extend(file_entries),
+			Err(e) => failures.push(format!("{}: {}", file, e)),
+		}
+	}
+	if failures.len() < files.len() {
+		if !failures.is_empty() {
+			report::warn(format!("Skipped {} unreadable journal files, first {}", failures.len(), failures[0]));
+		}
+		return entries;
+	}
+
+	// The files belong to root and the systemd-journal group; journalctl may still be allowed
+	// to read them, for example through the adm group on Debian
+	let since_arg = format!("--since=@{}", since.timestamp());
+	match source.command("journalctl", &["--output=export", "--priority=warning", &since_arg, "--no-pager"]) {
+		Ok(output) if output.status == Some(0) => journal::parse_export(output.stdout.as_bytes()),
+		_ => {
+			report::warn(format!("Cannot read the journal: {}", failures[0]));
+			Vec::new()
+		}
+	}
+}
+
+/// The newest critical, error and warning entries as events.
+fn newest_events(mut entries: Vec<Entry>) -> Vec<EventEntry> {
+	entries.sort_by_key(|entry| std::cmp::Reverse(entry.realtime));
+	entries
+		.iter()
+		.filter_map(|entry| {
+			let level = match entry.field("PRIORITY")?.parse::<u8>().ok()? {
+				0..=2 => "Critical",
+				3 => "Error",
+				4 => "Warning",
+				_ => return None,
+			};
+			let time = DateTime::from_timestamp_micros(entry.realtime as i64)?;
+			Some(EventEntry {
+				level: level.to_string(),
+				source: entry
+					.field("SYSLOG_IDENTIFIER")
+					.or_else(|| entry.field("_SYSTEMD_UNIT"))
+					.or_else(|| entry.field("_COMM"))
+					.unwrap_or_default(),
+				// The catalog id systemd and some daemons attach to well-known messages
+				event_id: entry.field("MESSAGE_ID").unwrap_or_default(),
+				time_created: time.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string(),
+				message: entry.field("MESSAGE").unwrap_or_default(),
+			})
 		})
+		.take(MAX_EVENTS)
+		.collect()
+}
+
+/// Lines of the syslog files as journal entries. The files do not record a priority, so one is
+/// guessed from the wording of the message.
+fn syslog_entries(source: &dyn DataSource, since: DateTime<Utc>) -> Vec<Entry> {
+	let mut entries = Vec::new();
+	let mut found = false;
+	for file in SYSLOG_FILES {
+		let Ok(text) = source.read_to_string(file) else {
+			continue;
+		};
+		found = true;
+		for line in text.lines() {
+			let Some((time, rest)) = syslog_time(line, since) else {
+				continue;
+			};
+			if time < since {
+				continue;
+			}
+			// host ident[pid]: message
+			let Some((_host, rest)) = rest.trim_start().split_once(' ') else {
+				continue;
+			};
+			let (tag, message) = rest.split_once(": ").unwrap_or(("", rest));
+			let identifier = tag.split('[').next().unwrap_or(tag);
+			let Some(priority) = guess_priority(message) else {
+				continue;
+			};
+			entries.push(Entry {
+				realtime: time.timestamp_micros().max(0) as u64,
+				fields: vec![
+					("PRIORITY".to_string(), priority.to_string().into_bytes()),
+					("SYSLOG_IDENTIFIER".to_string(), identifier.as_bytes().to_vec()),
+					("MESSAGE".to_string(), message.as_bytes().to_vec()),
+				],
+			});
+		}
+	}
+	if !found {
+		report::warn("No journal or syslog file to read events from");
 	}
+	entries
 }
 
-fn query_event_log(log_name: &str, since: &str) -> Vec<EventEntry> {
+/// The timestamp at the start of a syslog line, RFC 3339 or the traditional `Oct 17 08:12:44` in
+/// local time with the year left out, and the rest of the line.
+fn syslog_time(line: &str, since: DateTime<Utc>) -> Option<(DateTime<Utc>, &str)> {
+	let (first, rest) = line.split_once(' ')?;
+	if let Ok(time) = DateTime::parse_from_rfc3339(first) {
+		return Some((time.with_timezone(&Utc), rest));
+	}
+
+	let stamp = line.get(..15)?;
+	// The year is the one that puts the line closest to, but not far after, the window start
+	let local = |year: i32| {
+		let time = NaiveDateTime::parse_from_str(&format!("{} {}", year, stamp), "%Y %b %e %H:%M:%S").ok()?;
+		Local.from_local_datetime(&time).earliest().map(|time| time.with_timezone(&Utc))
+	};
+	let year = since.with_timezone(&Local).year();
+	let time = local(year).filter(|time| *time <= since + Duration::days(2)).or_else(|| local(year - 1))?;
+	Some((time, line.get(15..)?))
+}
+
+fn guess_priority(message: &str) -> Option<u8> {
+	let message = message.to_ascii_lowercase();
+	if ["panic", "emerg", "critical", "fatal"].iter().any(|word| message.contains(word)) {
+		Some(2)
+	} else if ["error", "fail", "segfault"].iter().any(|word| message.contains(word)) {
+		Some(3)
+	} else if message.contains("warn") {
+		Some(4)
+	} else {
+		None
+	}
+}
+
+fn query_event_log(source: &dyn DataSource, log_name: &str, since: &str) -> Vec<EventEntry> {
 	let query = format!(
 		"*[System[(Level>=1 and Level<=3) and TimeCreated[@SystemTime>='{}']]]",
 		since
 	);
 
-	let output = Command::new("wevtutil")
-		.args([
+	let output = source.command(
+		"wevtutil",
+		&[
 			"qe",
 			log_name,
 			&format!("/q:{}", query),
-			"/c:15",
+			&format!("/c:{}", MAX_EVENTS),
 			"/rd:true",
 			"/f:xml",
-		])
-		.creation_flags(0x08000000)
-		.output();
+		],
+	);
 
-	let Ok(output) = output else {
-		return Vec::new();
+	let output = match output {
+		Ok(output) => output,
+		Err(e) => {
+			report::warn(format!("Failed to query the {} event log: {}", log_name, e));
+			return Vec::new();
+		}
 	};
-	let xml = String::from_utf8_lossy(&output.stdout);
-
-	parse_event_xml(&xml)
+	parse_event_xml(&output.stdout)
 }
 
 fn parse_event_xml(xml: &str) -> Vec<EventEntry> {
@@ -1,10 +1,10 @@
-use crate::{ComputerInfoExt, VariantExt};
+use crate::linux::{lookup_ids, parse_key_values, read_optional, read_pci_ids};
+use crate::monitor_info::{DisplayMode, connector_mode};
+use crate::source::{CIMV2, DataSource, Hive, Platform};
+use crate::{ComputerInfoExt, VariantExt, report};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use winreg::enums::HKEY_LOCAL_MACHINE;
-use winreg::RegKey;
-use wmi::Variant;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GpuInfo {
@@ -14,6 +14,9 @@ pub struct GpuInfo {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GpuAdapter {
 	pub name: String,
+	/// PCI vendor and device id, e.g. `10DE:2484`.
+	pub pci_id: String,
+	pub driver: String,
 	pub driver_version: String,
 	pub driver_date: String,
 	pub adapter_ram_mb: u64,
@@ -21,16 +24,34 @@ pub struct GpuAdapter {
 	pub refresh_rate: u32,
 	pub status: String,
 	pub availability: String,
+	/// Connected outputs and their current mode. Only reported on Linux.
+	pub outputs: Vec<GpuOutput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GpuOutput {
+	/// DRM connector name, e.g. `card0-DP-1`.
+	pub connector: String,
+	pub resolution: String,
+	pub refresh_rate: u32,
 }
 
 impl ComputerInfoExt for GpuInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
-		let results: Vec<HashMap<String, Variant>> =
-			com.raw_query("SELECT * FROM Win32_VideoController")?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("GPU collection")),
+		}
+	}
+}
+
+impl GpuInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let results = source.wmi_query(CIMV2, "SELECT * FROM Win32_VideoController")?;
 
 		// Build registry-based VRAM lookup: gpu_name -> vram_bytes
-		let registry_vram = Self::fetch_registry_vram();
+		let registry_vram = Self::fetch_registry_vram(source);
 
 		let adapters = results
 			.iter()
@@ -57,13 +78,36 @@ impl ComputerInfoExt for GpuInfo {
 				let name = data.get_string("Name").unwrap_or_default();
 
 				// Try registry VRAM first (accurate for >4GB), fall back to WMI AdapterRAM
-				let vram_bytes = registry_vram
-					.get(&name)
-					.copied()
-					.unwrap_or_else(|| data.get_u64("AdapterRAM").unwrap_or(0));
+				let vram_bytes = registry_vram.get(&name).copied().unwrap_or_else(|| {
+					report::warn(format!(
+						"No registry VRAM size for '{}', using Win32_VideoController.AdapterRAM (capped at 4 GB)",
+						name
+					));
+					data.get_u64("AdapterRAM").unwrap_or(0)
+				});
+
+				// PCI\VEN_10DE&DEV_2484&SUBSYS_...
+				let pnp_id = data.get_string("PNPDeviceID").unwrap_or_default().to_uppercase();
+				let pci_id = match (pnp_id.split_once("VEN_"), pnp_id.split_once("DEV_")) {
+					(Some((_, vendor)), Some((_, device))) if vendor.len() >= 4 && device.len() >= 4 => {
+						format!("{}:{}", &vendor[..4], &device[..4])
+					}
+					_ => String::new(),
+				};
+				// Comma-separated user-mode driver DLLs, e.g. C:\...\nvldumdx.dll,...
+				let driver = data
+					.get_string("InstalledDisplayDrivers")
+					.unwrap_or_default()
+					.split(',')
+					.next()
+					.and_then(|path| path.rsplit('\\').next())
+					.map(|file| file.trim_end_matches(".dll").to_string())
+					.unwrap_or_default();
 
 				GpuAdapter {
 					name,
+					pci_id,
+					driver,
 					driver_version: data.get_string("DriverVersion").unwrap_or_default(),
 					driver_date: data
 						.get_string("DriverDate")
@@ -80,37 +124,372 @@ impl ComputerInfoExt for GpuInfo {
 					refresh_rate: data.get_u32("CurrentRefreshRate").unwrap_or(0),
 					status: data.get_string("Status").unwrap_or_default(),
 					availability,
+					outputs: Vec::new(),
 				}
 			})
 			.collect();
 
 		Ok(GpuInfo { adapters })
 	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		// Headless machines without a DRM driver have no /sys/class/drm at all
+		let entries = source.read_dir("/sys/class/drm").unwrap_or_default();
+		let cards: Vec<&String> = entries
+			.iter()
+			.filter(|name| name.strip_prefix("card").is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit())))
+			.collect();
+		if cards.is_empty() {
+			return Ok(GpuInfo::default());
+		}
+
+		let modes = source.display_modes().unwrap_or_else(|e| {
+			report::warn(format!("Current display modes unavailable, using each connector's preferred mode: {}", e));
+			Vec::new()
+		});
+		let pci_ids = read_pci_ids(source);
+
+		let adapters = cards
+			.into_iter()
+			.filter_map(|card| Self::read_drm_card(source, card, &entries, &modes, pci_ids.as_deref()))
+			.collect();
+
+		Ok(GpuInfo { adapters })
+	}
+
+	/// Describes `/sys/class/drm/<card>`, or `None` for firmware framebuffers such as simpledrm
+	/// that stand in until the real driver loads.
+	fn read_drm_card(
+		source: &dyn DataSource,
+		card: &str,
+		entries: &[String],
+		modes: &[DisplayMode],
+		pci_ids: Option<&str>,
+	) -> Option<GpuAdapter> {
+		let dir = format!("/sys/class/drm/{}/device", card);
+		let link_name = |path: String| {
+			source
+				.read_link(&path)
+				.ok()
+				.and_then(|target| target.rsplit('/').next().map(String::from))
+		};
+		let driver = link_name(format!("{}/driver", dir)).unwrap_or_default();
+		if driver.ends_with("framebuffer") {
+			return None;
+		}
+
+		let pci_hex = |file: &str| read_optional(source, &format!("{}/{}", dir, file)).map(|id| id.trim_start_matches("0x").to_lowercase());
+		let name;
+		let pci_id;
+		if let (Some(vendor), Some(device)) = (pci_hex("vendor"), pci_hex("device")) {
+			pci_id = format!("{}:{}", vendor, device).to_uppercase();
+			let (vendor_name, device_name) = pci_ids.map(|ids| lookup_ids(ids, &vendor, &device)).unwrap_or_default();
+			let vendor_name = match vendor.as_str() {
+				"1002" => Some("AMD".to_string()),
+				"10de" => Some("NVIDIA".to_string()),
+				"8086" => Some("Intel".to_string()),
+				_ => vendor_name,
+			}
+			.unwrap_or_else(|| "PCI device".to_string());
+			name = match device_name {
+				Some(device_name) => format!("{} {}", vendor_name, device_name),
+				None => format!("{} [{}]", vendor_name, pci_id),
+			};
+		} else {
+			// SoC display engines sit on the platform bus and are named by their devicetree node
+			let uevent = source
+				.read_to_string(&format!("{}/uevent", dir))
+				.map(|text| parse_key_values(&text))
+				.unwrap_or_default();
+			pci_id = String::new();
+			name = uevent.get("OF_COMPATIBLE_0").cloned().unwrap_or_else(|| driver.clone());
+		}
+
+		// Out-of-tree modules such as nvidia carry a version; in-tree drivers ship with the kernel
+		let driver_version = link_name(format!("{}/driver/module", dir))
+			.and_then(|module| read_optional(source, &format!("/sys/module/{}/version", module)))
+			.or_else(|| read_optional(source, "/proc/sys/kernel/osrelease"))
+			.unwrap_or_default();
+
+		// amdgpu exposes its VRAM size; other drivers do not
+		let adapter_ram_mb = read_optional(source, &format!("{}/mem_info_vram_total", dir))
+			.and_then(|bytes| bytes.parse::<u64>().ok())
+			.map(|bytes| bytes / (1024 * 1024))
+			.unwrap_or(0);
+
+		let prefix = format!("{}-", card);
+		let outputs: Vec<GpuOutput> = entries
+			.iter()
+			.filter(|entry| entry.starts_with(&prefix))
+			.filter(|connector| {
+				read_optional(source, &format!("/sys/class/drm/{}/status", connector)).as_deref() == Some("connected")
+			})
+			.map(|connector| {
+				let (resolution, refresh_rate) = connector_mode(source, connector, modes);
+				GpuOutput {
+					connector: connector.clone(),
+					resolution,
+					refresh_rate,
+				}
+			})
+			.collect();
+		let (resolution, refresh_rate) = outputs
+			.first()
+			.map(|output| (output.resolution.clone(), output.refresh_rate))
+			.unwrap_or_else(|| ("N/A".to_string(), 0));
+
+		let availability = match read_optional(source, &format!("{}/power_state", dir)).as_deref() {
+			Some("D0") => "Running/Full Power",
+			Some(state) if state.starts_with("D3") => "Power Save - Standby",
+			_ => "Unknown",
+		}
+		.to_string();
+
+		Some(GpuAdapter {
+			name,
+			pci_id,
+			status: if driver.is_empty() { "Unknown" } else { "OK" }.to_string(),
+			driver,
+			driver_version,
+			driver_date: String::new(),
+			adapter_ram_mb,
+			resolution,
+			refresh_rate,
+			availability,
+			outputs,
+		})
+	}
+}
+
+#[cfg(target_os = "linux")]
+mod drm {
+	//! The parts of the DRM mode-setting uAPI (`drm_mode.h`) needed to read the mode each
+	//! connector is currently driven at.
+
+	use crate::monitor_info::DisplayMode;
+	use anyhow::Result;
+	use std::fs::File;
+	use std::os::fd::AsRawFd;
+
+	const GET_RESOURCES: u64 = 0xA0;
+	const GET_CRTC: u64 = 0xA1;
+	const GET_ENCODER: u64 = 0xA6;
+	const GET_CONNECTOR: u64 = 0xA7;
+
+	/// Connector type names as the kernel uses them in `/sys/class/drm`.
+	const CONNECTOR_NAMES: &[&str] = &[
+		"Unknown", "VGA", "DVI-I", "DVI-D", "DVI-A", "Composite", "SVIDEO", "LVDS", "Component", "DIN", "DP",
+		"HDMI-A", "HDMI-B", "TV", "eDP", "Virtual", "DSI", "DPI", "Writeback", "SPI", "USB",
+	];
+
+	#[repr(C)]
+	#[derive(Default)]
+	struct CardResources {
+		fb_id_ptr: u64,
+		crtc_id_ptr: u64,
+		connector_id_ptr: u64,
+		encoder_id_ptr: u64,
+		count_fbs: u32,
+		count_crtcs: u32,
+		count_connectors: u32,
+		count_encoders: u32,
+		min_width: u32,
+		max_width: u32,
+		min_height: u32,
+		max_height: u32,
+	}
+
+	#[repr(C)]
+	#[derive(Default)]
+	struct ModeInfo {
+		clock: u32,
+		hdisplay: u16,
+		hsync_start: u16,
+		hsync_end: u16,
+		htotal: u16,
+		hskew: u16,
+		vdisplay: u16,
+		vsync_start: u16,
+		vsync_end: u16,
+		vtotal: u16,
+		vscan: u16,
+		vrefresh: u32,
+		flags: u32,
+		mode_type: u32,
+		name: [u8; 32],
+	}
+
+	#[repr(C)]
+	#[derive(Default)]
+	struct Connector {
+		encoders_ptr: u64,
+		modes_ptr: u64,
+		props_ptr: u64,
+		prop_values_ptr: u64,
+		count_modes: u32,
+		count_props: u32,
+		count_encoders: u32,
+		encoder_id: u32,
+		connector_id: u32,
+		connector_type: u32,
+		connector_type_id: u32,
+		connection: u32,
+		mm_width: u32,
+		mm_height: u32,
+		subpixel: u32,
+		pad: u32,
+	}
+
+	#[repr(C)]
+	#[derive(Default)]
+	struct Encoder {
+		encoder_id: u32,
+		encoder_type: u32,
+		crtc_id: u32,
+		possible_crtcs: u32,
+		possible_clones: u32,
+	}
+
+	#[repr(C)]
+	#[derive(Default)]
+	struct Crtc {
+		set_connectors_ptr: u64,
+		count_connectors: u32,
+		crtc_id: u32,
+		fb_id: u32,
+		x: u32,
+		y: u32,
+		gamma_size: u32,
+		mode_valid: u32,
+		mode: ModeInfo,
+	}
+
+	/// `DRM_IOWR(nr, T)`
+	fn ioctl<T>(card: &File, nr: u64, arg: &mut T) -> std::io::Result<()> {
+		let request = (3 << 30) | ((std::mem::size_of::<T>() as u64) << 16) | ((b'd' as u64) << 8) | nr;
+		// SAFETY: every request number is paired with the struct drm_mode.h declares for it, and
+		// any pointers inside it point at buffers sized by the counts passed alongside
+		if unsafe { libc::ioctl(card.as_raw_fd(), request as _, arg as *mut T) } != 0 {
+			return Err(std::io::Error::last_os_error());
+		}
+		Ok(())
+	}
+
+	/// Current mode of every connector that is lit, across all `/dev/dri/card*` nodes.
+	pub(crate) fn query_drm_modes() -> Result<Vec<DisplayMode>> {
+		let mut cards: Vec<String> = std::fs::read_dir("/dev/dri")?
+			.filter_map(|entry| entry.ok())
+			.map(|entry| entry.file_name().to_string_lossy().into_owned())
+			.filter(|name| name.strip_prefix("card").is_some_and(|n| n.bytes().all(|b| b.is_ascii_digit())))
+			.collect();
+		cards.sort();
+
+		let mut modes = Vec::new();
+		let mut first_error = None;
+		for card in cards {
+			match File::open(format!("/dev/dri/{}", card)).and_then(|file| query_card(&card, &file, &mut modes)) {
+				Ok(()) => {}
+				Err(e) => {
+					first_error.get_or_insert_with(|| anyhow::anyhow!("Failed to query /dev/dri/{}: {}", card, e));
+				}
+			}
+		}
+		match first_error {
+			Some(e) if modes.is_empty() => Err(e),
+			_ => Ok(modes),
+		}
+	}
+
+	fn query_card(card: &str, file: &File, modes: &mut Vec<DisplayMode>) -> std::io::Result<()> {
+		let mut resources = CardResources::default();
+		ioctl(file, GET_RESOURCES, &mut resources)?;
+		let mut connector_ids = vec![0u32; resources.count_connectors as usize];
+		resources = CardResources {
+			connector_id_ptr: connector_ids.as_mut_ptr() as u64,
+			count_connectors: connector_ids.len() as u32,
+			..CardResources::default()
+		};
+		ioctl(file, GET_RESOURCES, &mut resources)?;
+		connector_ids.truncate(resources.count_connectors as usize);
+
+		for connector_id in connector_ids {
+			// Passing room for one mode returns the cached state instead of forcing a re-probe
+			let mut probe_mode = ModeInfo::default();
+			let mut connector = Connector {
+				connector_id,
+				modes_ptr: &mut probe_mode as *mut ModeInfo as u64,
+				count_modes: 1,
+				..Connector::default()
+			};
+			ioctl(file, GET_CONNECTOR, &mut connector)?;
+			if connector.encoder_id == 0 {
+				continue;
+			}
+
+			let mut encoder = Encoder {
+				encoder_id: connector.encoder_id,
+				..Encoder::default()
+			};
+			ioctl(file, GET_ENCODER, &mut encoder)?;
+			if encoder.crtc_id == 0 {
+				continue;
+			}
+
+			let mut crtc = Crtc {
+				crtc_id: encoder.crtc_id,
+				..Crtc::default()
+			};
+			ioctl(file, GET_CRTC, &mut crtc)?;
+			if crtc.mode_valid == 0 {
+				continue;
+			}
+
+			let mode = &crtc.mode;
+			let pixels_per_frame = u64::from(mode.htotal) * u64::from(mode.vtotal);
+			let refresh_rate = (u64::from(mode.clock) * 1000 + pixels_per_frame / 2)
+				.checked_div(pixels_per_frame)
+				.map_or(mode.vrefresh, |hz| hz as u32);
+			let type_name = CONNECTOR_NAMES.get(connector.connector_type as usize).unwrap_or(&"Unknown");
+			modes.push(DisplayMode {
+				device_name: format!("{}-{}-{}", card, type_name, connector.connector_type_id),
+				width: u32::from(mode.hdisplay),
+				height: u32::from(mode.vdisplay),
+				refresh_rate,
+				monitor_ids: Vec::new(),
+			});
+		}
+		Ok(())
+	}
 }
 
+#[cfg(target_os = "linux")]
+pub(crate) use drm::query_drm_modes;
+
 impl GpuInfo {
 	/// Read VRAM from the registry where `HardwareInformation.qwMemorySize` is a REG_QWORD.
 	/// This avoids the WMI uint32 cap (~4GB) on AdapterRAM.
-	fn fetch_registry_vram() -> HashMap<String, u64> {
+	fn fetch_registry_vram(source: &dyn DataSource) -> HashMap<String, u64> {
+		const CLASS_KEY: &str =
+			r"SYSTEM\CurrentControlSet\Control\Class\{4d36e968-e325-11ce-bfc1-08002be10318}";
+
 		let mut map = HashMap::new();
-		let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-		let Ok(class_key) = hklm.open_subkey(
-			r"SYSTEM\CurrentControlSet\Control\Class\{4d36e968-e325-11ce-bfc1-08002be10318}",
-		) else {
+		let Ok(class_key) = source.registry_key(Hive::LocalMachine, CLASS_KEY) else {
 			return map;
 		};
 
-		for subkey_name in class_key.enum_keys().flatten() {
-			let Ok(subkey) = class_key.open_subkey(&subkey_name) else {
+		for subkey_name in &class_key.subkeys {
+			let Ok(subkey) =
+				source.registry_key(Hive::LocalMachine, &format!(r"{}\{}", CLASS_KEY, subkey_name))
+			else {
 				continue;
 			};
-			let Ok(desc): Result<String, _> = subkey.get_value("DriverDesc") else {
+			let Ok(desc) = subkey.values.get_string("DriverDesc") else {
 				continue;
 			};
-			if let Ok(vram) = subkey.get_value::<u64, _>("HardwareInformation.qwMemorySize") {
-				if vram > 0 {
-					map.insert(desc, vram);
-				}
+			if let Ok(vram) = subkey.values.get_u64("HardwareInformation.qwMemorySize")
+				&& vram > 0
+			{
+				map.insert(desc, vram);
 			}
 		}
 
@@ -1,8 +1,26 @@
-use crate::{ComputerInfoExt, VariantExt};
+use crate::linux::read_optional;
+use crate::source::{CIMV2, DataSource, Platform};
+use crate::sqlite::{self, Value};
+use crate::{ComputerInfoExt, VariantExt, report};
 use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDateTime};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use wmi::Variant;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+
+/// Updates reported, newest first on Linux, where kernel images come on top.
+const MAX_HOTFIXES: usize = 25;
+/// `history.log` and its rotated copies, `history.log.1.gz` and so on.
+const APT_LOG_DIRECTORY: &str = "/var/log/apt";
+/// dnf's transaction database; `yum` is dnf from Red Hat Enterprise Linux 8 on.
+const DNF_HISTORY: &str = "/var/lib/dnf/history.sqlite";
+const PACMAN_LOG: &str = "/var/log/pacman.log";
+const BOOT: &str = "/boot";
+/// Fedora and Arch install the kernel image next to its modules rather than in /boot.
+const MODULE_DIRECTORIES: &[&str] = &["/usr/lib/modules", "/lib/modules"];
+/// Actions in the order a transaction's description lists them.
+const ACTIONS: &[&str] = &["Install", "Upgrade", "Downgrade", "Reinstall", "Remove"];
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct HotfixInfo {
@@ -11,29 +29,397 @@ pub struct HotfixInfo {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Hotfix {
+	/// KB number on Windows; on Linux the command that ran the transaction, or the kernel release.
 	pub hotfix_id: String,
 	pub description: String,
 	pub installed_by: String,
 	pub installed_on: String,
+	/// Packages a Linux transaction changed, as `name version` or `name old -> new`.
+	#[serde(default)]
+	pub packages: Vec<String>,
 }
 
 impl ComputerInfoExt for HotfixInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
-		let results: Vec<HashMap<String, Variant>> =
-			com.raw_query("SELECT * FROM Win32_QuickFixEngineering")?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Hotfix collection")),
+		}
+	}
+}
+
+impl HotfixInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let results = source.wmi_query(CIMV2, "SELECT * FROM Win32_QuickFixEngineering")?;
 
 		let hotfixes = results
 			.iter()
-			.take(25)
+			.take(MAX_HOTFIXES)
 			.map(|data| Hotfix {
 				hotfix_id: data.get_string("HotFixID").unwrap_or_default(),
 				description: data.get_string("Description").unwrap_or_default(),
 				installed_by: data.get_string("InstalledBy").unwrap_or_default(),
 				installed_on: data.get_string("InstalledOn").unwrap_or_default(),
+				..Default::default()
 			})
 			.collect();
 
 		Ok(HotfixInfo { hotfixes })
 	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let mut transactions = apt_transactions(source);
+		transactions.extend(dnf_transactions(source));
+		transactions.extend(pacman_transactions(source));
+		if transactions.is_empty() {
+			report::warn("No apt, dnf or pacman history to read updates from");
+		}
+		transactions.sort_by_key(|transaction| Reverse(transaction.0));
+
+		let mut hotfixes = kernel_images(source);
+		hotfixes.extend(transactions.into_iter().take(MAX_HOTFIXES).map(|(_, hotfix)| hotfix));
+		Ok(HotfixInfo { hotfixes })
+	}
+}
+
+/// Packages of one transaction by action, and the description listing those actions.
+#[derive(Default)]
+struct Changes {
+	packages: BTreeMap<&'static str, Vec<String>>,
+}
+
+impl Changes {
+	fn add(&mut self, action: &'static str, package: String) {
+		self.packages.entry(action).or_default().push(package);
+	}
+
+	fn is_empty(&self) -> bool {
+		self.packages.is_empty()
+	}
+
+	/// `Upgrade, Remove`, with `failed` appended when the transaction did not complete.
+	fn into_hotfix(self, command: String, user: String, time: NaiveDateTime, failed: bool) -> (NaiveDateTime, Hotfix) {
+		let mut description: Vec<&str> = ACTIONS.iter().copied().filter(|action| self.packages.contains_key(action)).collect();
+		if failed {
+			description.push("failed");
+		}
+		let packages = ACTIONS.iter().filter_map(|action| self.packages.get(action)).flatten().cloned().collect();
+		(
+			time,
+			Hotfix {
+				hotfix_id: command,
+				description: description.join(", "),
+				installed_by: user,
+				installed_on: format_time(time),
+				packages,
+			},
+		)
+	}
+}
+
+/// Transactions in apt's history log, blocks of `Key: value` lines such as
+///
+/// ```text
+/// Start-Date: 2026-10-12  06:25:31
+/// Commandline: apt-get -y upgrade
+/// Requested-By: alex (1000)
+/// Upgrade: openssl:amd64 (3.0.13-0ubuntu3.3, 3.0.13-0ubuntu3.4)
+/// End-Date: 2026-10-12  06:25:40
+/// ```
+fn apt_transactions(source: &dyn DataSource) -> Vec<(NaiveDateTime, Hotfix)> {
+	let names = source.read_dir(APT_LOG_DIRECTORY).unwrap_or_default();
+	let mut transactions = Vec::new();
+	for name in names.iter().filter(|name| name.as_str() == "history.log" || name.starts_with("history.log.")) {
+		let path = format!("{}/{}", APT_LOG_DIRECTORY, name);
+		let text = match read_log(source, &path) {
+			Ok(text) => text,
+			Err(e) => {
+				report::warn(format!("Failed to read {}: {:#}", path, e));
+				continue;
+			}
+		};
+		for block in text.split("\n\n") {
+			let fields: HashMap<&str, &str> = block
+				.lines()
+				.filter_map(|line| line.split_once(": "))
+				.map(|(key, value)| (key.trim(), value.trim()))
+				.collect();
+			// The date and time are separated by two spaces
+			let start = fields.get("Start-Date").map(|date| date.split_whitespace().collect::<Vec<_>>().join(" "));
+			let Some(time) = start.and_then(|date| NaiveDateTime::parse_from_str(&date, "%Y-%m-%d %H:%M:%S").ok()) else {
+				continue;
+			};
+
+			let mut changes = Changes::default();
+			for (key, action) in [
+				("Install", "Install"),
+				("Upgrade", "Upgrade"),
+				("Downgrade", "Downgrade"),
+				("Reinstall", "Reinstall"),
+				("Remove", "Remove"),
+				("Purge", "Remove"),
+			] {
+				for package in fields.get(key).map(|list| apt_packages(list)).unwrap_or_default() {
+					changes.add(action, package);
+				}
+			}
+			if changes.is_empty() {
+				continue;
+			}
+			// unattended-upgrades records no command line
+			let command = fields.get("Commandline").map_or("apt", |command| command).to_string();
+			// alex (1000); absent when apt ran as root without sudo
+			let user = fields.get("Requested-By").and_then(|user| user.split_whitespace().next()).unwrap_or_default();
+			transactions.push(changes.into_hotfix(command, user.to_string(), time, fields.contains_key("Error")));
+		}
+	}
+	transactions
+}
+
+/// `openssl:amd64 (3.0.13-0ubuntu3.3, 3.0.13-0ubuntu3.4), gcc-14-base:amd64 (14.2.0-4ubuntu2~24.04, automatic)`:
+/// the old and new versions of an upgrade, or a version and whether it was pulled in as a dependency.
+fn apt_packages(list: &str) -> Vec<String> {
+	list.split("), ")
+		.filter_map(|item| {
+			let (name, versions) = item.trim_end_matches(')').split_once(" (")?;
+			let versions: Vec<&str> = versions.split(", ").filter(|version| *version != "automatic").collect();
+			Some(match versions[..] {
+				[old, new] => format!("{} {} -> {}", name, old, new),
+				[version] => format!("{} {}", name, version),
+				_ => name.to_string(),
+			})
+		})
+		.collect()
+}
+
+/// A log file, decompressed when logrotate has gzipped it.
+fn read_log(source: &dyn DataSource, path: &str) -> Result<String> {
+	let data = source.read_file(path)?;
+	if !path.ends_with(".gz") {
+		return Ok(String::from_utf8_lossy(&data).into_owned());
+	}
+	let mut text = String::new();
+	flate2::read::MultiGzDecoder::new(data.as_slice()).read_to_string(&mut text)?;
+	Ok(text)
+}
+
+fn dnf_transactions(source: &dyn DataSource) -> Vec<(NaiveDateTime, Hotfix)> {
+	let Ok(data) = source.read_file(DNF_HISTORY) else {
+		return Vec::new();
+	};
+	read_dnf_history(&data, &user_names(source)).unwrap_or_else(|e| {
+		report::warn(format!("Failed to read {}: {:#}", DNF_HISTORY, e));
+		Vec::new()
+	})
+}
+
+/// Transactions in libdnf's software database, joining `trans` to the packages of its `trans_item`s.
+fn read_dnf_history(data: &[u8], users: &HashMap<i64, String>) -> Result<Vec<(NaiveDateTime, Hotfix)>> {
+	let database = sqlite::Database::parse(data)?;
+
+	// rpm: item_id, name, epoch, version, release, arch
+	let mut packages = HashMap::new();
+	for (_, columns) in database.rows("rpm")? {
+		if let [Value::Integer(item), Value::Text(name), Value::Integer(epoch), Value::Text(version), Value::Text(release), Value::Text(arch), ..] =
+			columns.as_slice()
+		{
+			let version = match epoch {
+				0 => format!("{}-{}", version, release),
+				epoch => format!("{}:{}-{}", epoch, version, release),
+			};
+			packages.insert(*item, (format!("{}.{}", name, arch), version));
+		}
+	}
+
+	// trans_item: id, trans_id, item_id, repo_id, action, reason, state
+	let mut items: HashMap<i64, Vec<(i64, i64)>> = HashMap::new();
+	for (_, columns) in database.rows("trans_item")? {
+		if let [_, Value::Integer(transaction), Value::Integer(item), _, Value::Integer(action), ..] = columns.as_slice() {
+			items.entry(*transaction).or_default().push((*item, *action));
+		}
+	}
+
+	let mut transactions = Vec::new();
+	// trans: id, dt_begin, dt_end, rpmdb_version_begin, rpmdb_version_end, releasever, user_id, cmdline, state
+	for (id, columns) in database.rows("trans")? {
+		let [_, Value::Integer(begin), _, _, _, _, Value::Integer(user), command, Value::Integer(state), ..] = columns.as_slice() else {
+			continue;
+		};
+		let Some(time) = DateTime::from_timestamp(*begin, 0).map(|time| time.with_timezone(&Local).naive_local()) else {
+			continue;
+		};
+
+		// The old version of an upgrade or downgrade is a separate item
+		let mut replaced = HashMap::new();
+		for (item, action) in items.get(&id).into_iter().flatten() {
+			if let (3 | 7, Some((name, version))) = (action, packages.get(item)) {
+				replaced.insert(name, version);
+			}
+		}
+		let mut changes = Changes::default();
+		for (item, action) in items.get(&id).into_iter().flatten() {
+			let Some((name, version)) = packages.get(item) else {
+				continue;
+			};
+			// libdnf's TransactionItemAction; obsoleting installs the replacement
+			let action = match action {
+				1 | 4 => "Install",
+				6 => "Upgrade",
+				2 => "Downgrade",
+				9 => "Reinstall",
+				5 | 8 => "Remove",
+				_ => continue,
+			};
+			let package = match replaced.get(name) {
+				Some(old) if matches!(action, "Upgrade" | "Downgrade") => format!("{} {} -> {}", name, old, version),
+				_ => format!("{} {}", name, version),
+			};
+			changes.add(action, package);
+		}
+		if changes.is_empty() {
+			continue;
+		}
+
+		let command = match command {
+			Value::Text(command) => format!("dnf {}", command),
+			_ => "dnf".to_string(),
+		};
+		let user = users.get(user).cloned().unwrap_or_else(|| user.to_string());
+		// 1 is done, 2 is error
+		transactions.push(changes.into_hotfix(command, user, time, *state != 1));
+	}
+	Ok(transactions)
+}
+
+/// Transactions in pacman's log, from `[ALPM] transaction started` to `completed` or `failed`, each
+/// credited to the `[PACMAN] Running '...'` command before it.
+fn pacman_transactions(source: &dyn DataSource) -> Vec<(NaiveDateTime, Hotfix)> {
+	let Ok(log) = source.read_to_string(PACMAN_LOG) else {
+		return Vec::new();
+	};
+	let mut transactions = Vec::new();
+	let mut command = "pacman".to_string();
+	let mut current: Option<(NaiveDateTime, Changes)> = None;
+	for line in log.lines() {
+		// [2026-10-12T06:25:41+0200] [ALPM] upgraded linux (6.11.1.arch1-1 -> 6.11.3.arch1-1)
+		let Some((time, rest)) = line.strip_prefix('[').and_then(|line| line.split_once("] ")) else {
+			continue;
+		};
+		// Wall time as logged; pacman before 5.1 wrote [2018-05-14 09:12]
+		let Some(time) = DateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%z")
+			.map(|time| time.naive_local())
+			.or_else(|_| NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M"))
+			.ok()
+		else {
+			continue;
+		};
+
+		if let Some(running) = rest.strip_prefix("[PACMAN] Running '") {
+			// Logs older than pacman 5 have no transaction lines, so a new command ends the last one
+			finish_transaction(&mut current, &command, false, &mut transactions);
+			command = running.trim_end_matches('\'').to_string();
+			continue;
+		}
+		let Some(message) = rest.strip_prefix("[ALPM] ") else {
+			continue;
+		};
+		match message {
+			"transaction started" => {
+				finish_transaction(&mut current, &command, false, &mut transactions);
+				current = Some((time, Changes::default()));
+				continue;
+			}
+			"transaction completed" => {
+				finish_transaction(&mut current, &command, false, &mut transactions);
+				continue;
+			}
+			"transaction failed" | "transaction interrupted" => {
+				finish_transaction(&mut current, &command, true, &mut transactions);
+				continue;
+			}
+			_ => {}
+		}
+
+		let Some((verb, package)) = message.split_once(' ') else {
+			continue;
+		};
+		let action = match verb {
+			"installed" => "Install",
+			"upgraded" => "Upgrade",
+			"downgraded" => "Downgrade",
+			"reinstalled" => "Reinstall",
+			"removed" => "Remove",
+			_ => continue,
+		};
+		let Some((name, version)) = package.split_once(" (") else {
+			continue;
+		};
+		let (_, changes) = current.get_or_insert_with(|| (time, Changes::default()));
+		changes.add(action, format!("{} {}", name, version.trim_end_matches(')')));
+	}
+	finish_transaction(&mut current, &command, false, &mut transactions);
+	transactions
+}
+
+fn finish_transaction(
+	current: &mut Option<(NaiveDateTime, Changes)>,
+	command: &str,
+	failed: bool,
+	transactions: &mut Vec<(NaiveDateTime, Hotfix)>,
+) {
+	// pacman records no user
+	if let Some((start, changes)) = current.take()
+		&& !changes.is_empty()
+	{
+		transactions.push(changes.into_hotfix(command.to_string(), String::new(), start, failed));
+	}
+}
+
+/// Kernel releases with modules and an image, either `/boot/vmlinuz-<release>` or a `vmlinuz` in
+/// the module directory, newest image first.
+fn kernel_images(source: &dyn DataSource) -> Vec<Hotfix> {
+	let running = read_optional(source, "/proc/sys/kernel/osrelease");
+	let mut kernels = Vec::new();
+	for directory in MODULE_DIRECTORIES {
+		for release in source.read_dir(directory).unwrap_or_default() {
+			if kernels.iter().any(|(_, hotfix): &(_, Hotfix)| hotfix.hotfix_id == release) {
+				continue;
+			}
+			let images = [format!("{}/vmlinuz-{}", BOOT, release), format!("{}/{}/vmlinuz", directory, release)];
+			// Modules left behind by a removed kernel have no image
+			let Some(modified) = images.iter().find_map(|image| source.modified(image).ok()) else {
+				continue;
+			};
+			let time = modified.with_timezone(&Local).naive_local();
+			let description = if running.as_deref() == Some(release.as_str()) { "Kernel image, running" } else { "Kernel image" };
+			kernels.push((
+				time,
+				Hotfix {
+					hotfix_id: release,
+					description: description.to_string(),
+					installed_on: format_time(time),
+					..Default::default()
+				},
+			));
+		}
+	}
+	kernels.sort_by_key(|kernel| Reverse(kernel.0));
+	kernels.into_iter().map(|(_, hotfix)| hotfix).collect()
+}
+
+/// Account names by UID, for the user IDs dnf records.
+fn user_names(source: &dyn DataSource) -> HashMap<i64, String> {
+	let passwd = source.read_to_string("/etc/passwd").unwrap_or_default();
+	passwd
+		.lines()
+		.filter_map(|line| {
+			let fields: Vec<&str> = line.split(':').collect();
+			Some((fields.get(2)?.parse().ok()?, fields[0].to_string()))
+		})
+		.collect()
+}
+
+fn format_time(time: NaiveDateTime) -> String {
+	time.format("%Y-%m-%d %H:%M:%S").to_string()
 }
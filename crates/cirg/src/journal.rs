@@ -0,0 +1,191 @@
+//! Entries of systemd journal files and of `journalctl --output=export` streams, enough to read
+//! the journal through a `DataSource` without linking libsystemd.
+//!
+//! Journal files are scanned object by object rather than through their entry arrays, so entries
+//! come out in the order they were written. Fields compressed with XZ, which only journals from
+//! before systemd 246 use, are skipped.
+
+use anyhow::{Context, Result, anyhow, bail};
+use std::io::Read;
+
+const SIGNATURE: &[u8] = b"LPKSHHRH";
+const HEADER_INCOMPATIBLE_FLAGS: usize = 12;
+const HEADER_SIZE: usize = 88;
+const HEADER_ARENA_SIZE: usize = 96;
+const HEADER_TAIL_ENTRY_REALTIME: usize = 192;
+
+const INCOMPATIBLE_COMPRESSED_XZ: u32 = 1 << 0;
+const INCOMPATIBLE_COMPRESSED_LZ4: u32 = 1 << 1;
+const INCOMPATIBLE_KEYED_HASH: u32 = 1 << 2;
+const INCOMPATIBLE_COMPRESSED_ZSTD: u32 = 1 << 3;
+/// 32-bit item offsets in entries and entry arrays, and a longer data object header.
+const INCOMPATIBLE_COMPACT: u32 = 1 << 4;
+
+const OBJECT_DATA: u8 = 1;
+const OBJECT_ENTRY: u8 = 3;
+const OBJECT_COMPRESSED_XZ: u8 = 1 << 0;
+const OBJECT_COMPRESSED_LZ4: u8 = 1 << 1;
+const OBJECT_COMPRESSED_ZSTD: u8 = 1 << 2;
+
+/// One journal entry: a set of `FIELD=value` pairs stamped with the time journald received it.
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+	/// Microseconds since the Unix epoch.
+	pub(crate) realtime: u64,
+	pub(crate) fields: Vec<(String, Vec<u8>)>,
+}
+
+impl Entry {
+	pub(crate) fn field(&self, name: &str) -> Option<String> {
+		self.fields
+			.iter()
+			.find(|(field, _)| field == name)
+			.map(|(_, value)| String::from_utf8_lossy(value).into_owned())
+	}
+}
+
+/// Entries of a journal file received at or after `since`, in microseconds since the Unix epoch.
+pub(crate) fn read_entries(data: &[u8], since: u64) -> Result<Vec<Entry>> {
+	if !data.starts_with(SIGNATURE) {
+		bail!("Not a journal file");
+	}
+	let incompatible = read_u32(data, HEADER_INCOMPATIBLE_FLAGS)?;
+	let known = INCOMPATIBLE_COMPRESSED_XZ
+		| INCOMPATIBLE_COMPRESSED_LZ4
+		| INCOMPATIBLE_KEYED_HASH
+		| INCOMPATIBLE_COMPRESSED_ZSTD
+		| INCOMPATIBLE_COMPACT;
+	if incompatible & !known != 0 {
+		bail!("Unsupported journal file features {:#x}", incompatible & !known);
+	}
+	let compact = incompatible & INCOMPATIBLE_COMPACT != 0;
+
+	// Archived files that end before the window need no scan
+	let tail_realtime = read_u64(data, HEADER_TAIL_ENTRY_REALTIME)?;
+	if tail_realtime != 0 && tail_realtime < since {
+		return Ok(Vec::new());
+	}
+
+	let header_size = read_u64(data, HEADER_SIZE)? as usize;
+	let arena_end = header_size.saturating_add(read_u64(data, HEADER_ARENA_SIZE)? as usize).min(data.len());
+	let mut entries = Vec::new();
+	let mut offset = header_size;
+	// A file journald was writing to when it was copied can end in a partial object
+	while offset + 16 <= arena_end {
+		let size = read_u64(data, offset + 8)? as usize;
+		if size < 16 || offset + size > arena_end {
+			break;
+		}
+		if data[offset] == OBJECT_ENTRY && read_u64(data, offset + 24)? >= since {
+			entries.push(read_entry(data, offset, size, compact)?);
+		}
+		// Objects are 8-byte aligned
+		offset += size.div_ceil(8) * 8;
+	}
+	Ok(entries)
+}
+
+fn read_entry(data: &[u8], offset: usize, size: usize, compact: bool) -> Result<Entry> {
+	// Object header, seqnum, realtime, monotonic, boot id and xor hash come before the items
+	let realtime = read_u64(data, offset + 24)?;
+	// Compact items are a 32-bit data object offset; regular ones add the data's 64-bit hash
+	let item_size = if compact { 4 } else { 16 };
+
+	let mut fields = Vec::new();
+	for item in (offset + 64..offset + size).step_by(item_size) {
+		let data_offset = if compact { read_u32(data, item)? as usize } else { read_u64(data, item)? as usize };
+		// Fields that cannot be decompressed are left out rather than failing the entry
+		if let Ok(Some(field)) = read_data(data, data_offset, compact) {
+			fields.push(field);
+		}
+	}
+	Ok(Entry { realtime, fields })
+}
+
+/// The `FIELD=value` payload of a data object.
+fn read_data(data: &[u8], offset: usize, compact: bool) -> Result<Option<(String, Vec<u8>)>> {
+	let header = data.get(offset..offset + 16).context("Data object past the end of the file")?;
+	if header[0] != OBJECT_DATA {
+		bail!("Entry item at {:#x} is not a data object", offset);
+	}
+	let size = read_u64(data, offset + 8)? as usize;
+	// Hash, next hash, next field, entry, entry array and entry count; compact files add the tail
+	// entry array offset and count
+	let payload_start = offset + if compact { 72 } else { 64 };
+	let payload = data.get(payload_start..offset + size).context("Truncated data object")?;
+	let payload = decompress(header[1], payload)?;
+
+	let Some(split) = payload.iter().position(|&byte| byte == b'=') else {
+		return Ok(None);
+	};
+	let name = String::from_utf8_lossy(&payload[..split]).into_owned();
+	Ok(Some((name, payload[split + 1..].to_vec())))
+}
+
+fn decompress(flags: u8, payload: &[u8]) -> Result<Vec<u8>> {
+	if flags & OBJECT_COMPRESSED_ZSTD != 0 {
+		let mut decoder = ruzstd::decoding::StreamingDecoder::new(payload).map_err(|e| anyhow!("Invalid zstd frame: {}", e))?;
+		let mut plain = Vec::new();
+		decoder.read_to_end(&mut plain)?;
+		Ok(plain)
+	} else if flags & OBJECT_COMPRESSED_LZ4 != 0 {
+		// The uncompressed size as a 64-bit little-endian number, then a raw LZ4 block
+		let size = read_u64(payload, 0)? as usize;
+		Ok(lz4_flex::block::decompress(&payload[8..], size)?)
+	} else if flags & OBJECT_COMPRESSED_XZ != 0 {
+		bail!("XZ-compressed journal data is not supported")
+	} else {
+		Ok(payload.to_vec())
+	}
+}
+
+/// Entries of the journal export format: `FIELD=value` lines with a blank line after each entry,
+/// and fields holding newlines or binary data written as the name, a 64-bit little-endian length
+/// and the raw value.
+pub(crate) fn parse_export(data: &[u8]) -> Vec<Entry> {
+	let mut entries = Vec::new();
+	let mut fields = Vec::new();
+	let mut rest = data;
+	while !rest.is_empty() {
+		let end = rest.iter().position(|&byte| byte == b'\n').unwrap_or(rest.len());
+		let line = &rest[..end];
+		rest = rest.get(end + 1..).unwrap_or_default();
+
+		if line.is_empty() {
+			entries.extend(export_entry(std::mem::take(&mut fields)));
+		} else if let Some(split) = line.iter().position(|&byte| byte == b'=') {
+			fields.push((String::from_utf8_lossy(&line[..split]).into_owned(), line[split + 1..].to_vec()));
+		} else {
+			let Some(length) = rest.get(..8).map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap_or_default()) as usize)
+			else {
+				break;
+			};
+			let Some(value) = rest.get(8..8 + length) else {
+				break;
+			};
+			fields.push((String::from_utf8_lossy(line).into_owned(), value.to_vec()));
+			// The value is followed by a newline
+			rest = rest.get(8 + length + 1..).unwrap_or_default();
+		}
+	}
+	entries.extend(export_entry(fields));
+	entries
+}
+
+fn export_entry(fields: Vec<(String, Vec<u8>)>) -> Option<Entry> {
+	let realtime = fields
+		.iter()
+		.find(|(name, _)| name == "__REALTIME_TIMESTAMP")
+		.and_then(|(_, value)| std::str::from_utf8(value).ok()?.parse().ok())?;
+	Some(Entry { realtime, fields })
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+	let bytes = data.get(offset..offset + 4).context("Truncated journal file")?;
+	Ok(u32::from_le_bytes(bytes.try_into()?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+	let bytes = data.get(offset..offset + 8).context("Truncated journal file")?;
+	Ok(u64::from_le_bytes(bytes.try_into()?))
+}
@@ -1,29 +1,40 @@
-use std::collections::HashMap;
 use anyhow::anyhow;
-use wmi::Variant;
+use source::{DataSource, LiveSource, Row, Variant};
 
 pub mod audio_info;
+pub mod calendar;
 pub mod computer_info;
 pub mod cpu_info;
 pub mod disk_info;
+pub mod edid;
 pub mod environment_info;
 pub mod event_log_info;
 pub mod gpu_info;
 pub mod hotfix_info;
+mod journal;
+mod linux;
 pub mod memory_info;
 pub mod monitor_info;
 pub mod network_info;
 pub mod power_info;
 pub mod process_info;
+pub mod report;
 pub mod scheduled_task_info;
 pub mod security_info;
 pub mod service_info;
+pub mod smbios;
 pub mod software_info;
+mod sqlite;
+pub mod source;
 pub mod startup_info;
 pub mod usb_info;
 pub mod users_groups_info;
 
-// Helper trait for extracting values from WMI Variant HashMap
+pub use report::{
+	CollectOptions, Section, SectionResult, SectionStatus, SystemReport, collect, collect_all, collect_from, collect_with,
+};
+
+// Helper trait for extracting values from WMI rows and registry values
 pub(crate) trait VariantExt {
 	fn get_string(&self, key: &str) -> anyhow::Result<String>;
 	fn get_u16(&self, key: &str) -> anyhow::Result<u16>;
@@ -32,7 +43,7 @@ pub(crate) trait VariantExt {
 	fn get_bool(&self, key: &str) -> anyhow::Result<bool>;
 }
 
-impl VariantExt for HashMap<String, Variant> {
+impl VariantExt for Row {
 	fn get_string(&self, key: &str) -> anyhow::Result<String> {
 		match self.get(key) {
 			Some(Variant::String(s)) => Ok(s.clone()),
@@ -46,6 +57,9 @@ impl VariantExt for HashMap<String, Variant> {
 			Some(Variant::UI1(v)) => Ok(*v as u16),
 			Some(Variant::UI2(v)) => Ok(*v),
 			Some(Variant::I2(v)) => Ok(*v as u16),
+			// CIM uint16/uint32 properties are usually marshalled as VT_I4
+			Some(Variant::I4(v)) => u16::try_from(*v).map_err(|e| anyhow!("Value for key '{}' out of range: {}", key, e)),
+			Some(Variant::UI4(v)) => u16::try_from(*v).map_err(|e| anyhow!("Value for key '{}' out of range: {}", key, e)),
 			None => Err(anyhow!("Key '{}' not found", key)),
 			Some(_) => Err(anyhow!("Value for key '{}' is not a u16-compatible type", key)),
 		}
@@ -84,6 +98,26 @@ impl VariantExt for HashMap<String, Variant> {
 	}
 }
 
-pub trait ComputerInfoExt{
-	fn fetch()->anyhow::Result<Self> where Self: Sized;
+pub trait ComputerInfoExt {
+	/// Collects from the machine the process is running on.
+	fn fetch() -> anyhow::Result<Self>
+	where
+		Self: Sized,
+	{
+		Self::fetch_from(&LiveSource::new())
+	}
+
+	/// Collects through the given data source, e.g. a replayed fixture.
+	fn fetch_from(source: &dyn DataSource) -> anyhow::Result<Self>
+	where
+		Self: Sized;
+
+	/// Collects through the given data source with the given settings. Only sections that have
+	/// settings override this.
+	fn fetch_with(source: &dyn DataSource, _options: &CollectOptions) -> anyhow::Result<Self>
+	where
+		Self: Sized,
+	{
+		Self::fetch_from(source)
+	}
 }
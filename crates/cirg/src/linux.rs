@@ -0,0 +1,147 @@
+//! Helpers shared by the Linux collectors, which read `/proc`, `/sys` and `/etc` through a
+//! `DataSource` rather than the filesystem directly.
+
+use crate::source::DataSource;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Locations of the PCI ID database on the common distributions.
+const PCI_IDS_PATHS: &[&str] = &["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids", "/usr/share/pci.ids"];
+
+/// Locations of the USB ID database; usbutils keeps its own copy on some distributions.
+const USB_IDS_PATHS: &[&str] = &["/usr/share/hwdata/usb.ids", "/var/lib/usbutils/usb.ids", "/usr/share/misc/usb.ids"];
+
+/// Holds the UID and GID ranges `useradd` picks human accounts from.
+const LOGIN_DEFS: &str = "/etc/login.defs";
+
+/// `useradd` defaults when login.defs does not set a range.
+const DEFAULT_ID_RANGE: (u32, u32) = (1000, 60000);
+
+/// Reads a single-value file such as `/proc/sys/kernel/hostname`, without the trailing newline.
+pub(crate) fn read_value(source: &dyn DataSource, path: &str) -> Result<String> {
+	let value = source
+		.read_to_string(path)
+		.with_context(|| format!("Failed to read {}", path))?;
+	Ok(value.trim().to_string())
+}
+
+/// Like `read_value`, but treats a missing or empty file as absent.
+pub(crate) fn read_optional(source: &dyn DataSource, path: &str) -> Option<String> {
+	read_value(source, path).ok().filter(|value| !value.is_empty())
+}
+
+/// Machine hardware name as reported by `uname -m`, e.g. `x86_64` or `aarch64`.
+pub(crate) fn kernel_arch(source: &dyn DataSource) -> String {
+	read_optional(source, "/proc/sys/kernel/arch").unwrap_or_else(|| std::env::consts::ARCH.to_string())
+}
+
+/// Expands a kernel CPU list such as `0-3,8,10-11` into the CPU numbers it names.
+pub(crate) fn parse_cpu_list(list: &str) -> Vec<u32> {
+	list.trim()
+		.split(',')
+		.filter(|range| !range.is_empty())
+		.flat_map(|range| {
+			let (start, end) = range.split_once('-').unwrap_or((range, range));
+			let start = start.trim().parse::<u32>().unwrap_or(u32::MAX);
+			let end = end.trim().parse::<u32>().unwrap_or(0);
+			start..=end
+		})
+		.collect()
+}
+
+/// Parses `KEY=value` lines as used by os-release and `/etc/environment`. Blank lines and
+/// comments are skipped and one level of single or double quotes is removed.
+pub(crate) fn parse_key_values(text: &str) -> BTreeMap<String, String> {
+	text.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.filter_map(|line| line.split_once('='))
+		.map(|(key, value)| (key.trim().to_string(), unquote(value.trim()).to_string()))
+		.collect()
+}
+
+pub(crate) fn unquote(value: &str) -> &str {
+	for quote in ['"', '\''] {
+		if let Some(inner) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+			return inner;
+		}
+	}
+	value
+}
+
+/// UIDs of human accounts: UID_MIN to UID_MAX from `/etc/login.defs`. Everything outside is root,
+/// a service account or nobody.
+pub(crate) fn uid_range(source: &dyn DataSource) -> RangeInclusive<u32> {
+	id_range(source, "UID_MIN", "UID_MAX")
+}
+
+/// GIDs of the groups created for human accounts, from GID_MIN and GID_MAX.
+pub(crate) fn gid_range(source: &dyn DataSource) -> RangeInclusive<u32> {
+	id_range(source, "GID_MIN", "GID_MAX")
+}
+
+/// A range such as UID_MIN and UID_MAX from `/etc/login.defs`, which holds `KEY value` lines.
+fn id_range(source: &dyn DataSource, min: &str, max: &str) -> RangeInclusive<u32> {
+	let login_defs = source.read_to_string(LOGIN_DEFS).unwrap_or_default();
+	let value = |key: &str| {
+		login_defs
+			.lines()
+			.filter_map(|line| line.trim().split_once(char::is_whitespace))
+			.find(|(name, _)| *name == key)
+			.and_then(|(_, value)| value.trim().parse().ok())
+	};
+	value(min).unwrap_or(DEFAULT_ID_RANGE.0)..=value(max).unwrap_or(DEFAULT_ID_RANGE.1)
+}
+
+/// Name and home directory of root and of every human account in `/etc/passwd`.
+pub(crate) fn user_homes(source: &dyn DataSource) -> Vec<(String, String)> {
+	let passwd = source.read_to_string("/etc/passwd").unwrap_or_default();
+	let humans = uid_range(source);
+	passwd
+		.lines()
+		.filter_map(|line| {
+			// alex:x:1000:1000:Alex,,,:/home/alex:/bin/bash
+			let fields: Vec<&str> = line.split(':').collect();
+			let (uid, home) = (fields.get(2)?.parse::<u32>().ok()?, fields.get(5)?);
+			(uid == 0 || humans.contains(&uid)).then(|| (fields[0].to_string(), home.to_string()))
+		})
+		.collect()
+}
+
+/// Contents of the first `pci.ids` database found, if the system has one.
+pub(crate) fn read_pci_ids(source: &dyn DataSource) -> Option<String> {
+	PCI_IDS_PATHS.iter().find_map(|path| source.read_to_string(path).ok())
+}
+
+/// Contents of the first `usb.ids` database found, if the system has one.
+pub(crate) fn read_usb_ids(source: &dyn DataSource) -> Option<String> {
+	USB_IDS_PATHS.iter().find_map(|path| source.read_to_string(path).ok())
+}
+
+/// Vendor and device names from a `pci.ids` or `usb.ids` database, for lower-case hex ids.
+pub(crate) fn lookup_ids(database: &str, vendor: &str, device: &str) -> (Option<String>, Option<String>) {
+	let mut vendor_name = None;
+	for line in database.lines() {
+		if line.starts_with('#') || line.is_empty() {
+			continue;
+		}
+		match vendor_name {
+			None => {
+				if let Some(name) = line.strip_prefix(vendor).and_then(|rest| rest.strip_prefix("  ")) {
+					vendor_name = Some(name.to_string());
+				}
+			}
+			Some(_) => {
+				// Devices are indented by one tab, their subsystems by two
+				let Some(entry) = line.strip_prefix('\t') else {
+					break;
+				};
+				if let Some(name) = entry.strip_prefix(device).and_then(|rest| rest.strip_prefix("  ")) {
+					return (vendor_name, Some(name.to_string()));
+				}
+			}
+		}
+	}
+	(vendor_name, None)
+}
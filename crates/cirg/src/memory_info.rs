@@ -1,14 +1,37 @@
+use crate::report;
+use crate::smbios::{self, SmbiosTable};
+use crate::source::{CIMV2, DataSource, Platform};
 use crate::{ComputerInfoExt, VariantExt};
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use wmi::Variant;
+use std::collections::BTreeMap;
+
+const MB: u64 = 1024 * 1024;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct MemoryInfo {
 	pub slots: Vec<MemorySlot>,
 	pub total_slots: u32,
 	pub max_capacity_gb: u64,
+	pub error_correction: String,
+	pub usage: MemoryUsage,
+}
+
+/// Current RAM, swap/pagefile and commit figures, in MB.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MemoryUsage {
+	pub total_mb: u64,
+	pub available_mb: u64,
+	pub used_mb: u64,
+	pub used_pct: f32,
+	/// Page cache, or the standby and modified lists on Windows.
+	pub cached_mb: u64,
+	/// Block device buffers. Always 0 on Windows, which has no separate figure.
+	pub buffers_mb: u64,
+	pub swap_total_mb: u64,
+	pub swap_used_mb: u64,
+	pub commit_limit_mb: Option<u64>,
+	pub committed_mb: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -20,14 +43,27 @@ pub struct MemorySlot {
 	pub form_factor: String,
 	pub manufacturer: String,
 	pub part_number: String,
+	pub serial_number: String,
+	/// Number of ranks, 0 when not reported.
+	pub rank: u32,
+	pub configured_speed_mhz: u32,
+	pub voltage_mv: u32,
+	pub ecc: bool,
 }
 
 impl ComputerInfoExt for MemoryInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Memory collection")),
+		}
+	}
+}
 
-		let array_results: Vec<HashMap<String, Variant>> =
-			com.raw_query("SELECT * FROM Win32_PhysicalMemoryArray")?;
+impl MemoryInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let array_results = source.wmi_query(CIMV2, "SELECT * FROM Win32_PhysicalMemoryArray")?;
 		let array_data = array_results.first();
 
 		let total_slots = array_data
@@ -37,9 +73,13 @@ impl ComputerInfoExt for MemoryInfo {
 			.and_then(|d| d.get_u64("MaxCapacity").ok())
 			.unwrap_or(0);
 		let max_capacity_gb = max_capacity_kb / (1024 * 1024);
+		let error_correction = array_data
+			.and_then(|d| d.get_u16("MemoryErrorCorrection").ok())
+			.map(|code| smbios::memory_error_correction(code as u8))
+			.unwrap_or("Unknown")
+			.to_string();
 
-		let mem_results: Vec<HashMap<String, Variant>> =
-			com.raw_query("SELECT * FROM Win32_PhysicalMemory")?;
+		let mem_results = source.wmi_query(CIMV2, "SELECT * FROM Win32_PhysicalMemory")?;
 
 		let slots = mem_results
 			.iter()
@@ -48,23 +88,13 @@ impl ComputerInfoExt for MemoryInfo {
 				let capacity_gb = capacity_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
 
 				let type_code = data.get_u16("SMBIOSMemoryType").unwrap_or(0);
-				let memory_type = match type_code {
-					20 => "DDR",
-					21 => "DDR2",
-					24 => "DDR3",
-					26 => "DDR4",
-					34 => "DDR5",
-					_ => "Unknown",
-				}
-				.to_string();
+				let memory_type = smbios::memory_type(type_code as u8).to_string();
 
 				let ff_code = data.get_u16("FormFactor").unwrap_or(0);
-				let form_factor = match ff_code {
-					8 => "DIMM",
-					12 => "SO-DIMM",
-					_ => "Unknown",
-				}
-				.to_string();
+				let form_factor = cim_form_factor(ff_code).to_string();
+
+				let total_width = data.get_u16("TotalWidth").unwrap_or(0);
+				let data_width = data.get_u16("DataWidth").unwrap_or(0);
 
 				MemorySlot {
 					bank_label: data.get_string("DeviceLocator").unwrap_or_default(),
@@ -78,6 +108,12 @@ impl ComputerInfoExt for MemoryInfo {
 						.unwrap_or_default()
 						.trim()
 						.to_string(),
+					serial_number: data.get_string("SerialNumber").unwrap_or_default().trim().to_string(),
+					// Attributes is the SMBIOS attributes byte, whose low nibble is the rank
+					rank: data.get_u32("Attributes").unwrap_or(0) & 0x0F,
+					configured_speed_mhz: data.get_u32("ConfiguredClockSpeed").unwrap_or(0),
+					voltage_mv: data.get_u32("ConfiguredVoltage").unwrap_or(0),
+					ecc: data_width > 0 && total_width > data_width,
 				}
 			})
 			.collect();
@@ -86,6 +122,209 @@ impl ComputerInfoExt for MemoryInfo {
 			slots,
 			total_slots,
 			max_capacity_gb,
+			error_correction,
+			usage: MemoryUsage::fetch_windows(source),
+		})
+	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let mut memory = match SmbiosTable::read(source) {
+			Ok(table) => Self::from_smbios(&table),
+			Err(e) => {
+				report::warn(format!("Memory modules unavailable without the SMBIOS table: {:#}", e));
+				MemoryInfo::default()
+			}
+		};
+		memory.usage = MemoryUsage::fetch_linux(source)?;
+		Ok(memory)
+	}
+
+	/// Builds the slot list from the physical memory array (type 16) and memory device (type 17)
+	/// structures of a raw SMBIOS table.
+	pub fn from_smbios(table: &SmbiosTable) -> Self {
+		let arrays = table
+			.memory_arrays()
+			.into_iter()
+			.filter(|array| array.array_use == "System Memory")
+			.collect::<Vec<_>>();
+		let devices = table
+			.memory_devices()
+			.into_iter()
+			.filter(|device| arrays.is_empty() || arrays.iter().any(|a| a.handle == device.array_handle))
+			.collect::<Vec<_>>();
+
+		let total_slots = match arrays.iter().map(|a| a.device_count as u32).sum() {
+			0 => devices.len() as u32,
+			n => n,
+		};
+		let max_capacity_kb: u64 = arrays.iter().map(|a| a.maximum_capacity_kb).sum();
+
+		// Like Win32_PhysicalMemory, only populated slots are listed
+		let slots = devices
+			.iter()
+			.filter(|device| device.size_kb > 0)
+			.map(|device| MemorySlot {
+				bank_label: device.device_locator.clone(),
+				capacity_gb: device.size_kb as f64 / (1024.0 * 1024.0),
+				speed_mhz: device.speed,
+				memory_type: device.memory_type.to_string(),
+				form_factor: device.form_factor.to_string(),
+				manufacturer: device.manufacturer.clone(),
+				part_number: device.part_number.clone(),
+				serial_number: device.serial_number.clone(),
+				rank: device.rank.unwrap_or(0) as u32,
+				configured_speed_mhz: device.configured_speed,
+				voltage_mv: device.configured_voltage_mv as u32,
+				ecc: device.ecc(),
+			})
+			.collect();
+
+		MemoryInfo {
+			slots,
+			total_slots,
+			max_capacity_gb: max_capacity_kb / (1024 * 1024),
+			error_correction: arrays
+				.first()
+				.map(|array| array.error_correction)
+				.unwrap_or("Unknown")
+				.to_string(),
+			usage: MemoryUsage::default(),
+		}
+	}
+}
+
+impl MemoryUsage {
+	fn fetch_windows(source: &dyn DataSource) -> Self {
+		let query = "SELECT TotalVisibleMemorySize, FreePhysicalMemory FROM Win32_OperatingSystem";
+		let (total_kb, free_kb) = match source.wmi_query(CIMV2, query) {
+			Ok(results) => {
+				let kb = |key: &str| results.first().and_then(|data| data.get_u64(key).ok()).unwrap_or(0);
+				(kb("TotalVisibleMemorySize"), kb("FreePhysicalMemory"))
+			}
+			Err(e) => {
+				report::warn(format!("Physical memory totals unavailable: {}", e));
+				(0, 0)
+			}
+		};
+
+		// One row per page file, sized in MB
+		let query = "SELECT AllocatedBaseSize, CurrentUsage FROM Win32_PageFileUsage";
+		let (swap_total_mb, swap_used_mb) = match source.wmi_query(CIMV2, query) {
+			Ok(results) => {
+				let sum = |key: &str| results.iter().filter_map(|data| data.get_u32(key).ok()).map(u64::from).sum();
+				(sum("AllocatedBaseSize"), sum("CurrentUsage"))
+			}
+			Err(e) => {
+				report::warn(format!("Page file usage unavailable: {}", e));
+				(0, 0)
+			}
+		};
+
+		let mut usage = MemoryUsage::new(total_kb / 1024, free_kb / 1024, swap_total_mb, swap_used_mb);
+
+		let query = "SELECT * FROM Win32_PerfFormattedData_PerfOS_Memory";
+		match source.wmi_query(CIMV2, query) {
+			Ok(results) => {
+				if let Some(data) = results.first() {
+					let bytes = |key: &str| data.get_u64(key).ok().map(|b| b / MB);
+					usage.commit_limit_mb = bytes("CommitLimit");
+					usage.committed_mb = bytes("CommittedBytes");
+					// Task Manager's "Cached" is the standby lists plus modified pages
+					usage.cached_mb = [
+						"StandbyCacheCoreBytes",
+						"StandbyCacheNormalPriorityBytes",
+						"StandbyCacheReserveBytes",
+						"ModifiedPageListBytes",
+					]
+					.iter()
+					.filter_map(|key| bytes(key))
+					.sum();
+				}
+			}
+			Err(e) => report::warn(format!("Memory performance counters unavailable: {}", e)),
+		}
+
+		usage
+	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let meminfo = parse_meminfo(&source.read_to_string("/proc/meminfo")?);
+		let kb = |key: &str| meminfo.get(key).copied();
+		let total = kb("MemTotal").ok_or_else(|| anyhow!("MemTotal missing from /proc/meminfo"))?;
+		let buffers = kb("Buffers").unwrap_or(0);
+		let cached = kb("Cached").unwrap_or(0);
+		// MemAvailable only exists since Linux 3.14; before that free + cache was the usual estimate
+		let available = kb("MemAvailable").unwrap_or_else(|| kb("MemFree").unwrap_or(0) + buffers + cached);
+		let swap_total = kb("SwapTotal").unwrap_or(0);
+
+		let mut usage = MemoryUsage::new(
+			total / 1024,
+			available / 1024,
+			swap_total / 1024,
+			swap_total.saturating_sub(kb("SwapFree").unwrap_or(0)) / 1024,
+		);
+		usage.cached_mb = cached / 1024;
+		usage.buffers_mb = buffers / 1024;
+		usage.commit_limit_mb = kb("CommitLimit").map(|kb| kb / 1024);
+		usage.committed_mb = kb("Committed_AS").map(|kb| kb / 1024);
+		Ok(usage)
+	}
+
+	fn new(total_mb: u64, available_mb: u64, swap_total_mb: u64, swap_used_mb: u64) -> Self {
+		let used_mb = total_mb.saturating_sub(available_mb);
+		MemoryUsage {
+			total_mb,
+			available_mb,
+			used_mb,
+			used_pct: if total_mb > 0 {
+				used_mb as f32 / total_mb as f32 * 100.0
+			} else {
+				0.0
+			},
+			swap_total_mb,
+			swap_used_mb,
+			..MemoryUsage::default()
+		}
+	}
+}
+
+/// Parses `/proc/meminfo` lines such as `MemTotal:       16318480 kB` into KB values.
+fn parse_meminfo(text: &str) -> BTreeMap<String, u64> {
+	text.lines()
+		.filter_map(|line| {
+			let (key, value) = line.split_once(':')?;
+			let value = value.split_whitespace().next()?.parse().ok()?;
+			Some((key.trim().to_string(), value))
 		})
+		.collect()
+}
+
+/// `Win32_PhysicalMemory.FormFactor` uses the CIM enumeration, not the SMBIOS one.
+fn cim_form_factor(code: u16) -> &'static str {
+	match code {
+		1 => "Other",
+		2 => "SIP",
+		3 => "DIP",
+		4 => "ZIP",
+		5 => "SOJ",
+		6 => "Proprietary",
+		7 => "SIMM",
+		8 => "DIMM",
+		9 => "TSOP",
+		10 => "PGA",
+		11 => "RIMM",
+		12 => "SO-DIMM",
+		13 => "SRIMM",
+		14 => "SMD",
+		15 => "SSMP",
+		16 => "QFP",
+		17 => "TQFP",
+		18 => "SOIC",
+		19 => "LCC",
+		20 => "PLCC",
+		21 => "BGA",
+		22 => "FPBGA",
+		23 => "LGA",
+		_ => "Unknown",
 	}
 }
@@ -1,10 +1,12 @@
-use crate::ComputerInfoExt;
+use crate::edid::Edid;
+use crate::linux::read_optional;
+use crate::source::{DataSource, Platform, Row, Variant};
+use crate::{ComputerInfoExt, VariantExt, report};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use windows::Win32::Graphics::Gdi::*;
-use windows::core::PCWSTR;
-use wmi::Variant;
+#[cfg(target_os = "windows")]
+use windows::{Win32::Graphics::Gdi::*, core::PCWSTR};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct MonitorInfo {
@@ -17,54 +19,148 @@ pub struct Monitor {
 	pub name: String,
 	pub serial_number: String,
 	pub year_of_manufacture: u16,
+	/// Output the monitor is attached to, e.g. `\\.\DISPLAY1` or `card0-DP-1`.
+	pub connector: String,
+	/// Physical link, e.g. `DisplayPort` or `HDMI`.
+	pub connection_type: String,
+	/// Current mode, or `N/A` when the monitor is not part of the desktop.
 	pub resolution: String,
 	pub refresh_rate: u32,
+	/// Preferred mode from the EDID.
+	pub native_resolution: String,
+	pub width_mm: u32,
+	pub height_mm: u32,
+	pub diagonal_inches: f32,
+	/// Bits per color channel, 0 if the monitor does not say.
+	pub bit_depth: u8,
+	pub hdr_formats: Vec<String>,
+	/// Modes listed in the EDID, largest first, e.g. `3840x2160@60`.
+	pub supported_modes: Vec<String>,
+}
+
+/// Current mode of an active display adapter output.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DisplayMode {
+	pub device_name: String,
+	pub width: u32,
+	pub height: u32,
+	pub refresh_rate: u32,
+	/// Device interface paths of the monitors showing this output, used to pair it with
+	/// `WmiMonitorID`. Empty on Linux, where `device_name` is the connector itself.
+	#[serde(default)]
+	pub monitor_ids: Vec<String>,
 }
 
 impl ComputerInfoExt for MonitorInfo {
-	fn fetch() -> Result<Self> {
-		let com_wmi = wmi::WMIConnection::with_namespace_path(r"root\wmi")?;
-		let results: Vec<HashMap<String, Variant>> =
-			com_wmi.raw_query("SELECT * FROM WmiMonitorID")?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Monitor collection")),
+		}
+	}
+}
+
+impl MonitorInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let results = source.wmi_query(r"root\wmi", "SELECT * FROM WmiMonitorID")?;
 
 		// Use Win32 API for resolution/refresh rate (per-monitor, not per-GPU)
-		let display_modes = fetch_display_modes();
+		let display_modes = source.display_modes().unwrap_or_else(|e| {
+			report::warn(format!("Current display modes unavailable: {}", e));
+			Vec::new()
+		});
+		let mut modes_by_monitor = HashMap::new();
+		for mode in &display_modes {
+			for id in &mode.monitor_ids {
+				modes_by_monitor.insert(monitor_key(id), mode);
+			}
+		}
+
+		let output_technologies: HashMap<String, i64> = source
+			.wmi_query(r"root\wmi", "SELECT InstanceName, VideoOutputTechnology FROM WmiMonitorConnectionParams")
+			.unwrap_or_default()
+			.into_iter()
+			.filter_map(|row| {
+				let instance = row.get_string("InstanceName").ok()?;
+				let technology = match row.get("VideoOutputTechnology")? {
+					Variant::I4(v) => i64::from(*v),
+					Variant::UI4(v) => i64::from(*v),
+					_ => return None,
+				};
+				Some((instance, technology))
+			})
+			.collect();
 
 		let monitors = results
 			.iter()
-			.enumerate()
-			.map(|(idx, data)| {
-				let manufacturer = data
-					.get("ManufacturerName")
-					.map(decode_wmi_byte_array)
-					.unwrap_or_default();
-				let name = data
-					.get("UserFriendlyName")
-					.map(decode_wmi_byte_array)
-					.unwrap_or_default();
-				let serial = data
-					.get("SerialNumberID")
-					.map(decode_wmi_byte_array)
-					.unwrap_or_default();
-				let year = match data.get("YearOfManufacture") {
-					Some(Variant::UI2(y)) => *y,
-					Some(Variant::I2(y)) => *y as u16,
-					_ => 0,
+			.map(|data| {
+				let instance = data.get_string("InstanceName").unwrap_or_default();
+				let mut monitor = Monitor {
+					manufacturer: data.get("ManufacturerName").map(decode_wmi_byte_array).unwrap_or_default(),
+					name: data.get("UserFriendlyName").map(decode_wmi_byte_array).unwrap_or_default(),
+					serial_number: data.get("SerialNumberID").map(decode_wmi_byte_array).unwrap_or_default(),
+					year_of_manufacture: data.get_u16("YearOfManufacture").unwrap_or(0),
+					connection_type: output_technologies
+						.get(&instance)
+						.and_then(|&technology| output_technology_name(technology))
+						.unwrap_or_default()
+						.to_string(),
+					resolution: "N/A".to_string(),
+					..Monitor::default()
+				};
+
+				match read_wmi_edid(source, &instance).and_then(|bytes| Edid::parse(&bytes)) {
+					Ok(edid) => monitor.apply_edid(&edid),
+					Err(e) => report::warn(format!("Failed to read the EDID of {}: {:#}", instance, e)),
+				}
+
+				if let Some(mode) = modes_by_monitor.get(&monitor_key(&instance)) {
+					monitor.connector = mode.device_name.clone();
+					monitor.set_mode(mode.width, mode.height, mode.refresh_rate);
+				}
+				monitor
+			})
+			.collect();
+
+		Ok(MonitorInfo { monitors })
+	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let Ok(entries) = source.read_dir("/sys/class/drm") else {
+			return Ok(MonitorInfo::default());
+		};
+		let modes = source.display_modes().unwrap_or_else(|e| {
+			report::warn(format!("Current display modes unavailable, using each connector's preferred mode: {}", e));
+			Vec::new()
+		});
+
+		let monitors = entries
+			.iter()
+			.filter(|entry| entry.starts_with("card") && entry.contains('-'))
+			.filter(|connector| {
+				read_optional(source, &format!("/sys/class/drm/{}/status", connector)).as_deref() == Some("connected")
+			})
+			.map(|connector| {
+				let mut monitor = Monitor {
+					connector: connector.clone(),
+					..Monitor::default()
 				};
 
-				let (resolution, refresh_rate) = display_modes
-					.get(idx)
-					.cloned()
-					.unwrap_or_else(|| ("N/A".to_string(), 0));
-
-				Monitor {
-					manufacturer,
-					name,
-					serial_number: serial,
-					year_of_manufacture: year,
-					resolution,
-					refresh_rate,
+				// The kernel leaves the file empty when the monitor did not answer the DDC probe
+				let edid = source.read_file(&format!("/sys/class/drm/{}/edid", connector)).unwrap_or_default();
+				if !edid.is_empty() {
+					match Edid::parse(&edid) {
+						Ok(edid) => monitor.apply_edid(&edid),
+						Err(e) => report::warn(format!("Failed to decode the EDID of {}: {:#}", connector, e)),
+					}
+				}
+				if let Some(connection_type) = connector_type_name(connector) {
+					monitor.connection_type = connection_type.to_string();
 				}
+
+				(monitor.resolution, monitor.refresh_rate) = connector_mode(source, connector, &modes);
+				monitor
 			})
 			.collect();
 
@@ -72,9 +168,176 @@ impl ComputerInfoExt for MonitorInfo {
 	}
 }
 
+impl Monitor {
+	/// Fills in what the EDID says, keeping identity fields the OS already provided.
+	fn apply_edid(&mut self, edid: &Edid) {
+		if self.manufacturer.is_empty() {
+			self.manufacturer = edid.manufacturer_id.clone();
+		}
+		if self.name.is_empty() {
+			self.name = edid.name.clone().unwrap_or_else(|| format!("{}{:04X}", edid.manufacturer_id, edid.product_code));
+		}
+		if self.serial_number.is_empty() {
+			self.serial_number = edid
+				.serial_string
+				.clone()
+				.or_else(|| edid.serial_number.map(|serial| serial.to_string()))
+				.unwrap_or_default();
+		}
+		if self.year_of_manufacture == 0 {
+			self.year_of_manufacture = edid.year;
+		}
+		if self.connection_type.is_empty() {
+			self.connection_type = edid.interface.unwrap_or_default().to_string();
+		}
+
+		self.native_resolution = edid
+			.preferred
+			.map(|timing| format!("{}x{}", timing.width, timing.height))
+			.unwrap_or_default();
+		(self.width_mm, self.height_mm) = edid.physical_size_mm().unwrap_or_default();
+		self.diagonal_inches = edid.diagonal_inches().unwrap_or_default();
+		self.bit_depth = edid.bit_depth.unwrap_or_default();
+		self.hdr_formats = edid.hdr_formats.iter().map(|format| format.to_string()).collect();
+		self.supported_modes = edid.modes.iter().map(|timing| timing.to_string()).collect();
+	}
+
+	fn set_mode(&mut self, width: u32, height: u32, refresh_rate: u32) {
+		self.resolution = if width > 0 && height > 0 {
+			format!("{}x{}", width, height)
+		} else {
+			"N/A".to_string()
+		};
+		self.refresh_rate = refresh_rate;
+	}
+}
+
+/// Current mode of a DRM connector, falling back to the first entry of its `modes` file, which
+/// is the preferred mode and usually the one in use.
+pub(crate) fn connector_mode(source: &dyn DataSource, connector: &str, modes: &[DisplayMode]) -> (String, u32) {
+	match modes.iter().find(|mode| mode.device_name == connector) {
+		Some(mode) => (format!("{}x{}", mode.width, mode.height), mode.refresh_rate),
+		None => (
+			source
+				.read_to_string(&format!("/sys/class/drm/{}/modes", connector))
+				.ok()
+				.and_then(|modes| modes.lines().next().map(String::from))
+				.unwrap_or_else(|| "N/A".to_string()),
+			0,
+		),
+	}
+}
+
+/// Link type from a DRM connector name such as `card0-HDMI-A-1`.
+fn connector_type_name(connector: &str) -> Option<&'static str> {
+	let (_, kind) = connector.split_once('-')?;
+	let kind = kind.rsplit_once('-').map_or(kind, |(kind, _)| kind);
+	Some(match kind {
+		"DP" => "DisplayPort",
+		"eDP" => "DisplayPort (embedded)",
+		"HDMI-A" | "HDMI-B" => "HDMI",
+		"DVI-I" | "DVI-D" | "DVI-A" => "DVI",
+		"VGA" => "VGA",
+		"LVDS" => "LVDS",
+		"DSI" => "DSI",
+		"DPI" => "DPI",
+		"SPI" => "SPI",
+		"USB" => "USB",
+		"Composite" => "Composite",
+		"SVIDEO" => "S-Video",
+		"Component" => "Component",
+		"DIN" => "DIN",
+		"TV" => "TV",
+		"Virtual" | "Writeback" => "Virtual",
+		_ => return None,
+	})
+}
+
+/// `D3DKMDT_VIDEO_OUTPUT_TECHNOLOGY` as reported by `WmiMonitorConnectionParams`.
+fn output_technology_name(technology: i64) -> Option<&'static str> {
+	Some(match technology {
+		0 => "VGA",
+		1 => "S-Video",
+		2 => "Composite",
+		3 => "Component",
+		4 => "DVI",
+		5 => "HDMI",
+		6 => "LVDS",
+		8 => "D-Jpn",
+		9 => "SDI",
+		10 => "DisplayPort",
+		11 => "DisplayPort (embedded)",
+		12 => "UDI",
+		13 => "UDI (embedded)",
+		14 => "SDTV dongle",
+		15 => "Miracast",
+		16 => "Indirect (wired)",
+		0x8000_0000 => "Internal",
+		_ => return None,
+	})
+}
+
+/// Reads the base EDID block and its extensions, one `WmiGetMonitorRawEEdidV1Block` call each.
+fn read_wmi_edid(source: &dyn DataSource, instance: &str) -> Result<Vec<u8>> {
+	let object_path = format!(
+		r#"WmiMonitorDescriptorMethods.InstanceName="{}""#,
+		instance.replace('\\', r"\\")
+	);
+	let read_block = |block: u8| -> Result<Vec<u8>> {
+		let args = Row::from([("BlockId".to_string(), Variant::UI1(block))]);
+		let output = source.wmi_method(r"root\wmi", &object_path, "WmiGetMonitorRawEEdidV1Block", &args)?;
+		match output.get("BlockContent") {
+			Some(Variant::Array(bytes)) => Ok(bytes
+				.iter()
+				.filter_map(|b| match b {
+					Variant::UI1(b) => Some(*b),
+					_ => None,
+				})
+				.collect()),
+			_ => Err(anyhow::anyhow!("Block {} has no content", block)),
+		}
+	};
+
+	let mut edid = read_block(0)?;
+	let extensions = edid.get(126).copied().unwrap_or(0);
+	for block in 1..=extensions {
+		match read_block(block) {
+			Ok(content) => edid.extend(content),
+			Err(_) => break,
+		}
+	}
+	Ok(edid)
+}
+
+/// Reduces `DISPLAY\DEL41B8\5&1234&0&UID4352_0` (a `WmiMonitorID` instance) and
+/// `\\?\DISPLAY#DEL41B8#5&1234&0&UID4352#{e6f07b5f-...}` (a monitor device interface) to the
+/// same key.
+fn monitor_key(id: &str) -> String {
+	let id = id.trim_start_matches(r"\\?\");
+	let id = match id.rsplit_once('#') {
+		Some((device, guid)) if guid.starts_with('{') => device,
+		_ => id,
+	};
+	let id = match id.rsplit_once('_') {
+		Some((device, index)) if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) => device,
+		_ => id,
+	};
+	id.replace('\\', "#").to_uppercase()
+}
+
 /// Enumerate active displays using Win32 `EnumDisplayDevicesW` / `EnumDisplaySettingsW`.
-/// Returns a Vec of (resolution_string, refresh_rate_hz) in OS display order.
-fn fetch_display_modes() -> Vec<(String, u32)> {
+/// Returns the current mode of each active display in OS display order, with the device
+/// interface paths of the monitors attached to it.
+#[cfg(target_os = "windows")]
+pub(crate) fn fetch_display_modes() -> Vec<DisplayMode> {
+	/// Makes `EnumDisplayDevicesW` return the device interface path in `DeviceID`.
+	const EDD_GET_DEVICE_INTERFACE_NAME: u32 = 0x1;
+
+	fn wide_to_string(wide: &[u16]) -> String {
+		let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+		String::from_utf16_lossy(&wide[..len])
+	}
+
 	let mut displays = Vec::new();
 	let mut dev_num = 0u32;
 
@@ -104,15 +367,38 @@ fn fetch_display_modes() -> Vec<(String, u32)> {
 				)
 			};
 			if ok.as_bool() {
-				let w = devmode.dmPelsWidth;
-				let h = devmode.dmPelsHeight;
-				let hz = devmode.dmDisplayFrequency;
-				let res = if w > 0 && h > 0 {
-					format!("{}x{}", w, h)
-				} else {
-					"N/A".to_string()
-				};
-				displays.push((res, hz));
+				// Enumerating with the adapter's name lists the monitors on that output
+				let mut monitor_ids = Vec::new();
+				let mut monitor_num = 0u32;
+				loop {
+					let mut monitor = DISPLAY_DEVICEW {
+						cb: std::mem::size_of::<DISPLAY_DEVICEW>() as u32,
+						..Default::default()
+					};
+					let ok = unsafe {
+						EnumDisplayDevicesW(
+							PCWSTR(device.DeviceName.as_ptr()),
+							monitor_num,
+							&mut monitor,
+							EDD_GET_DEVICE_INTERFACE_NAME,
+						)
+					};
+					if !ok.as_bool() {
+						break;
+					}
+					if (monitor.StateFlags & DISPLAY_DEVICE_ACTIVE).0 != 0 {
+						monitor_ids.push(wide_to_string(&monitor.DeviceID));
+					}
+					monitor_num += 1;
+				}
+
+				displays.push(DisplayMode {
+					device_name: wide_to_string(&device.DeviceName),
+					width: devmode.dmPelsWidth,
+					height: devmode.dmPelsHeight,
+					refresh_rate: devmode.dmDisplayFrequency,
+					monitor_ids,
+				});
 			}
 		}
 
@@ -1,8 +1,13 @@
-use crate::{ComputerInfoExt, VariantExt};
+use crate::linux::{parse_key_values, read_optional};
+use crate::source::{CIMV2, DataSource, Platform, Variant};
+use crate::{ComputerInfoExt, VariantExt, report};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use wmi::Variant;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// `ARPHRD_LOOPBACK` in `/sys/class/net/*/type`.
+const ARPHRD_LOOPBACK: &str = "772";
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct NetworkInfo {
@@ -22,11 +27,27 @@ pub struct NetworkAdapter {
 	pub gateway: String,
 }
 
+/// An address assigned to a network interface.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceAddress {
+	pub interface: String,
+	pub address: IpAddr,
+}
+
 impl ComputerInfoExt for NetworkInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Network collection")),
+		}
+	}
+}
 
-		let adapter_results: Vec<HashMap<String, Variant>> = com.raw_query(
+impl NetworkInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let adapter_results = source.wmi_query(
+			CIMV2,
 			"SELECT Index, Name, Speed, NetConnectionID, MACAddress FROM Win32_NetworkAdapter WHERE NetEnabled=True",
 		)?;
 
@@ -43,7 +64,8 @@ impl ComputerInfoExt for NetworkInfo {
 			}
 		}
 
-		let config_results: Vec<HashMap<String, Variant>> = com.raw_query(
+		let config_results = source.wmi_query(
+			CIMV2,
 			"SELECT * FROM Win32_NetworkAdapterConfiguration WHERE IPEnabled=True",
 		)?;
 
@@ -100,6 +122,227 @@ impl ComputerInfoExt for NetworkInfo {
 
 		Ok(NetworkInfo { adapters })
 	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let addresses = source.interface_addresses().unwrap_or_else(|e| {
+			report::warn(format!("Failed to list interface addresses: {}", e));
+			Vec::new()
+		});
+		let gateways = read_default_gateways(source);
+		let dns_servers = read_dns_servers(source);
+
+		let mut adapters = Vec::new();
+		for name in source.read_dir("/sys/class/net")? {
+			let dir = format!("/sys/class/net/{}", name);
+			if read_optional(source, &format!("{}/type", dir)).as_deref() == Some(ARPHRD_LOOPBACK) {
+				continue;
+			}
+
+			let mut ipv4_addresses = Vec::new();
+			let mut ipv6_addresses = Vec::new();
+			for entry in addresses.iter().filter(|entry| entry.interface == name) {
+				match entry.address {
+					IpAddr::V4(ip) => ipv4_addresses.push(ip.to_string()),
+					IpAddr::V6(ip) => ipv6_addresses.push(ip.to_string()),
+				}
+			}
+			// Windows lists adapters that are connected or have IP enabled
+			let up = read_optional(source, &format!("{}/operstate", dir)).as_deref() == Some("up");
+			if !up && ipv4_addresses.is_empty() && ipv6_addresses.is_empty() {
+				continue;
+			}
+
+			let mac_address = read_optional(source, &format!("{}/address", dir))
+				.filter(|mac| mac != "00:00:00:00:00:00")
+				.unwrap_or_default()
+				.to_uppercase();
+			// Mbps, or -1 (or EINVAL) when the link is down or the driver does not know
+			let speed_mbps = read_optional(source, &format!("{}/speed", dir))
+				.and_then(|speed| speed.parse::<u64>().ok())
+				.unwrap_or(0);
+			let ifindex = read_optional(source, &format!("{}/ifindex", dir)).unwrap_or_default();
+			let gateway = gateways.get(&name).cloned().unwrap_or_default();
+
+			// systemd-networkd records per-link DNS servers; otherwise the resolver's servers
+			// apply to whichever adapter carries the default route
+			let link_state = source
+				.read_to_string(&format!("/run/systemd/netif/links/{}", ifindex))
+				.map(|text| parse_key_values(&text))
+				.unwrap_or_default();
+			let dns_servers = match link_state.get("DNS") {
+				Some(servers) if !servers.is_empty() => servers.split_whitespace().map(String::from).collect(),
+				_ if !gateway.is_empty() => dns_servers.clone(),
+				_ => Vec::new(),
+			};
+
+			adapters.push(NetworkAdapter {
+				description: describe_interface(source, &dir),
+				mac_address,
+				speed: format_speed(speed_mbps * 1_000_000),
+				ipv4_addresses,
+				ipv6_addresses,
+				dns_servers,
+				dhcp_enabled: has_dhcp_lease(source, &name, &ifindex),
+				gateway,
+				name,
+			});
+		}
+
+		Ok(NetworkInfo { adapters })
+	}
+}
+
+/// Interface name -> default gateway, from the lowest-metric default route. IPv4 routes win
+/// over IPv6 ones, matching the order Windows reports `DefaultIPGateway` in.
+fn read_default_gateways(source: &dyn DataSource) -> HashMap<String, String> {
+	let mut routes: Vec<(bool, u32, String, IpAddr)> = Vec::new();
+
+	// Iface Destination Gateway Flags RefCnt Use Metric Mask MTU Window IRTT, addresses as
+	// native-endian hex of the network-order bytes
+	if let Ok(text) = source.read_to_string("/proc/net/route") {
+		for fields in text.lines().skip(1).map(|line| line.split_whitespace().collect::<Vec<_>>()) {
+			if fields.len() < 8 || fields[1] != "00000000" || fields[7] != "00000000" {
+				continue;
+			}
+			let gateway = u32::from_str_radix(fields[2], 16).unwrap_or(0);
+			let metric = fields[6].parse().unwrap_or(u32::MAX);
+			if gateway != 0 {
+				routes.push((false, metric, fields[0].to_string(), Ipv4Addr::from(gateway.to_ne_bytes()).into()));
+			}
+		}
+	}
+
+	// Destination PrefixLen Source PrefixLen NextHop Metric RefCnt Use Flags Iface, in hex
+	if let Ok(text) = source.read_to_string("/proc/net/ipv6_route") {
+		for fields in text.lines().map(|line| line.split_whitespace().collect::<Vec<_>>()) {
+			if fields.len() < 10 || fields[1] != "00" || u128::from_str_radix(fields[0], 16) != Ok(0) {
+				continue;
+			}
+			let next_hop = u128::from_str_radix(fields[4], 16).unwrap_or(0);
+			let metric = u32::from_str_radix(fields[5], 16).unwrap_or(u32::MAX);
+			if next_hop != 0 {
+				routes.push((true, metric, fields[9].to_string(), Ipv6Addr::from(next_hop).into()));
+			}
+		}
+	}
+
+	routes.sort_by_key(|(ipv6, metric, _, _)| (*ipv6, *metric));
+	let mut gateways = HashMap::new();
+	for (_, _, interface, gateway) in routes {
+		gateways.entry(interface).or_insert_with(|| gateway.to_string());
+	}
+	gateways
+}
+
+/// Name servers from `/etc/resolv.conf`, looking past systemd-resolved's local stub listener
+/// to the upstream servers it forwards to.
+fn read_dns_servers(source: &dyn DataSource) -> Vec<String> {
+	let servers = read_nameservers(source, "/etc/resolv.conf");
+	let is_stub = |server: &String| server == "127.0.0.53" || server == "127.0.0.54";
+	if !servers.is_empty() && servers.iter().all(is_stub) {
+		let upstream = read_nameservers(source, "/run/systemd/resolve/resolv.conf");
+		if !upstream.is_empty() {
+			return upstream;
+		}
+	}
+	servers
+}
+
+fn read_nameservers(source: &dyn DataSource, path: &str) -> Vec<String> {
+	let Ok(text) = source.read_to_string(path) else {
+		return Vec::new();
+	};
+	text.lines()
+		.filter_map(|line| {
+			let mut fields = line.split_whitespace();
+			match (fields.next(), fields.next()) {
+				(Some("nameserver"), Some(server)) => Some(server.to_string()),
+				_ => None,
+			}
+		})
+		.collect()
+}
+
+/// Whether a DHCP client holds a lease for the interface: systemd-networkd, NetworkManager
+/// (internal client or dhclient plugin), standalone dhclient or dhcpcd.
+fn has_dhcp_lease(source: &dyn DataSource, name: &str, ifindex: &str) -> bool {
+	if source.read_file(&format!("/run/systemd/netif/leases/{}", ifindex)).is_ok() {
+		return true;
+	}
+	let contains = |dir: &str, matches: &dyn Fn(&str) -> bool| {
+		source
+			.read_dir(dir)
+			.map(|files| files.iter().any(|file| matches(file)))
+			.unwrap_or(false)
+	};
+	let network_manager = format!("-{}.lease", name);
+	let dhclient = format!("dhclient.{}.leases", name);
+	let dhcpcd = [format!("{}.lease", name), format!("dhcpcd-{}.lease", name)];
+
+	contains("/var/lib/NetworkManager", &|file| file.ends_with(&network_manager))
+		|| contains("/var/lib/dhcp", &|file| file == dhclient)
+		|| contains("/var/lib/dhcpcd", &|file| dhcpcd.iter().any(|lease| file == lease))
+}
+
+/// Kind of interface and its driver, e.g. `Ethernet (igc)` or `Bridge`.
+fn describe_interface(source: &dyn DataSource, dir: &str) -> String {
+	let uevent = source
+		.read_to_string(&format!("{}/uevent", dir))
+		.map(|text| parse_key_values(&text))
+		.unwrap_or_default();
+	let virtual_device = source.read_link(dir).is_ok_and(|target| target.contains("/virtual/"));
+
+	let kind = match uevent.get("DEVTYPE").map(String::as_str) {
+		Some("wlan") => "Wireless".to_string(),
+		Some("bridge") => "Bridge".to_string(),
+		Some("vlan") => "VLAN".to_string(),
+		Some("wireguard") => "WireGuard".to_string(),
+		Some(devtype) => devtype.to_string(),
+		None if virtual_device => "Virtual".to_string(),
+		None => "Ethernet".to_string(),
+	};
+
+	match source.read_link(&format!("{}/device/driver", dir)) {
+		Ok(driver) => format!("{} ({})", kind, driver.rsplit('/').next().unwrap_or_default()),
+		Err(_) => kind,
+	}
+}
+
+#[cfg(unix)]
+pub(crate) fn query_interface_addresses() -> Result<Vec<InterfaceAddress>> {
+	let mut list: *mut libc::ifaddrs = std::ptr::null_mut();
+	// SAFETY: getifaddrs allocates the list, which is only read below and freed once
+	if unsafe { libc::getifaddrs(&mut list) } != 0 {
+		return Err(std::io::Error::last_os_error().into());
+	}
+
+	let mut addresses = Vec::new();
+	let mut entry = list;
+	while let Some(ifa) = unsafe { entry.as_ref() } {
+		entry = ifa.ifa_next;
+		let Some(addr) = (unsafe { ifa.ifa_addr.as_ref() }) else {
+			continue;
+		};
+		// SAFETY: sa_family says which sockaddr variant ifa_addr points to
+		let address = match i32::from(addr.sa_family) {
+			libc::AF_INET => {
+				let sin = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in) };
+				IpAddr::V4(Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr)))
+			}
+			libc::AF_INET6 => {
+				let sin6 = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in6) };
+				IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr))
+			}
+			_ => continue,
+		};
+		let interface = unsafe { std::ffi::CStr::from_ptr(ifa.ifa_name) }
+			.to_string_lossy()
+			.into_owned();
+		addresses.push(InterfaceAddress { interface, address });
+	}
+
+	unsafe { libc::freeifaddrs(list) };
+	Ok(addresses)
 }
 
 fn extract_string_array(variant: &Variant) -> Vec<String> {
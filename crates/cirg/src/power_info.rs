@@ -1,14 +1,14 @@
-use crate::{ComputerInfoExt, VariantExt};
+use crate::linux::{parse_key_values, read_optional};
+use crate::source::{CIMV2, DataSource, Platform};
+use crate::{ComputerInfoExt, VariantExt, report};
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::os::windows::process::CommandExt;
-use std::process::Command;
-use wmi::Variant;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PowerInfo {
     pub plan: String,
+    /// `AC` or `Battery`.
+    pub power_source: String,
     pub battery: Option<BatteryInfo>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -20,16 +20,22 @@ pub struct BatteryInfo {
     pub design_capacity: String,
     pub full_charge_capacity: String,
     pub chemistry: String,
+    pub cycle_count: String,
 }
 
 impl ComputerInfoExt for PowerInfo {
-    fn fetch() -> Result<Self> {
-        let output = Command::new("powercfg")
-            .arg("/getactivescheme")
-            .creation_flags(0x08000000)
-            .output()?;
+    fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+        match source.platform() {
+            Platform::Windows => Self::fetch_windows(source),
+            Platform::Linux => Self::fetch_linux(source),
+            platform => Err(platform.unsupported("Power collection")),
+        }
+    }
+}
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
+impl PowerInfo {
+    fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+        let stdout = source.command("powercfg", &["/getactivescheme"])?.stdout;
         // Parse output: "Power Scheme GUID: <guid>  (<name>)"
         let plan = stdout
             .lines()
@@ -44,19 +50,96 @@ impl ComputerInfoExt for PowerInfo {
                 }
             })
             .unwrap_or_else(|| "Unknown".to_string());
-        let battery = BatteryInfo::fetch().ok();
+        // No battery is a normal result on desktops; only a failed query is worth a warning
+        let battery = BatteryInfo::query(source)
+            .inspect_err(|e| report::warn(format!("Battery query failed: {:#}", e)))
+            .ok()
+            .flatten();
+        let power_source = match &battery {
+            Some(battery) if battery.on_battery => "Battery",
+            _ => "AC",
+        }
+        .to_string();
 
-        Ok(PowerInfo { plan, battery })
+        Ok(PowerInfo {
+            plan,
+            power_source,
+            battery: battery.map(|battery| battery.info),
+        })
+    }
+
+    fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+        let supplies = source.read_dir(POWER_SUPPLY).unwrap_or_default();
+        let supply_type = |name: &String| read_optional(source, &format!("{}/{}/type", POWER_SUPPLY, name));
+
+        // Peripherals such as wireless mice report their batteries here too, with scope Device
+        let battery = supplies
+            .iter()
+            .filter(|name| supply_type(name).as_deref() == Some("Battery"))
+            .find(|name| read_optional(source, &format!("{}/{}/scope", POWER_SUPPLY, name)).as_deref() != Some("Device"))
+            .map(|name| BatteryInfo::read_power_supply(source, &format!("{}/{}", POWER_SUPPLY, name)));
+
+        let ac_online = supplies
+            .iter()
+            .filter(|name| matches!(supply_type(name).as_deref(), Some("Mains" | "USB")))
+            .any(|name| read_optional(source, &format!("{}/{}/online", POWER_SUPPLY, name)).as_deref() == Some("1"));
+        let power_source = match &battery {
+            Some(battery) if !ac_online && battery.status == "Discharging" => "Battery",
+            _ => "AC",
+        }
+        .to_string();
+
+        Ok(PowerInfo {
+            plan: Self::linux_power_plan(source),
+            power_source,
+            battery,
+        })
+    }
+
+    /// The power-profiles-daemon profile, or else the cpufreq governor of the first CPU.
+    fn linux_power_plan(source: &dyn DataSource) -> String {
+        let profile = source
+            .read_to_string("/var/lib/power-profiles-daemon/state.ini")
+            .ok()
+            .and_then(|state| parse_key_values(&state).remove("Profile"));
+        if let Some(profile) = profile {
+            return match profile.as_str() {
+                "power-saver" => "Power saver".to_string(),
+                "balanced" => "Balanced".to_string(),
+                "performance" => "Performance".to_string(),
+                _ => profile,
+            };
+        }
+        read_optional(source, "/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor")
+            .map(|governor| format!("{} (cpufreq governor)", governor))
+            .unwrap_or_else(|| "Unknown".to_string())
     }
 }
 
+const POWER_SUPPLY: &str = "/sys/class/power_supply";
+
+/// A Windows battery together with whether `BatteryStatus` says it is discharging.
+struct WindowsBattery {
+    info: BatteryInfo,
+    on_battery: bool,
+}
+
 impl ComputerInfoExt for BatteryInfo {
-    fn fetch() -> Result<Self> {
-        let com = wmi::WMIConnection::new()?;
-        let results: Vec<HashMap<String, Variant>> =
-            com.raw_query("SELECT * FROM Win32_Battery")?;
+    fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+        Self::query(source)?
+            .map(|battery| battery.info)
+            .ok_or_else(|| anyhow!("No battery found"))
+    }
+}
+
+impl BatteryInfo {
+    /// Returns `Ok(None)` when the machine has no battery.
+    fn query(source: &dyn DataSource) -> Result<Option<WindowsBattery>> {
+        let results = source.wmi_query(CIMV2, "SELECT * FROM Win32_Battery")?;
 
-        let data = results.first().ok_or_else(|| anyhow!("No battery found"))?;
+        let Some(data) = results.first() else {
+            return Ok(None);
+        };
 
         let chemistry_code = data.get_u16("Chemistry").unwrap_or(0);
         let chemistry = match chemistry_code {
@@ -69,7 +152,10 @@ impl ComputerInfoExt for BatteryInfo {
             _ => "Other",
         };
 
-        Ok(BatteryInfo {
+        // 1 is discharging, 4 and 5 are low and critical, which also only happen on battery
+        let on_battery = matches!(data.get_u16("BatteryStatus"), Ok(1 | 4 | 5));
+
+        let info = BatteryInfo {
             name: data.get_string("Name")?,
             status: data.get_string("Status")?,
             charge_pct: data.get_u16("EstimatedChargeRemaining")?.to_string(),
@@ -83,6 +169,61 @@ impl ComputerInfoExt for BatteryInfo {
                 .map(|v| v.to_string())
                 .unwrap_or_else(|_| "Unknown".to_string()),
             chemistry: chemistry.to_string(),
-        })
+            cycle_count: "Unknown".to_string(),
+        };
+        Ok(Some(WindowsBattery { info, on_battery }))
+    }
+
+    /// Reads a battery from `/sys/class/power_supply/<name>`. Capacities are in mWh like
+    /// `Win32_Battery`; batteries that only report charge in µAh are converted with their
+    /// design voltage.
+    fn read_power_supply(source: &dyn DataSource, dir: &str) -> Self {
+        let read = |file: &str| read_optional(source, &format!("{}/{}", dir, file));
+        let number = |file: &str| read(file).and_then(|value| value.parse::<u64>().ok());
+        let voltage_uv = number("voltage_min_design").or_else(|| number("voltage_now"));
+        // energy_* is in µWh, charge_* in µAh
+        let energy_mwh = |name: &str| {
+            number(&format!("energy_{}", name))
+                .map(|uwh| uwh / 1000)
+                .or_else(|| Some(number(&format!("charge_{}", name))? * voltage_uv? / 1_000_000_000))
+        };
+        let power_uw = number("power_now").or_else(|| Some(number("current_now")? * voltage_uv? / 1_000_000));
+
+        let status = read("status").unwrap_or_else(|| "Unknown".to_string());
+        let run_time_mins = match (status.as_str(), energy_mwh("now"), power_uw) {
+            ("Discharging", Some(now_mwh), Some(power_uw)) if power_uw > 0 => (now_mwh * 60_000 / power_uw).to_string(),
+            _ => "Unknown".to_string(),
+        };
+
+        let name = [read("manufacturer"), read("model_name")]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let chemistry = match read("technology").as_deref() {
+            Some("Li-ion") => "Lithium-ion",
+            Some("Li-poly") => "Lithium Polymer",
+            Some("LiFe") => "Lithium Iron Phosphate",
+            Some("LiMn") => "Lithium Manganese",
+            Some("NiMH") => "Nickel Metal Hydride",
+            Some("NiCd") => "Nickel Cadmium",
+            _ => "Unknown",
+        };
+        let unknown = || "Unknown".to_string();
+
+        BatteryInfo {
+            name: if name.is_empty() { dir.rsplit('/').next().unwrap_or_default().to_string() } else { name },
+            charge_pct: read("capacity").unwrap_or_else(unknown),
+            run_time_mins,
+            design_capacity: energy_mwh("full_design").map(|mwh| mwh.to_string()).unwrap_or_else(unknown),
+            full_charge_capacity: energy_mwh("full").map(|mwh| mwh.to_string()).unwrap_or_else(unknown),
+            chemistry: chemistry.to_string(),
+            // Many firmwares report 0 rather than leaving the count out
+            cycle_count: number("cycle_count")
+                .filter(|&count| count > 0)
+                .map(|count| count.to_string())
+                .unwrap_or_else(unknown),
+            status,
+        }
     }
 }
@@ -1,7 +1,22 @@
 use crate::ComputerInfoExt;
-use anyhow::Result;
+use crate::report::{CollectOptions, Unsupported};
+use crate::source::DataSource;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
-use sysinfo::System;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, ThreadKind, Users};
+
+/// Processes reported by default. Their ancestors follow them so the tree stays connected up to
+/// its root.
+const DEFAULT_COUNT: usize = 30;
+
+/// Time between the two reads of the process table that CPU usage and I/O rates are taken over;
+/// sysinfo needs at least `MINIMUM_CPU_UPDATE_INTERVAL` for CPU usage to mean anything.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProcessInfo {
@@ -12,45 +27,218 @@ pub struct ProcessInfo {
 pub struct ProcessEntry {
 	pub name: String,
 	pub pid: u32,
+	/// CPU time used since the process started, across all of its threads.
 	pub cpu_seconds: u64,
+	/// Resident memory.
 	pub memory_mb: f64,
 	pub exe_path: String,
 	pub command: String,
+	/// None for the roots of the tree, such as init or the System process.
+	#[serde(default)]
+	pub parent_pid: Option<u32>,
+	/// Account the process runs as.
+	#[serde(default)]
+	pub user: String,
+	/// Local time, as YYYY-MM-DD HH:MM:SS.
+	#[serde(default)]
+	pub start_time: String,
+	/// Running, sleeping, zombie and so on.
+	#[serde(default)]
+	pub status: String,
+	/// Only known on Linux.
+	#[serde(default)]
+	pub threads: Option<u32>,
+	#[serde(default)]
+	pub working_directory: String,
+	/// Address space the process has mapped, resident or not.
+	#[serde(default)]
+	pub virtual_memory_mb: f64,
+	/// Usage over the sample interval, in percent of one core, so above 100 for busy
+	/// multithreaded processes.
+	#[serde(default)]
+	pub cpu_percent: f64,
+	#[serde(default)]
+	pub read_bytes_per_sec: f64,
+	#[serde(default)]
+	pub write_bytes_per_sec: f64,
+}
+
+/// Which processes the report keeps, and the order they are listed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessOptions {
+	pub sort: ProcessSort,
+	pub count: ProcessCount,
+}
+
+impl Default for ProcessOptions {
+	fn default() -> Self {
+		ProcessOptions { sort: ProcessSort::Memory, count: ProcessCount::Top(DEFAULT_COUNT) }
+	}
+}
+
+/// What the processes are ranked by, highest or newest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessSort {
+	Cpu,
+	#[default]
+	Memory,
+	/// Bytes read and written per second.
+	Io,
+	StartTime,
+}
+
+impl FromStr for ProcessSort {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+			"cpu" => Ok(ProcessSort::Cpu),
+			"memory" => Ok(ProcessSort::Memory),
+			"io" => Ok(ProcessSort::Io),
+			"start_time" => Ok(ProcessSort::StartTime),
+			_ => Err(anyhow!("Unknown process sort '{}', expected cpu, memory, io or start-time", s.trim())),
+		}
+	}
+}
+
+/// How many of the ranked processes are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessCount {
+	Top(usize),
+	All,
+}
+
+impl FromStr for ProcessCount {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let s = s.trim();
+		if s.eq_ignore_ascii_case("all") {
+			return Ok(ProcessCount::All);
+		}
+		s.parse()
+			.map(ProcessCount::Top)
+			.map_err(|_| anyhow!("Invalid process count '{}', expected a number or all", s))
+	}
 }
 
 impl ComputerInfoExt for ProcessInfo {
-	fn fetch() -> Result<Self> {
-		let mut sys = System::new_all();
-		sys.refresh_all();
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		Self::fetch_with(source, &CollectOptions::default())
+	}
 
-		let mut processes: Vec<ProcessEntry> = sys
+	fn fetch_with(source: &dyn DataSource, options: &CollectOptions) -> Result<Self> {
+		// A recording or captured tree would otherwise be reported with this machine's processes
+		if !source.is_live() {
+			return Err(Unsupported("Process collection needs the live machine".to_string()).into());
+		}
+		Self::fetch_with_options(options.processes)
+	}
+}
+
+impl ProcessInfo {
+	/// Reads the process table twice, `SAMPLE_INTERVAL` apart, and keeps the processes `options`
+	/// ranks highest. sysinfo reads the process table directly, so this section is always live.
+	pub fn fetch_with_options(options: ProcessOptions) -> Result<Self> {
+		let mut sys = System::new();
+		sys.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::everything());
+		let sampled = Instant::now();
+		std::thread::sleep(SAMPLE_INTERVAL);
+		sys.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::everything());
+		let elapsed = sampled.elapsed().as_secs_f64();
+		let users = Users::new_with_refreshed_list();
+
+		let mut processes: Vec<(u64, ProcessEntry)> = sys
 			.processes()
 			.values()
-			.map(|p| ProcessEntry {
-				name: p.name().to_string_lossy().to_string(),
-				pid: p.pid().as_u32(),
-				cpu_seconds: p.run_time(),
-				memory_mb: p.memory() as f64 / (1024.0 * 1024.0),
-				exe_path: p
-					.exe()
-					.map(|e| e.to_string_lossy().to_string())
-					.unwrap_or_default(),
-				command: p
-					.cmd()
-					.iter()
-					.map(|s| s.to_string_lossy())
-					.collect::<Vec<_>>()
-					.join(" "),
+			// On Linux the threads of a process are listed as tasks of their own
+			.filter(|p| p.thread_kind() != Some(ThreadKind::Userland))
+			.map(|p| {
+				// Counted since the previous refresh, so over the sample interval
+				let disk = p.disk_usage();
+				let entry = ProcessEntry {
+					name: p.name().to_string_lossy().to_string(),
+					pid: p.pid().as_u32(),
+					cpu_seconds: p.accumulated_cpu_time() / 1000,
+					memory_mb: p.memory() as f64 / (1024.0 * 1024.0),
+					exe_path: p
+						.exe()
+						.map(|e| e.to_string_lossy().to_string())
+						.unwrap_or_default(),
+					command: p
+						.cmd()
+						.iter()
+						.map(|s| s.to_string_lossy())
+						.collect::<Vec<_>>()
+						.join(" "),
+					parent_pid: p
+						.parent()
+						// Windows reuses pids, so a parent that started after the child is an unrelated process
+						.filter(|pid| sys.process(*pid).is_none_or(|parent| parent.start_time() <= p.start_time()))
+						.map(|pid| pid.as_u32()),
+					user: p
+						.user_id()
+						.and_then(|uid| users.get_user_by_id(uid))
+						.map(|user| user.name().to_string())
+						.unwrap_or_default(),
+					start_time: DateTime::from_timestamp(p.start_time() as i64, 0)
+						.map(|time| time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+						.unwrap_or_default(),
+					status: p.status().to_string(),
+					// The main thread is not among its own tasks
+					threads: p.tasks().map(|tasks| tasks.len() as u32 + 1),
+					working_directory: p
+						.cwd()
+						.map(|cwd| cwd.to_string_lossy().to_string())
+						.unwrap_or_default(),
+					virtual_memory_mb: p.virtual_memory() as f64 / (1024.0 * 1024.0),
+					cpu_percent: p.cpu_usage() as f64,
+					read_bytes_per_sec: disk.read_bytes as f64 / elapsed,
+					write_bytes_per_sec: disk.written_bytes as f64 / elapsed,
+				};
+				(p.start_time(), entry)
 			})
 			.collect();
 
-		processes.sort_by(|a, b| {
-			b.memory_mb
-				.partial_cmp(&a.memory_mb)
-				.unwrap_or(std::cmp::Ordering::Equal)
+		let descending = |a: f64, b: f64| b.partial_cmp(&a).unwrap_or(Ordering::Equal);
+		processes.sort_by(|(a_start, a), (b_start, b)| match options.sort {
+			ProcessSort::Cpu => descending(a.cpu_percent, b.cpu_percent),
+			ProcessSort::Memory => descending(a.memory_mb, b.memory_mb),
+			ProcessSort::Io => descending(
+				a.read_bytes_per_sec + a.write_bytes_per_sec,
+				b.read_bytes_per_sec + b.write_bytes_per_sec,
+			),
+			ProcessSort::StartTime => b_start.cmp(a_start),
 		});
-		processes.truncate(30);
+		let processes = processes.into_iter().map(|(_, process)| process).collect();
 
+		let processes = match options.count {
+			ProcessCount::Top(count) => with_ancestors(processes, count),
+			ProcessCount::All => processes,
+		};
 		Ok(ProcessInfo { processes })
 	}
 }
+
+/// The first `count` processes, followed by whichever of the rest are their ancestors.
+fn with_ancestors(processes: Vec<ProcessEntry>, count: usize) -> Vec<ProcessEntry> {
+	let parents: HashMap<u32, Option<u32>> = processes.iter().map(|p| (p.pid, p.parent_pid)).collect();
+	let mut ancestors = HashSet::new();
+	for process in processes.iter().take(count) {
+		let mut parent = process.parent_pid;
+		// A pid seen before means the rest of the chain is already in
+		while let Some(pid) = parent
+			&& ancestors.insert(pid)
+		{
+			parent = parents.get(&pid).copied().flatten();
+		}
+	}
+	processes
+		.into_iter()
+		.enumerate()
+		.filter(|(index, process)| *index < count || ancestors.contains(&process.pid))
+		.map(|(_, process)| process)
+		.collect()
+}
@@ -0,0 +1,321 @@
+use crate::ComputerInfoExt;
+use crate::audio_info::AudioInfo;
+use crate::computer_info::ComputerInfo;
+use crate::cpu_info::CpuInfo;
+use crate::disk_info::DiskInfo;
+use crate::environment_info::EnvironmentInfo;
+use crate::event_log_info::EventLogInfo;
+use crate::gpu_info::GpuInfo;
+use crate::hotfix_info::HotfixInfo;
+use crate::memory_info::MemoryInfo;
+use crate::monitor_info::MonitorInfo;
+use crate::network_info::NetworkInfo;
+use crate::power_info::PowerInfo;
+use crate::process_info::{ProcessInfo, ProcessOptions};
+use crate::scheduled_task_info::ScheduledTaskInfo;
+use crate::security_info::SecurityInfo;
+use crate::service_info::ServiceInfo;
+use crate::software_info::SoftwareInfo;
+use crate::source::{DataSource, LiveSource};
+use crate::startup_info::StartupInfo;
+use crate::usb_info::UsbInfo;
+use crate::users_groups_info::UsersGroupsInfo;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+/// Declares every report section in one place: the `Section` enum, the matching
+/// `SystemReport` field and the collector that fills it.
+macro_rules! sections {
+	($($variant:ident => $field:ident: $ty:ty, $title:literal;)*) => {
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+		#[serde(rename_all = "snake_case")]
+		pub enum Section {
+			$($variant,)*
+		}
+
+		impl Section {
+			/// Every section, in report order.
+			pub const ALL: &'static [Section] = &[$(Section::$variant,)*];
+
+			/// Field name used for this section in the serialized report.
+			pub fn key(self) -> &'static str {
+				match self {
+					$(Section::$variant => stringify!($field),)*
+				}
+			}
+
+			/// Human-readable section title.
+			pub fn title(self) -> &'static str {
+				match self {
+					$(Section::$variant => $title,)*
+				}
+			}
+
+			fn collector(self) -> Collector {
+				match self {
+					$(Section::$variant => |source, options| <$ty as ComputerInfoExt>::fetch_with(source, options).map(SectionData::$variant),)*
+				}
+			}
+		}
+
+		#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+		pub struct SystemReport {
+			$(pub $field: SectionResult<$ty>,)*
+		}
+
+		// Only lives between a collector returning and the report taking it apart
+		#[allow(clippy::large_enum_variant)]
+		enum SectionData {
+			$($variant($ty),)*
+		}
+
+		impl SystemReport {
+			/// Outcome of a section without its data.
+			pub fn outcome(&self, section: Section) -> SectionOutcome<'_> {
+				match section {
+					$(Section::$variant => self.$field.outcome(),)*
+				}
+			}
+
+			fn insert(&mut self, section: Section, result: SectionResult<SectionData>) {
+				match section {
+					$(Section::$variant => self.$field = result.map(|data| match data {
+						SectionData::$variant(value) => value,
+						_ => unreachable!("collector returned data for another section"),
+					}),)*
+				}
+			}
+		}
+	};
+}
+
+sections! {
+	Computer => computer: ComputerInfo, "Computer";
+	Cpu => cpu: CpuInfo, "CPU";
+	Gpu => gpu: GpuInfo, "GPU";
+	Memory => memory: MemoryInfo, "Memory";
+	Disk => disk: DiskInfo, "Disk";
+	Network => network: NetworkInfo, "Network";
+	Monitor => monitor: MonitorInfo, "Monitor";
+	Audio => audio: AudioInfo, "Audio";
+	Usb => usb: UsbInfo, "USB";
+	Power => power: PowerInfo, "Power";
+	Security => security: SecurityInfo, "Security";
+	Process => process: ProcessInfo, "Processes";
+	Service => service: ServiceInfo, "Services";
+	Startup => startup: StartupInfo, "Startup";
+	Software => software: SoftwareInfo, "Software";
+	Hotfix => hotfix: HotfixInfo, "Hotfixes";
+	UsersGroups => users_groups: UsersGroupsInfo, "Users & Groups";
+	Environment => environment: EnvironmentInfo, "Environment";
+	EventLog => event_log: EventLogInfo, "Event Log";
+	ScheduledTask => scheduled_task: ScheduledTaskInfo, "Scheduled Tasks";
+}
+
+impl FromStr for Section {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let s = s.trim();
+		Section::ALL
+			.iter()
+			.copied()
+			.find(|section| section.key().eq_ignore_ascii_case(s))
+			.ok_or_else(|| anyhow!("Unknown section '{}'", s))
+	}
+}
+
+/// Settings for the collectors that take any. The defaults are what `collect` uses.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollectOptions {
+	pub processes: ProcessOptions,
+}
+
+type Collector = fn(&dyn DataSource, &CollectOptions) -> Result<SectionData>;
+
+/// How collecting a section turned out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionStatus {
+	Ok,
+	Failed,
+	#[default]
+	Skipped,
+	Unsupported,
+}
+
+/// A single section of the report together with how it was collected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionResult<T> {
+	pub status: SectionStatus,
+	pub data: Option<T>,
+	/// Error chain, outermost context first. Empty unless the section failed.
+	pub errors: Vec<String>,
+	/// Non-fatal problems, e.g. a query that fell back to a less accurate source.
+	pub warnings: Vec<String>,
+	pub duration_ms: u64,
+}
+
+impl<T> Default for SectionResult<T> {
+	fn default() -> Self {
+		SectionResult {
+			status: SectionStatus::Skipped,
+			data: None,
+			errors: Vec::new(),
+			warnings: Vec::new(),
+			duration_ms: 0,
+		}
+	}
+}
+
+impl<T> SectionResult<T> {
+	pub fn outcome(&self) -> SectionOutcome<'_> {
+		SectionOutcome {
+			status: self.status,
+			errors: &self.errors,
+			warnings: &self.warnings,
+			duration_ms: self.duration_ms,
+		}
+	}
+
+	fn map<U>(self, f: impl FnOnce(T) -> U) -> SectionResult<U> {
+		SectionResult {
+			status: self.status,
+			data: self.data.map(f),
+			errors: self.errors,
+			warnings: self.warnings,
+			duration_ms: self.duration_ms,
+		}
+	}
+}
+
+/// Borrowed view of a section's status, errors, warnings and timing.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SectionOutcome<'a> {
+	pub status: SectionStatus,
+	pub errors: &'a [String],
+	pub warnings: &'a [String],
+	pub duration_ms: u64,
+}
+
+/// Error returned by a collector when the section cannot be gathered on this platform.
+#[derive(Debug, Clone)]
+pub struct Unsupported(pub String);
+
+impl fmt::Display for Unsupported {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+impl std::error::Error for Unsupported {}
+
+thread_local! {
+	static WARNINGS: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Records a non-fatal warning against the section currently being collected on this thread.
+/// Outside of a collector the warning is only logged.
+pub fn warn(message: impl Into<String>) {
+	let message = message.into();
+	log::warn!("{}", message);
+	WARNINGS.with(|warnings| {
+		if let Some(warnings) = warnings.borrow_mut().as_mut() {
+			warnings.push(message);
+		}
+	});
+}
+
+fn run_collector(fetch: Collector, source: &dyn DataSource, options: &CollectOptions) -> SectionResult<SectionData> {
+	WARNINGS.with(|warnings| *warnings.borrow_mut() = Some(Vec::new()));
+	let stopwatch = Instant::now();
+	let result = fetch(source, options);
+	let duration_ms = stopwatch.elapsed().as_millis() as u64;
+	let warnings = WARNINGS.with(|warnings| warnings.borrow_mut().take()).unwrap_or_default();
+
+	match result {
+		Ok(data) => SectionResult {
+			status: SectionStatus::Ok,
+			data: Some(data),
+			errors: Vec::new(),
+			warnings,
+			duration_ms,
+		},
+		Err(e) => SectionResult {
+			status: if e.chain().any(|cause| cause.is::<Unsupported>()) {
+				SectionStatus::Unsupported
+			} else {
+				SectionStatus::Failed
+			},
+			data: None,
+			errors: e.chain().map(|cause| cause.to_string()).collect(),
+			warnings,
+			duration_ms,
+		},
+	}
+}
+
+/// Collects every section concurrently from the live machine.
+pub async fn collect_all() -> SystemReport {
+	collect(Section::ALL).await
+}
+
+/// Collects the requested sections concurrently from the live machine.
+pub async fn collect(sections: &[Section]) -> SystemReport {
+	collect_from(Arc::new(LiveSource::new()), sections).await
+}
+
+/// Collects the requested sections concurrently through `source`, each on its own blocking task.
+/// Sections that were not requested are reported as skipped.
+pub async fn collect_from(source: Arc<dyn DataSource>, sections: &[Section]) -> SystemReport {
+	collect_with(source, sections, CollectOptions::default()).await
+}
+
+/// Like `collect_from`, with collector settings other than the defaults.
+pub async fn collect_with(source: Arc<dyn DataSource>, sections: &[Section], options: CollectOptions) -> SystemReport {
+	let options = Arc::new(options);
+	let mut tasks = JoinSet::new();
+	let mut task_sections = HashMap::new();
+	let mut requested = sections.to_vec();
+	requested.sort();
+	requested.dedup();
+
+	for section in requested {
+		let fetch = section.collector();
+		let source = source.clone();
+		let options = options.clone();
+		let handle = tasks.spawn_blocking(move || run_collector(fetch, source.as_ref(), &options));
+		task_sections.insert(handle.id(), section);
+	}
+
+	let mut report = SystemReport::default();
+	while let Some(joined) = tasks.join_next_with_id().await {
+		let (id, result) = match joined {
+			Ok((id, result)) => (id, result),
+			Err(e) => (
+				e.id(),
+				SectionResult {
+					status: SectionStatus::Failed,
+					errors: vec![format!("Collector panicked: {}", e)],
+					..SectionResult::default()
+				},
+			),
+		};
+		let Some(&section) = task_sections.get(&id) else {
+			continue;
+		};
+		if result.status == SectionStatus::Failed {
+			log::error!("Failed to collect {}: {}", section.title(), result.errors.join(": "));
+		}
+		report.insert(section, result);
+	}
+
+	report
+}
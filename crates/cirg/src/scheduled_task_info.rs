@@ -1,8 +1,31 @@
-use crate::ComputerInfoExt;
-use anyhow::Result;
+use crate::calendar::Schedule;
+use crate::linux::{parse_key_values, user_homes};
+use crate::service_info::{SYSTEM_UNIT_PATHS, USER_UNIT_PATHS, find_unit_file, parse_unit_file, unit_command, unit_files};
+use crate::source::{DataSource, Platform};
+use crate::{ComputerInfoExt, report};
+use anyhow::{Result, anyhow};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
-use std::os::windows::process::CommandExt;
-use std::process::Command;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The system crontab, whose entries name the user to run as before the command.
+const SYSTEM_CRONTAB: &str = "/etc/crontab";
+/// Crontab fragments in the system format, installed by packages.
+const CRON_D: &str = "/etc/cron.d";
+/// Directories of scripts `run-parts` runs, with the schedule they get when no crontab entry does.
+const CRON_PERIODS: &[(&str, &str)] = &[
+	("/etc/cron.hourly", "@hourly"),
+	("/etc/cron.daily", "@daily"),
+	("/etc/cron.weekly", "@weekly"),
+	("/etc/cron.monthly", "@monthly"),
+];
+/// Per-user crontabs, named after their owner: Debian, SUSE, then Red Hat and Arch.
+const CRON_SPOOLS: &[&str] = &["/var/spool/cron/crontabs", "/var/spool/cron/tabs", "/var/spool/cron"];
+const ANACRONTAB: &str = "/etc/anacrontab";
+/// anacron's record of the day it last ran each job.
+const ANACRON_SPOOL: &str = "/var/spool/anacron";
+/// Timers with `Persistent=true` touch a stamp file here each time they elapse.
+const TIMER_STAMPS: &str = "/var/lib/systemd/timers";
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ScheduledTaskInfo {
@@ -18,16 +41,25 @@ pub struct ScheduledTask {
 	pub next_run: String,
 	pub result: String,
 	pub author: String,
+	/// What the task runs: the action on Windows, the crontab command or the activated service's
+	/// `ExecStart=` on Linux.
+	#[serde(default)]
+	pub command: String,
 }
 
 impl ComputerInfoExt for ScheduledTaskInfo {
-	fn fetch() -> Result<Self> {
-		let output = Command::new("schtasks")
-			.args(["/Query", "/FO", "CSV", "/V"])
-			.creation_flags(0x08000000)
-			.output()?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Scheduled task collection")),
+		}
+	}
+}
 
-		let stdout = String::from_utf8_lossy(&output.stdout);
+impl ScheduledTaskInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let stdout = source.command("schtasks", &["/Query", "/FO", "CSV", "/V"])?.stdout;
 		let mut lines = stdout.lines();
 
 		let header = match lines.next() {
@@ -44,6 +76,7 @@ impl ComputerInfoExt for ScheduledTaskInfo {
 		let last_run_idx = idx("Last Run Time");
 		let last_result_idx = idx("Last Result");
 		let author_idx = idx("Author");
+		let command_idx = idx("Task To Run");
 
 		let mut tasks = Vec::new();
 
@@ -88,6 +121,10 @@ impl ComputerInfoExt for ScheduledTaskInfo {
 					.and_then(|i| cols.get(i))
 					.cloned()
 					.unwrap_or_default(),
+				command: command_idx
+					.and_then(|i| cols.get(i))
+					.map(|command| command.trim().to_string())
+					.unwrap_or_default(),
 			});
 		}
 
@@ -95,6 +132,371 @@ impl ComputerInfoExt for ScheduledTaskInfo {
 	}
 }
 
+impl ScheduledTaskInfo {
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let now = Local::now().naive_local();
+
+		let mut entries = crontab_entries(source, SYSTEM_CRONTAB, None);
+		for file in source.read_dir(CRON_D).unwrap_or_default() {
+			if is_run_parts_name(&file) {
+				entries.extend(crontab_entries(source, &format!("{}/{}", CRON_D, file), None));
+			}
+		}
+		for spool in CRON_SPOOLS {
+			match source.read_dir(spool) {
+				// Other spool directories nested here read as empty crontabs
+				Ok(owners) => {
+					for owner in owners {
+						entries.extend(crontab_entries(source, &format!("{}/{}", spool, owner), Some(&owner)));
+					}
+				}
+				// The spool is only readable by root
+				Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied) => {
+					report::warn(format!("Cannot read the per-user crontabs in {}: {}", spool, e));
+				}
+				Err(_) => {}
+			}
+		}
+
+		let mut tasks: Vec<ScheduledTask> = entries
+			.iter()
+			.map(|entry| {
+				let name = entry.file.rsplit('/').next().unwrap_or_default().to_string();
+				let path = format!("{}:{}", entry.file, entry.line);
+				cron_task(name, path, &entry.user, &entry.schedule, &entry.command, None, now)
+			})
+			.collect();
+		tasks.extend(periodic_scripts(source, &entries, now));
+
+		tasks.extend(timer_tasks(source, SYSTEM_UNIT_PATHS, SYSTEM_UNIT_PATHS, "root", true, now));
+		tasks.extend(timer_tasks(source, USER_UNIT_PATHS, USER_UNIT_PATHS, "All users", false, now));
+		for (user, home) in user_homes(source) {
+			let own_units = format!("{}/.config/systemd/user", home);
+			let search_paths: Vec<&str> = std::iter::once(own_units.as_str()).chain(USER_UNIT_PATHS.iter().copied()).collect();
+			tasks.extend(timer_tasks(source, &[&own_units], &search_paths, &user, false, now));
+		}
+
+		Ok(ScheduledTaskInfo { tasks })
+	}
+}
+
+/// A job line of a crontab.
+struct CronEntry {
+	file: String,
+	line: usize,
+	user: String,
+	/// The five time fields, or a shorthand such as `@daily`.
+	schedule: String,
+	command: String,
+}
+
+/// Job lines of a crontab. `owner` is the user a per-user crontab belongs to; system crontabs name
+/// the user on each line instead.
+fn crontab_entries(source: &dyn DataSource, file: &str, owner: Option<&str>) -> Vec<CronEntry> {
+	let text = source.read_to_string(file).unwrap_or_default();
+	let mut entries = Vec::new();
+	for (number, line) in text.lines().enumerate() {
+		let line = line.trim();
+		// Comments and variable assignments such as MAILTO= cannot start with a time field
+		if !line.starts_with(|c: char| c.is_ascii_digit() || c == '*' || c == '@') {
+			continue;
+		}
+
+		let time_fields = if line.starts_with('@') { 1 } else { 5 };
+		let mut words = Vec::new();
+		let mut rest = line;
+		for _ in 0..time_fields + usize::from(owner.is_none()) {
+			let (word, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+			words.push(word);
+			rest = tail.trim_start();
+		}
+		if rest.is_empty() {
+			continue;
+		}
+		let user = match owner {
+			Some(owner) => owner,
+			None => words.pop().unwrap_or_default(),
+		};
+
+		entries.push(CronEntry {
+			file: file.to_string(),
+			line: number + 1,
+			user: user.to_string(),
+			schedule: words.join(" "),
+			command: rest.to_string(),
+		});
+	}
+	entries
+}
+
+fn cron_task(
+	name: String,
+	path: String,
+	user: &str,
+	schedule: &str,
+	command: &str,
+	last_run: Option<String>,
+	now: NaiveDateTime,
+) -> ScheduledTask {
+	let next_run = if schedule == "@reboot" {
+		"At system startup".to_string()
+	} else {
+		match Schedule::from_cron(schedule) {
+			Ok(schedule) => format_time(schedule.next_after(now)),
+			Err(e) => {
+				report::warn(format!("Cannot parse the schedule of {}: {}", path, e));
+				"N/A".to_string()
+			}
+		}
+	};
+	ScheduledTask {
+		name,
+		path,
+		state: "Ready".to_string(),
+		last_run: last_run.unwrap_or_else(|| "N/A".to_string()),
+		next_run,
+		// cron mails the output of a job rather than recording how it exited
+		result: "N/A".to_string(),
+		author: user.to_string(),
+		command: command.to_string(),
+	}
+}
+
+/// Scripts in the `/etc/cron.hourly` style directories, each scheduled like the job that runs the
+/// directory.
+fn periodic_scripts(source: &dyn DataSource, entries: &[CronEntry], now: NaiveDateTime) -> Vec<ScheduledTask> {
+	let anacrontab = source.read_to_string(ANACRONTAB).unwrap_or_default();
+	let mut tasks = Vec::new();
+	for (directory, period) in CRON_PERIODS {
+		let job = directory.trim_start_matches("/etc/");
+		let by_anacron = anacrontab
+			.lines()
+			.any(|line| !line.trim_start().starts_with('#') && line.contains(directory));
+
+		// anacron runs a job once a period at whatever time the machine is up, and records the day
+		let (schedule, last_run) = if by_anacron {
+			let stamp = source.read_to_string(&format!("{}/{}", ANACRON_SPOOL, job)).unwrap_or_default();
+			let day = NaiveDate::parse_from_str(stamp.trim(), "%Y%m%d").ok();
+			(*period, day.map(|day| day.format("%Y-%m-%d").to_string()))
+		} else {
+			let entry = entries.iter().find(|entry| entry.command.contains(directory));
+			(entry.map_or(*period, |entry| entry.schedule.as_str()), None)
+		};
+
+		for script in source.read_dir(directory).unwrap_or_default() {
+			if !is_run_parts_name(&script) {
+				continue;
+			}
+			let path = format!("{}/{}", directory, script);
+			tasks.push(cron_task(script, path.clone(), "root", schedule, &path, last_run.clone(), now));
+		}
+	}
+	tasks
+}
+
+/// `run-parts` skips names with anything but letters, digits, `_` and `-`, such as `.placeholder`
+/// and the `.dpkg-old` copies a package upgrade leaves.
+fn is_run_parts_name(name: &str) -> bool {
+	!name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Timer units defined or enabled in `directories`, with their files looked up in `search_paths`.
+/// `query` asks the system manager for when each last elapsed and how its service exited.
+fn timer_tasks(
+	source: &dyn DataSource,
+	directories: &[&str],
+	search_paths: &[&str],
+	author: &str,
+	query: bool,
+	now: NaiveDateTime,
+) -> Vec<ScheduledTask> {
+	// The first directory a unit name appears in wins, so /etc overrides /usr/lib
+	let mut timers: BTreeMap<String, String> = BTreeMap::new();
+	let mut enabled = HashSet::new();
+	for directory in directories {
+		for entry in source.read_dir(directory).unwrap_or_default() {
+			if entry.ends_with(".wants") || entry.ends_with(".requires") {
+				let links = source.read_dir(&format!("{}/{}", directory, entry)).unwrap_or_default();
+				enabled.extend(links.into_iter().filter(|link| link.ends_with(".timer")));
+			} else if entry.ends_with(".timer") {
+				timers.entry(entry.clone()).or_insert_with(|| format!("{}/{}", directory, entry));
+			}
+		}
+	}
+	for name in &enabled {
+		if !timers.contains_key(name)
+			&& let Some(path) = find_unit_file(source, search_paths, name)
+		{
+			timers.insert(name.clone(), path);
+		}
+	}
+
+	let settings: Vec<(&String, &String, TimerSettings)> = timers
+		.iter()
+		.map(|(name, path)| (name, path, TimerSettings::load(source, search_paths, name, path)))
+		.collect();
+
+	let properties = if query && !settings.is_empty() {
+		let units: Vec<&str> = settings
+			.iter()
+			.flat_map(|(name, _, timer)| [name.as_str(), timer.service.as_str()])
+			.collect();
+		unit_properties(source, &units)
+			.inspect_err(|e| report::warn(format!("Failed to query systemd for timer state: {:#}", e)))
+			.unwrap_or_default()
+	} else {
+		HashMap::new()
+	};
+	let no_properties = BTreeMap::new();
+
+	settings
+		.into_iter()
+		.map(|(name, path, timer)| {
+			let timer_properties = properties.get(name).unwrap_or(&no_properties);
+			let service_properties = properties.get(&timer.service).unwrap_or(&no_properties);
+			let property = |properties: &BTreeMap<String, String>, key: &str| properties.get(key).cloned().unwrap_or_default();
+
+			let stamp = format!("{}/stamp-{}", TIMER_STAMPS, name);
+			let last_run = systemd_timestamp(&property(timer_properties, "LastTriggerUSec"))
+				.or_else(|| source.modified(&stamp).ok().map(|time| time.with_timezone(&Local).naive_local()));
+
+			let state = if matches!(property(service_properties, "ActiveState").as_str(), "active" | "activating" | "reloading") {
+				"Running"
+			} else if enabled.contains(name) || property(timer_properties, "ActiveState") == "active" {
+				"Ready"
+			} else {
+				"Disabled"
+			};
+
+			let result = match (last_run, property(service_properties, "Result").as_str()) {
+				(None, _) | (_, "") => "N/A".to_string(),
+				(Some(_), "success") => property(service_properties, "ExecMainStatus"),
+				(Some(_), result) => format!("{} ({})", result, property(service_properties, "ExecMainStatus")),
+			};
+
+			let next_run = if state == "Disabled" { None } else { timer.next_run(name, last_run, now) };
+
+			ScheduledTask {
+				name: name.clone(),
+				path: path.clone(),
+				state: state.to_string(),
+				last_run: format_time(last_run),
+				next_run: format_time(next_run),
+				result,
+				author: author.to_string(),
+				command: unit_command(source, search_paths, &timer.service).unwrap_or_default(),
+			}
+		})
+		.collect()
+}
+
+/// The `[Timer]` settings of a timer unit and its drop-ins.
+struct TimerSettings {
+	calendars: Vec<String>,
+	/// `OnUnitActiveSec=` and `OnUnitInactiveSec=`, which count from the last time the timer elapsed.
+	intervals: Vec<String>,
+	service: String,
+}
+
+impl TimerSettings {
+	fn load(source: &dyn DataSource, search_paths: &[&str], name: &str, path: &str) -> Self {
+		let stem = name.trim_end_matches(".timer");
+		let mut settings = TimerSettings {
+			calendars: Vec::new(),
+			intervals: Vec::new(),
+			service: format!("{}.service", stem),
+		};
+		for file in unit_files(source, search_paths, name, path) {
+			let text = source.read_to_string(&file).unwrap_or_default();
+			for (section, key, value) in parse_unit_file(&text) {
+				if section != "Timer" {
+					continue;
+				}
+				match key.as_str() {
+					// An empty assignment resets the list
+					"OnCalendar" if value.is_empty() => settings.calendars.clear(),
+					"OnCalendar" => settings.calendars.push(value),
+					"OnUnitActiveSec" | "OnUnitInactiveSec" if value.is_empty() => settings.intervals.clear(),
+					"OnUnitActiveSec" | "OnUnitInactiveSec" => settings.intervals.push(value),
+					"Unit" => settings.service = value,
+					_ => {}
+				}
+			}
+		}
+		settings
+	}
+
+	/// The earliest time any of the timer's calendar or interval triggers elapses.
+	fn next_run(&self, name: &str, last_run: Option<NaiveDateTime>, now: NaiveDateTime) -> Option<NaiveDateTime> {
+		let calendar = self.calendars.iter().filter_map(|expression| {
+			Schedule::from_calendar(expression)
+				.inspect_err(|e| report::warn(format!("Cannot parse OnCalendar={} of {}: {}", expression, name, e)))
+				.ok()?
+				.next_after(now)
+		});
+		let intervals = self
+			.intervals
+			.iter()
+			.filter_map(|interval| Some(last_run? + parse_timespan(interval)?));
+		calendar.chain(intervals).min()
+	}
+}
+
+/// Properties of units from `systemctl show`, keyed by unit name.
+fn unit_properties(source: &dyn DataSource, units: &[&str]) -> Result<HashMap<String, BTreeMap<String, String>>> {
+	let mut args = vec!["show", "--property=Id,ActiveState,LastTriggerUSec,Result,ExecMainStatus"];
+	args.extend(units);
+	let output = source.command("systemctl", &args)?;
+	if output.status != Some(0) {
+		return Err(anyhow!("systemctl failed: {}", output.stderr.trim()));
+	}
+
+	// One block of Key=value lines per unit, separated by blank lines
+	Ok(output
+		.stdout
+		.split("\n\n")
+		.map(parse_key_values)
+		.filter_map(|properties| Some((properties.get("Id")?.clone(), properties)))
+		.collect())
+}
+
+/// `Fri 2026-10-16 00:00:04 CEST` -> 2026-10-16 00:00:04. Never elapsed reads as empty or `n/a`.
+fn systemd_timestamp(value: &str) -> Option<NaiveDateTime> {
+	let mut words = value.split_whitespace().skip_while(|word| NaiveDate::parse_from_str(word, "%Y-%m-%d").is_err());
+	let (date, time) = (words.next()?, words.next()?);
+	NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M:%S").ok()
+}
+
+/// A systemd time span such as `1h 30min`, `15m` or a bare number of seconds.
+fn parse_timespan(text: &str) -> Option<Duration> {
+	let mut total = Duration::zero();
+	let mut rest = text.trim();
+	while !rest.is_empty() {
+		let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+		let number: i64 = rest[..digits].parse().ok()?;
+		rest = rest[digits..].trim_start();
+		let letters = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+		let seconds = match &rest[..letters] {
+			"" | "s" | "sec" | "second" | "seconds" => 1,
+			"m" | "min" | "minute" | "minutes" => 60,
+			"h" | "hr" | "hour" | "hours" => 3600,
+			"d" | "day" | "days" => 86400,
+			"w" | "week" | "weeks" => 7 * 86400,
+			// systemd's month and year are averages, 30.44 and 365.25 days
+			"M" | "month" | "months" => 2_629_800,
+			"y" | "year" | "years" => 31_557_600,
+			_ => return None,
+		};
+		total += Duration::seconds(number * seconds);
+		rest = rest[letters..].trim_start();
+	}
+	Some(total)
+}
+
+fn format_time(time: Option<NaiveDateTime>) -> String {
+	time.map_or_else(|| "N/A".to_string(), |time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
 fn parse_csv_line(line: &str) -> Vec<String> {
 	let mut fields = Vec::new();
 	let mut current = String::new();
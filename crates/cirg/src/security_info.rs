@@ -1,11 +1,14 @@
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::HashSet;
 
-use crate::{ComputerInfoExt, VariantExt};
-use winreg::RegKey;
-use winreg::enums::*;
-use wmi::Variant;
+use crate::disk_info::unescape_mount;
+use crate::linux::{parse_key_values, read_optional, read_value};
+use crate::source::{CIMV2, DataSource, Hive, Platform};
+use crate::{ComputerInfoExt, VariantExt, report};
+
+/// Efivar holding the firmware's Secure Boot state, after four bytes of attributes.
+const SECURE_BOOT_EFIVAR: &str = "/sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c";
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SecurityInfo {
@@ -16,6 +19,11 @@ pub struct SecurityInfo {
     pub uac: bool,
     pub rdp_enabled: bool,
     pub bit_locker: bool,
+    /// Linux: mount points whose filesystem sits on dm-crypt, directly or through LVM.
+    #[serde(default)]
+    pub encrypted_volumes: Vec<String>,
+    /// Linux: the confining security module and its mode, e.g. `SELinux (enforcing, targeted)`.
+    pub mandatory_access_control: Option<String>,
     pub pending_updates: Option<Vec<UpdateItem>>,
 }
 
@@ -40,6 +48,10 @@ pub struct FirewallInfo {
     pub public_enabled: Option<bool>,
     pub public_inbound: Option<String>,
     pub public_outbound: Option<String>,
+    /// Linux: `nftables` or `iptables`, whose input and output hooks give the defaults below.
+    pub backend: Option<String>,
+    pub inbound: Option<String>,
+    pub outbound: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,92 +65,117 @@ pub struct UpdateItem {
 }
 
 impl ComputerInfoExt for SecurityInfo {
-    fn fetch() -> Result<Self> {
+    fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+        match source.platform() {
+            Platform::Windows => Self::fetch_windows(source),
+            Platform::Linux => Self::fetch_linux(source),
+            platform => Err(platform.unsupported("Security collection")),
+        }
+    }
+}
+
+impl SecurityInfo {
+    fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+        Ok(SecurityInfo {
+            secure_boot: Self::fetch_secure_boot(source),
+            tpm: Self::fetch_tpm(source),
+            antivirus: Self::fetch_antivirus(source),
+            firewall: Self::fetch_firewall(source),
+            uac: Self::fetch_uac(source),
+            rdp_enabled: Self::fetch_rdp_status(source),
+            bit_locker: Self::fetch_bitlocker(source),
+            encrypted_volumes: Vec::new(),
+            mandatory_access_control: None,
+            pending_updates: Self::fetch_pending_updates(source),
+        })
+    }
+
+    fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+        let encrypted_volumes = Self::linux_encrypted_volumes(source);
         Ok(SecurityInfo {
-            secure_boot: Self::fetch_secure_boot(),
-            tpm: Self::fetch_tpm(),
-            antivirus: Self::fetch_antivirus(),
-            firewall: Self::fetch_firewall(),
-            uac: Self::fetch_uac(),
-            rdp_enabled: Self::fetch_rdp_status(),
-            bit_locker: Self::fetch_bitlocker(),
-            pending_updates: Self::fetch_pending_updates(),
+            secure_boot: source
+                .read_file(SECURE_BOOT_EFIVAR)
+                .map(|data| data.get(4) == Some(&1))
+                .unwrap_or(false),
+            tpm: Self::linux_tpm(source),
+            antivirus: None,
+            firewall: Self::linux_firewall(source),
+            uac: false,
+            rdp_enabled: false,
+            bit_locker: !encrypted_volumes.is_empty(),
+            encrypted_volumes,
+            mandatory_access_control: Self::linux_mac(source),
+            pending_updates: None,
         })
     }
 }
 impl SecurityInfo {
-    fn fetch_secure_boot() -> bool {
+    fn fetch_secure_boot(source: &dyn DataSource) -> bool {
         // Read from registry — works without admin privileges
-        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-        if let Ok(key) = hklm.open_subkey(r"SYSTEM\CurrentControlSet\Control\SecureBoot\State") {
+        if let Ok(key) = source.registry_key(
+            Hive::LocalMachine,
+            r"SYSTEM\CurrentControlSet\Control\SecureBoot\State",
+        ) {
             return key
-                .get_value::<u32, _>("UEFISecureBootEnabled")
+                .values
+                .get_u32("UEFISecureBootEnabled")
                 .map(|v| v == 1)
                 .unwrap_or(false);
         }
         false
     }
 
-    fn fetch_tpm() -> Option<TmpInfo> {
-        #[cfg(target_os = "windows")]
-        {
-            // Try registry first (works without admin)
-            if let Some(info) = Self::fetch_tpm_registry() {
-                return Some(info);
-            }
+    fn fetch_tpm(source: &dyn DataSource) -> Option<TmpInfo> {
+        // Try registry first (works without admin)
+        if let Some(info) = Self::fetch_tpm_registry(source) {
+            return Some(info);
+        }
 
-            // Try WMI (requires admin)
-            if let Ok(tpm_info) = Self::fetch_tpm_wmi() {
-                return Some(tpm_info);
+        // Try WMI (requires admin)
+        match Self::fetch_tpm_wmi(source) {
+            Ok(Some(tpm_info)) => {
+                report::warn("TPM registry keys not found, TPM state read from Win32_Tpm instead");
+                Some(tpm_info)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                report::warn(format!("TPM not found in registry and Win32_Tpm query failed: {:#}", e));
+                None
             }
-
-            None
-        }
-        #[cfg(not(target_os = "windows"))]
-        {
-            None
         }
     }
 
-    fn fetch_tpm_registry() -> Option<TmpInfo> {
-        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-
+    fn fetch_tpm_registry(source: &dyn DataSource) -> Option<TmpInfo> {
         // Check if TPM service exists
-        let tpm_service_exists = hklm
-            .open_subkey(r"SYSTEM\CurrentControlSet\Services\TPM")
+        let tpm_service_exists = source
+            .registry_key(Hive::LocalMachine, r"SYSTEM\CurrentControlSet\Services\TPM")
             .is_ok();
 
         if !tpm_service_exists {
             return None;
         }
 
+        let tpm_key = source
+            .registry_key(Hive::LocalMachine, r"SOFTWARE\Microsoft\Tpm")
+            .map(|key| key.values)
+            .unwrap_or_default();
+
         // Read TPM version from registry
-        let version = hklm
-            .open_subkey(r"SOFTWARE\Microsoft\Tpm")
+        // Try SpecVersion first, then ManufacturerVersion
+        let version = tpm_key
+            .get_string("SpecVersion")
             .ok()
-            .and_then(|key| {
-                // Try SpecVersion first, then ManufacturerVersion
-                key.get_value::<String, _>("SpecVersion")
-                    .ok()
-                    .and_then(|v| v.split(',').next().map(|s| s.trim().to_string()))
-                    .or_else(|| key.get_value::<String, _>("ManufacturerVersion").ok())
-            })
+            .and_then(|v| v.split(',').next().map(|s| s.trim().to_string()))
+            .or_else(|| tpm_key.get_string("ManufacturerVersion").ok())
             .unwrap_or_else(|| "Unknown".to_string());
 
         // Read manufacturer info
-        let manufacturer = hklm
-            .open_subkey(r"SOFTWARE\Microsoft\Tpm")
-            .ok()
-            .and_then(|key| key.get_value::<String, _>("ManufacturerDisplayName").ok())
-            .unwrap_or_else(|| "Unknown".to_string());
+        let manufacturer = tpm_key
+            .get_string("ManufacturerDisplayName")
+            .unwrap_or_else(|_| "Unknown".to_string());
 
         // Check if TPM is ready via the IsReady registry value
-        let ready = hklm
-            .open_subkey(r"SOFTWARE\Microsoft\Tpm")
-            .ok()
-            .and_then(|key| key.get_value::<u32, _>("IsReady").ok())
-            .map(|v| v == 1)
-            .unwrap_or(false);
+        let ready = tpm_key.get_u32("IsReady").map(|v| v == 1).unwrap_or(false);
 
         Some(TmpInfo {
             present: true,
@@ -150,11 +187,9 @@ impl SecurityInfo {
         })
     }
 
-    fn fetch_tpm_wmi() -> Result<TmpInfo> {
-        let com = wmi::WMIConnection::with_namespace_path(r"root\cimv2\security\microsofttpm")?;
-
+    fn fetch_tpm_wmi(source: &dyn DataSource) -> Result<Option<TmpInfo>> {
         let query = r#"SELECT * FROM Win32_Tpm"#;
-        let results: Vec<HashMap<String, Variant>> = com.raw_query(query)?;
+        let results = source.wmi_query(r"root\cimv2\security\microsofttpm", query)?;
 
         if let Some(data) = results.first() {
             let present = true;
@@ -172,25 +207,23 @@ impl SecurityInfo {
                 .get_string("ManufacturerIdTxt")
                 .unwrap_or_else(|_| "Unknown".to_string());
 
-            return Ok(TmpInfo {
+            return Ok(Some(TmpInfo {
                 present,
                 ready,
                 enabled,
                 activated,
                 version,
                 manufacturer,
-            });
+            }));
         }
 
-        Err(anyhow!("No TPM found"))
+        Ok(None)
     }
 
-    fn fetch_antivirus() -> Option<String> {
+    fn fetch_antivirus(source: &dyn DataSource) -> Option<String> {
         // AntiVirusProduct lives in root\SecurityCenter2, not root\cimv2
-        let com = wmi::WMIConnection::with_namespace_path(r"root\SecurityCenter2").ok()?;
-
         let query = r#"SELECT displayName, productState FROM AntiVirusProduct"#;
-        let results: Vec<HashMap<String, Variant>> = com.raw_query(query).ok()?;
+        let results = source.wmi_query(r"root\SecurityCenter2", query).ok()?;
 
         if results.is_empty() {
             return None;
@@ -220,57 +253,66 @@ impl SecurityInfo {
         }
     }
 
-    fn fetch_firewall() -> Option<FirewallInfo> {
-        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    fn fetch_firewall(source: &dyn DataSource) -> Option<FirewallInfo> {
+        const POLICY_KEY: &str =
+            r"SYSTEM\CurrentControlSet\Services\SharedAccess\Parameters\FirewallPolicy";
 
         let mut fw_info = FirewallInfo::default();
         let mut has_data = false;
 
         // Domain Profile
-        if let Ok(key) = hklm.open_subkey(
-            r"SYSTEM\CurrentControlSet\Services\SharedAccess\Parameters\FirewallPolicy\DomainProfile"
-        ) {
-            fw_info.domain_enabled = Some(key.get_value::<u32, _>("EnableFirewall").unwrap_or(0) == 1);
-            let inbound = key.get_value::<u32, _>("DefaultInboundAction").unwrap_or(1);
-            let outbound = key.get_value::<u32, _>("DefaultOutboundAction").unwrap_or(0);
-            fw_info.domain_inbound = Some(if inbound == 1 { "Block" } else { "Allow" }.to_string());
-            fw_info.domain_outbound = Some(if outbound == 1 { "Block" } else { "Allow" }.to_string());
+        if let Some((enabled, inbound, outbound)) =
+            Self::fetch_firewall_profile(source, &format!(r"{}\DomainProfile", POLICY_KEY))
+        {
+            fw_info.domain_enabled = Some(enabled);
+            fw_info.domain_inbound = Some(inbound);
+            fw_info.domain_outbound = Some(outbound);
             has_data = true;
         }
 
         // Private Profile (StandardProfile)
-        if let Ok(key) = hklm.open_subkey(
-            r"SYSTEM\CurrentControlSet\Services\SharedAccess\Parameters\FirewallPolicy\StandardProfile"
-        ) {
-            fw_info.private_enabled = Some(key.get_value::<u32, _>("EnableFirewall").unwrap_or(0) == 1);
-            let inbound = key.get_value::<u32, _>("DefaultInboundAction").unwrap_or(1);
-            let outbound = key.get_value::<u32, _>("DefaultOutboundAction").unwrap_or(0);
-            fw_info.private_inbound = Some(if inbound == 1 { "Block" } else { "Allow" }.to_string());
-            fw_info.private_outbound = Some(if outbound == 1 { "Block" } else { "Allow" }.to_string());
+        if let Some((enabled, inbound, outbound)) =
+            Self::fetch_firewall_profile(source, &format!(r"{}\StandardProfile", POLICY_KEY))
+        {
+            fw_info.private_enabled = Some(enabled);
+            fw_info.private_inbound = Some(inbound);
+            fw_info.private_outbound = Some(outbound);
             has_data = true;
         }
 
         // Public Profile
-        if let Ok(key) = hklm.open_subkey(
-            r"SYSTEM\CurrentControlSet\Services\SharedAccess\Parameters\FirewallPolicy\PublicProfile"
-        ) {
-            fw_info.public_enabled = Some(key.get_value::<u32, _>("EnableFirewall").unwrap_or(0) == 1);
-            let inbound = key.get_value::<u32, _>("DefaultInboundAction").unwrap_or(1);
-            let outbound = key.get_value::<u32, _>("DefaultOutboundAction").unwrap_or(0);
-            fw_info.public_inbound = Some(if inbound == 1 { "Block" } else { "Allow" }.to_string());
-            fw_info.public_outbound = Some(if outbound == 1 { "Block" } else { "Allow" }.to_string());
+        if let Some((enabled, inbound, outbound)) =
+            Self::fetch_firewall_profile(source, &format!(r"{}\PublicProfile", POLICY_KEY))
+        {
+            fw_info.public_enabled = Some(enabled);
+            fw_info.public_inbound = Some(inbound);
+            fw_info.public_outbound = Some(outbound);
             has_data = true;
         }
 
         if has_data { Some(fw_info) } else { None }
     }
 
-    fn fetch_uac() -> bool {
-        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-        if let Ok(key) =
-            hklm.open_subkey(r"SOFTWARE\Microsoft\Windows\CurrentVersion\Policies\System")
-        {
-            key.get_value::<u32, _>("EnableLUA")
+    /// Returns (enabled, default inbound action, default outbound action) for one profile key.
+    fn fetch_firewall_profile(source: &dyn DataSource, path: &str) -> Option<(bool, String, String)> {
+        let key = source.registry_key(Hive::LocalMachine, path).ok()?.values;
+        let enabled = key.get_u32("EnableFirewall").unwrap_or(0) == 1;
+        let inbound = key.get_u32("DefaultInboundAction").unwrap_or(1);
+        let outbound = key.get_u32("DefaultOutboundAction").unwrap_or(0);
+        Some((
+            enabled,
+            if inbound == 1 { "Block" } else { "Allow" }.to_string(),
+            if outbound == 1 { "Block" } else { "Allow" }.to_string(),
+        ))
+    }
+
+    fn fetch_uac(source: &dyn DataSource) -> bool {
+        if let Ok(key) = source.registry_key(
+            Hive::LocalMachine,
+            r"SOFTWARE\Microsoft\Windows\CurrentVersion\Policies\System",
+        ) {
+            key.values
+                .get_u32("EnableLUA")
                 .map(|v| v == 1)
                 .unwrap_or(false)
         } else {
@@ -278,24 +320,21 @@ impl SecurityInfo {
         }
     }
 
-    fn fetch_rdp_status() -> bool {
-        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-        if let Ok(key) = hklm.open_subkey(r"SYSTEM\CurrentControlSet\Control\Terminal Server") {
-            let deny_connections = key.get_value::<u32, _>("fDenyTSConnections").unwrap_or(1);
+    fn fetch_rdp_status(source: &dyn DataSource) -> bool {
+        if let Ok(key) = source.registry_key(
+            Hive::LocalMachine,
+            r"SYSTEM\CurrentControlSet\Control\Terminal Server",
+        ) {
+            let deny_connections = key.values.get_u32("fDenyTSConnections").unwrap_or(1);
             deny_connections == 0
         } else {
             false
         }
     }
 
-    fn fetch_bitlocker() -> bool {
-        let com = match wmi::WMIConnection::new() {
-            Ok(c) => c,
-            Err(_) => return false,
-        };
-
+    fn fetch_bitlocker(source: &dyn DataSource) -> bool {
         let query = r#"SELECT ProtectionStatus FROM Win32_EncryptableVolume"#;
-        let results: Vec<HashMap<String, Variant>> = match com.raw_query(query) {
+        let results = match source.wmi_query(CIMV2, query) {
             Ok(r) => r,
             Err(_) => return false,
         };
@@ -307,78 +346,303 @@ impl SecurityInfo {
         })
     }
 
-    fn fetch_pending_updates() -> Option<Vec<UpdateItem>> {
-        Self::query_pending_updates().ok()
+    fn fetch_pending_updates(source: &dyn DataSource) -> Option<Vec<UpdateItem>> {
+        source
+            .pending_updates()
+            .inspect_err(|e| report::warn(format!("Windows Update search failed: {}", e)))
+            .ok()
     }
+}
 
-    fn query_pending_updates() -> windows::core::Result<Vec<UpdateItem>> {
-        use windows::Win32::System::Com::{
-            CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+impl SecurityInfo {
+    /// TPM state from `/sys/class/tpm`; only TPM 1.2 chips report their vendor and flags there.
+    fn linux_tpm(source: &dyn DataSource) -> Option<TmpInfo> {
+        let device = source.read_dir("/sys/class/tpm").ok()?.into_iter().next()?;
+        let path = format!("/sys/class/tpm/{}", device);
+        let flag = |name: &str| read_optional(source, &format!("{}/device/{}", path, name)).map(|value| value == "1");
+
+        // Manufacturer: 0x49465800
+        // TCG version: 1.2
+        let caps = read_optional(source, &format!("{}/device/caps", path)).unwrap_or_default();
+        let cap = |name: &str| {
+            caps.lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(key, _)| key.trim() == name)
+                .map(|(_, value)| value.trim().to_string())
         };
-        use windows::Win32::System::UpdateAgent::{IUpdateSession, UpdateSession};
-        use windows::core::BSTR;
-
-        unsafe {
-            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
-
-            let session: IUpdateSession =
-                CoCreateInstance(&UpdateSession, None, CLSCTX_INPROC_SERVER)?;
-            let searcher = session.CreateUpdateSearcher()?;
-            searcher.SetOnline(false.into())?;
-
-            let criteria = BSTR::from("IsInstalled=0 AND IsHidden=0");
-            let result = searcher.Search(&criteria)?;
-            let updates = result.Updates()?;
-            let count = updates.Count()?;
-
-            let mut items = Vec::new();
-            for i in 0..count {
-                let update = updates.get_Item(i)?;
-
-                let title = update.Title()?.to_string();
-
-                // Collect KB article IDs
-                let mut kb_ids = Vec::new();
-                let kb_collection = update.KBArticleIDs()?;
-                let kb_count = kb_collection.Count()?;
-                for k in 0..kb_count {
-                    let kb = kb_collection.get_Item(k)?;
-                    kb_ids.push(format!("KB{kb}"));
-                }
 
-                // MSRC severity — empty string becomes None
-                let severity_bstr = update.MsrcSeverity()?;
-                let severity_str = severity_bstr.to_string();
-                let severity = if severity_str.is_empty() {
-                    None
-                } else {
-                    Some(severity_str)
-                };
-
-                let is_downloaded = update.IsDownloaded()?.as_bool();
-                let is_mandatory = update.IsMandatory()?.as_bool();
-
-                // Collect categories
-                let mut categories = Vec::new();
-                let cat_collection = update.Categories()?;
-                let cat_count = cat_collection.Count()?;
-                for c in 0..cat_count {
-                    let cat = cat_collection.get_Item(c)?;
-                    let name = cat.Name()?.to_string();
-                    categories.push(name);
+        let version = match read_optional(source, &format!("{}/tpm_version_major", path)).as_deref() {
+            Some("2") => "2.0".to_string(),
+            _ => cap("TCG version").unwrap_or_else(|| "1.2".to_string()),
+        };
+        // The vendor id is four ASCII characters packed into a big-endian integer
+        let manufacturer = cap("Manufacturer")
+            .and_then(|id| u32::from_str_radix(id.trim_start_matches("0x"), 16).ok())
+            .map(|id| {
+                id.to_be_bytes()
+                    .iter()
+                    .filter(|byte| byte.is_ascii_graphic())
+                    .map(|&byte| byte as char)
+                    .collect::<String>()
+            })
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let enabled = flag("enabled").unwrap_or(true);
+        let activated = flag("active").unwrap_or(true);
+        Some(TmpInfo {
+            present: true,
+            ready: enabled && activated && flag("owned").unwrap_or(true),
+            enabled,
+            activated,
+            version,
+            manufacturer,
+        })
+    }
+
+    /// Mount points whose block device is a dm-crypt mapping or is stacked on one.
+    fn linux_encrypted_volumes(source: &dyn DataSource) -> Vec<String> {
+        let Ok(mountinfo) = read_value(source, "/proc/self/mountinfo") else {
+            return Vec::new();
+        };
+
+        let mut seen = HashSet::new();
+        mountinfo
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split(' ').collect();
+                let (device_number, mount_point) = (*fields.get(2)?, *fields.get(4)?);
+                // Bind mounts repeat the device of the filesystem they expose
+                if !seen.insert(device_number) {
+                    return None;
                 }
+                let device = source.read_link(&format!("/sys/dev/block/{}", device_number)).ok()?;
+                let name = device.rsplit('/').next()?;
+                is_dm_crypt(source, name).then(|| unescape_mount(mount_point))
+            })
+            .collect()
+    }
+
+    /// Default verdicts of the netfilter input and output hooks, from nftables or else legacy iptables.
+    fn linux_firewall(source: &dyn DataSource) -> Option<FirewallInfo> {
+        let ruleset = command_stdout(source, "nft", &["list", "ruleset"]);
+        if let Ok(ruleset) = &ruleset
+            && let Some((inbound, outbound)) = nft_verdicts(ruleset)
+        {
+            return Some(FirewallInfo::linux("nftables", inbound, outbound));
+        }
+
+        // An empty ruleset is normal on systems whose rules still live in legacy iptables
+        match (command_stdout(source, "iptables", &["-S"]), ruleset) {
+            (Ok(rules), _) => {
+                let (inbound, outbound) = iptables_verdicts(&rules);
+                Some(FirewallInfo::linux("iptables", inbound, outbound))
+            }
+            (Err(_), Ok(_)) => Some(FirewallInfo::linux("nftables", "Allow", "Allow")),
+            (Err(iptables), Err(nft)) => {
+                report::warn(format!("Failed to list firewall rules: {:#}; {:#}", nft, iptables));
+                None
+            }
+        }
+    }
+
+    /// The enabled security module that confines processes, with its mode.
+    fn linux_mac(source: &dyn DataSource) -> Option<String> {
+        let lsm = read_optional(source, "/sys/kernel/security/lsm").unwrap_or_default();
+        let modules: Vec<&str> = lsm.split(',').collect();
+
+        let enforce = read_optional(source, "/sys/fs/selinux/enforce");
+        if modules.contains(&"selinux") || enforce.is_some() {
+            let mode = match enforce.as_deref() {
+                Some("1") => "enforcing",
+                Some(_) => "permissive",
+                None => "disabled",
+            };
+            let policy = read_optional(source, "/etc/selinux/config")
+                .and_then(|config| parse_key_values(&config).remove("SELINUXTYPE"));
+            return Some(match policy {
+                Some(policy) => format!("SELinux ({}, {})", mode, policy),
+                None => format!("SELinux ({})", mode),
+            });
+        }
+
+        let apparmor = read_optional(source, "/sys/module/apparmor/parameters/enabled");
+        if modules.contains(&"apparmor") || apparmor.as_deref() == Some("Y") {
+            // Profile list is readable by root only
+            let Some(profiles) = read_optional(source, "/sys/kernel/security/apparmor/profiles") else {
+                return Some("AppArmor".to_string());
+            };
+            let count = |mode: &str| profiles.lines().filter(|line| line.ends_with(&format!("({})", mode))).count();
+            return Some(format!("AppArmor ({} enforcing, {} complain)", count("enforce"), count("complain")));
+        }
+
+        [("smack", "Smack"), ("tomoyo", "TOMOYO")]
+            .iter()
+            .find(|(module, _)| modules.contains(module))
+            .map(|(_, name)| name.to_string())
+    }
+}
 
-                items.push(UpdateItem {
-                    title,
-                    kb_article_ids: kb_ids,
-                    severity,
-                    is_downloaded,
-                    is_mandatory,
-                    categories,
-                });
+impl FirewallInfo {
+    fn linux(backend: &str, inbound: &str, outbound: &str) -> Self {
+        FirewallInfo {
+            backend: Some(backend.to_string()),
+            inbound: Some(inbound.to_string()),
+            outbound: Some(outbound.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Whether a device-mapper device is a dm-crypt mapping or is built on one, e.g. LVM on LUKS.
+fn is_dm_crypt(source: &dyn DataSource, name: &str) -> bool {
+    // CRYPT-LUKS2-<uuid>-<name>; verity and integrity targets share the prefix but do not encrypt
+    let Some(uuid) = read_optional(source, &format!("/sys/block/{}/dm/uuid", name)) else {
+        return false;
+    };
+    if uuid.starts_with("CRYPT-") {
+        return !uuid.starts_with("CRYPT-VERITY") && !uuid.starts_with("CRYPT-INTEGRITY");
+    }
+    source
+        .read_dir(&format!("/sys/block/{}/slaves", name))
+        .unwrap_or_default()
+        .iter()
+        .any(|slave| is_dm_crypt(source, slave))
+}
+
+fn command_stdout(source: &dyn DataSource, program: &str, args: &[&str]) -> Result<String> {
+    let output = source.command(program, args)?;
+    if output.status != Some(0) {
+        return Err(anyhow!("{} failed: {}", program, output.stderr.trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// Block or Allow for the input and output hooks of `nft list ruleset`, or None without base chains.
+fn nft_verdicts(ruleset: &str) -> Option<(&'static str, &'static str)> {
+    let (mut inbound, mut outbound) = (None, None);
+    let (mut hook, mut blocks, mut last_rule) = (None, false, "");
+    let mut in_chain = false;
+
+    for line in ruleset.lines().map(str::trim) {
+        if line.starts_with("chain ") {
+            (in_chain, hook, blocks, last_rule) = (true, None, false, "");
+        } else if !in_chain || line.is_empty() {
+            continue;
+        } else if line == "}" {
+            in_chain = false;
+            // A drop policy, or a final rule that drops whatever the earlier rules let through
+            let blocks = blocks || drops_everything(last_rule);
+            match hook {
+                Some("input") => inbound = Some(inbound.unwrap_or(false) || blocks),
+                Some("output") => outbound = Some(outbound.unwrap_or(false) || blocks),
+                _ => {}
+            }
+        } else if line.starts_with("type ") {
+            // type filter hook input priority filter; policy drop;
+            hook = line.split_whitespace().skip_while(|word| *word != "hook").nth(1);
+            blocks = line.contains("policy drop");
+        } else {
+            last_rule = line;
+        }
+    }
+
+    if inbound.is_none() && outbound.is_none() {
+        return None;
+    }
+    let verdict = |blocks: Option<bool>| if blocks == Some(true) { "Block" } else { "Allow" };
+    Some((verdict(inbound), verdict(outbound)))
+}
+
+/// An nftables rule with no match, only an optional counter and a drop or reject.
+fn drops_everything(rule: &str) -> bool {
+    let mut words = rule
+        .split_whitespace()
+        .skip_while(|word| matches!(*word, "counter" | "packets" | "bytes") || word.parse::<u64>().is_ok());
+    matches!(words.next(), Some("drop" | "reject"))
+}
+
+/// Block or Allow for the INPUT and OUTPUT chains of `iptables -S`.
+fn iptables_verdicts(rules: &str) -> (&'static str, &'static str) {
+    let verdict = |chain: &str| {
+        let policy = format!("-P {} DROP", chain);
+        let append = format!("-A {} ", chain);
+        let last_rule = rules.lines().rev().find_map(|line| line.strip_prefix(&append));
+        let blocks = rules.lines().any(|line| line == policy)
+            || last_rule.is_some_and(|rule| rule == "-j DROP" || rule.starts_with("-j REJECT"));
+        if blocks { "Block" } else { "Allow" }
+    };
+    (verdict("INPUT"), verdict("OUTPUT"))
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn query_pending_updates() -> windows::core::Result<Vec<UpdateItem>> {
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+    };
+    use windows::Win32::System::UpdateAgent::{IUpdateSession, UpdateSession};
+    use windows::core::BSTR;
+
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+        let session: IUpdateSession =
+            CoCreateInstance(&UpdateSession, None, CLSCTX_INPROC_SERVER)?;
+        let searcher = session.CreateUpdateSearcher()?;
+        searcher.SetOnline(false.into())?;
+
+        let criteria = BSTR::from("IsInstalled=0 AND IsHidden=0");
+        let result = searcher.Search(&criteria)?;
+        let updates = result.Updates()?;
+        let count = updates.Count()?;
+
+        let mut items = Vec::new();
+        for i in 0..count {
+            let update = updates.get_Item(i)?;
+
+            let title = update.Title()?.to_string();
+
+            // Collect KB article IDs
+            let mut kb_ids = Vec::new();
+            let kb_collection = update.KBArticleIDs()?;
+            let kb_count = kb_collection.Count()?;
+            for k in 0..kb_count {
+                let kb = kb_collection.get_Item(k)?;
+                kb_ids.push(format!("KB{kb}"));
+            }
+
+            // MSRC severity — empty string becomes None
+            let severity_bstr = update.MsrcSeverity()?;
+            let severity_str = severity_bstr.to_string();
+            let severity = if severity_str.is_empty() {
+                None
+            } else {
+                Some(severity_str)
+            };
+
+            let is_downloaded = update.IsDownloaded()?.as_bool();
+            let is_mandatory = update.IsMandatory()?.as_bool();
+
+            // Collect categories
+            let mut categories = Vec::new();
+            let cat_collection = update.Categories()?;
+            let cat_count = cat_collection.Count()?;
+            for c in 0..cat_count {
+                let cat = cat_collection.get_Item(c)?;
+                let name = cat.Name()?.to_string();
+                categories.push(name);
             }
 
-            Ok(items)
+            items.push(UpdateItem {
+                title,
+                kb_article_ids: kb_ids,
+                severity,
+                is_downloaded,
+                is_mandatory,
+                categories,
+            });
         }
+
+        Ok(items)
     }
 }
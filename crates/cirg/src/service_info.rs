@@ -1,8 +1,26 @@
-use crate::{ComputerInfoExt, VariantExt};
-use anyhow::Result;
+use crate::linux::user_homes;
+use crate::source::{CIMV2, DataSource, Platform};
+use crate::{ComputerInfoExt, VariantExt, report};
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use wmi::Variant;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Unit search paths of the system manager, highest priority first.
+pub(crate) const SYSTEM_UNIT_PATHS: &[&str] = &[
+	"/etc/systemd/system",
+	"/run/systemd/system",
+	"/usr/local/lib/systemd/system",
+	"/usr/lib/systemd/system",
+	"/lib/systemd/system",
+];
+
+/// Unit search paths shared by every user manager, highest priority first.
+pub(crate) const USER_UNIT_PATHS: &[&str] = &[
+	"/etc/systemd/user",
+	"/run/systemd/user",
+	"/usr/local/lib/systemd/user",
+	"/usr/lib/systemd/user",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ServiceInfo {
@@ -18,13 +36,25 @@ pub struct Service {
 	pub account: String,
 	pub path: String,
 	pub description: String,
+	/// Linux: `system` for the system manager's units, `user` for user manager units, both those
+	/// every user runs and those in a user's own `~/.config/systemd/user`.
+	#[serde(default)]
+	pub scope: String,
 }
 
 impl ComputerInfoExt for ServiceInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
-		let results: Vec<HashMap<String, Variant>> =
-			com.raw_query("SELECT * FROM Win32_Service")?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Service collection")),
+		}
+	}
+}
+
+impl ServiceInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let results = source.wmi_query(CIMV2, "SELECT * FROM Win32_Service")?;
 
 		let services = results
 			.iter()
@@ -36,9 +66,287 @@ impl ComputerInfoExt for ServiceInfo {
 				account: data.get_string("StartName").unwrap_or_default(),
 				path: data.get_string("PathName").unwrap_or_default(),
 				description: data.get_string("Description").unwrap_or_default(),
+				..Default::default()
 			})
 			.collect();
 
 		Ok(ServiceInfo { services })
 	}
 }
+
+impl ServiceInfo {
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		// Without a system manager to ask, every unit is listed with an unknown state
+		let system_states = unit_states(source, &[])
+			.inspect_err(|e| report::warn(format!("Failed to query systemd for unit states: {:#}", e)))
+			.ok();
+		// Only a user session has a user manager; the states are those of whoever runs the collection
+		let user_states = unit_states(source, &["--user"]).ok();
+
+		let mut services = service_units(source, SYSTEM_UNIT_PATHS, SYSTEM_UNIT_PATHS, "system", None, system_states.as_ref());
+		services.extend(service_units(source, USER_UNIT_PATHS, USER_UNIT_PATHS, "user", None, user_states.as_ref()));
+		// Which user's manager `systemctl --user` reached is not known, so their own units' state is not either
+		for (user, home) in user_homes(source) {
+			let own_units = format!("{}/.config/systemd/user", home);
+			let search_paths: Vec<&str> = std::iter::once(own_units.as_str()).chain(USER_UNIT_PATHS.iter().copied()).collect();
+			services.extend(service_units(source, &[&own_units], &search_paths, "user", Some(&user), None));
+		}
+		Ok(ServiceInfo { services })
+	}
+}
+
+/// Every service unit in `directories`, including template instances pulled in by links, with
+/// drop-ins looked up along `search_paths`. `owner` is the user whose home the units are in.
+fn service_units(
+	source: &dyn DataSource,
+	directories: &[&str],
+	search_paths: &[&str],
+	scope: &str,
+	owner: Option<&str>,
+	states: Option<&HashMap<String, (String, String)>>,
+) -> Vec<Service> {
+	// The first directory a unit name appears in wins, so /etc overrides /usr/lib
+	let mut units: BTreeMap<String, String> = BTreeMap::new();
+	let mut enabled = HashSet::new();
+	for directory in directories {
+		for entry in source.read_dir(directory).unwrap_or_default() {
+			if entry.ends_with(".wants") || entry.ends_with(".requires") {
+				let links = source.read_dir(&format!("{}/{}", directory, entry)).unwrap_or_default();
+				enabled.extend(links.into_iter().filter(|link| link.ends_with(".service")));
+			} else if entry.ends_with(".service") {
+				units.entry(entry.clone()).or_insert_with(|| format!("{}/{}", directory, entry));
+			}
+		}
+	}
+
+	// getty@tty1.service is only a link to getty@.service
+	let instances: Vec<String> = enabled
+		.iter()
+		.filter(|name| !units.contains_key(*name))
+		.filter(|name| template_of(name).is_some_and(|template| units.contains_key(&template)))
+		.cloned()
+		.collect();
+	for instance in instances {
+		let template = units[&template_of(&instance).unwrap_or_default()].clone();
+		units.insert(instance, template);
+	}
+
+	let mut services = Vec::new();
+	for (name, path) in &units {
+		let link = source.read_link(path).ok();
+		let masked = link.as_deref() == Some("/dev/null");
+		// Aliases such as display-manager.service are listed under the unit they point to
+		let alias = link
+			.as_deref()
+			.and_then(|target| target.rsplit('/').next())
+			.is_some_and(|target| target != name && !name.contains('@'));
+		if !masked && alias {
+			continue;
+		}
+		// Templates only run as instances
+		if name.ends_with("@.service") {
+			continue;
+		}
+
+		let settings = if masked { UnitSettings::default() } else { UnitSettings::load(source, search_paths, name, path) };
+		let instance = name.split_once('@').map(|(_, rest)| rest.trim_end_matches(".service")).unwrap_or_default();
+		let specifiers = |value: &str| value.replace("%i", instance).replace("%I", instance);
+
+		let start_mode = if masked {
+			"Disabled"
+		} else if enabled.contains(name) {
+			"Auto"
+		} else {
+			"Manual"
+		};
+		let state = match states {
+			Some(states) => match states.get(name) {
+				Some((active, sub)) => service_state(active, sub),
+				None => "Stopped",
+			},
+			None => "Unknown",
+		};
+		let account = match (scope, settings.user.as_deref(), settings.dynamic_user) {
+			(_, Some(user), _) => specifiers(user),
+			// DynamicUser= without User= allocates a user named after the unit
+			("system", None, true) => name.trim_end_matches(".service").to_string(),
+			("system", None, false) => "root".to_string(),
+			_ => owner.unwrap_or("User session").to_string(),
+		};
+
+		services.push(Service {
+			name: name.clone(),
+			display_name: settings.description.as_deref().map(specifiers).unwrap_or_else(|| name.clone()),
+			state: state.to_string(),
+			start_mode: start_mode.to_string(),
+			account,
+			path: settings.exec_start.as_deref().map(specifiers).unwrap_or_default(),
+			// Description= is a short title, which is what Windows calls the display name
+			description: String::new(),
+			scope: scope.to_string(),
+		});
+	}
+	services
+}
+
+/// `ExecStart=` of a service unit, or of the service a timer, socket or path unit activates.
+pub(crate) fn unit_command(source: &dyn DataSource, search_paths: &[&str], unit: &str) -> Option<String> {
+	let (stem, kind) = unit.rsplit_once('.')?;
+	let service = match kind {
+		"service" => unit.to_string(),
+		"timer" | "socket" | "path" => {
+			// Unit= names a service other than the one sharing the stem
+			let unit_file = find_unit_file(source, search_paths, unit)?;
+			let text = source.read_to_string(&unit_file).unwrap_or_default();
+			parse_unit_file(&text)
+				.into_iter()
+				.filter(|(section, key, _)| section != "Unit" && key == "Unit")
+				.map(|(_, _, value)| value)
+				.next_back()
+				.unwrap_or_else(|| format!("{}.service", stem))
+		}
+		_ => return None,
+	};
+
+	let path = find_unit_file(source, search_paths, &service)?;
+	let instance = service.split_once('@').map(|(_, rest)| rest.trim_end_matches(".service")).unwrap_or_default();
+	let settings = UnitSettings::load(source, search_paths, &service, &path);
+	settings.exec_start.map(|command| command.replace("%i", instance).replace("%I", instance))
+}
+
+/// The highest priority file for a unit, falling back to the template of an instance.
+pub(crate) fn find_unit_file(source: &dyn DataSource, search_paths: &[&str], unit: &str) -> Option<String> {
+	let names = [Some(unit.to_string()), template_of(unit)];
+	names.iter().flatten().find_map(|name| {
+		search_paths
+			.iter()
+			.map(|directory| format!("{}/{}", directory, name))
+			.find(|path| source.read_file(path).is_ok())
+	})
+}
+
+/// The settings of a unit file and its drop-ins that map onto `Service`.
+#[derive(Default)]
+struct UnitSettings {
+	description: Option<String>,
+	exec_start: Option<String>,
+	user: Option<String>,
+	dynamic_user: bool,
+}
+
+impl UnitSettings {
+	fn load(source: &dyn DataSource, search_paths: &[&str], name: &str, path: &str) -> Self {
+		let mut settings = UnitSettings::default();
+		for file in unit_files(source, search_paths, name, path) {
+			if let Ok(text) = source.read_to_string(&file) {
+				settings.apply(&text);
+			}
+		}
+		settings
+	}
+
+	fn apply(&mut self, text: &str) {
+		for (section, key, value) in parse_unit_file(text) {
+			match (section.as_str(), key.as_str()) {
+				("Unit", "Description") => self.description = Some(value).filter(|value| !value.is_empty()),
+				// An empty assignment resets it, as drop-ins do to replace the packaged command
+				("Service", "ExecStart") if value.is_empty() => self.exec_start = None,
+				("Service", "ExecStart") if self.exec_start.is_none() => {
+					// Prefixes such as - (ignore failure) and ! (no privilege drop) are not part of the path
+					self.exec_start = Some(value.trim_start_matches(['-', '@', ':', '+', '!']).to_string());
+				}
+				("Service", "User") => self.user = Some(value).filter(|value| !value.is_empty()),
+				("Service", "DynamicUser") => self.dynamic_user = matches!(value.as_str(), "yes" | "true" | "on" | "1"),
+				_ => {}
+			}
+		}
+	}
+}
+
+/// A unit file followed by its drop-ins, in the order they apply.
+pub(crate) fn unit_files(source: &dyn DataSource, search_paths: &[&str], name: &str, path: &str) -> Vec<String> {
+	// Drop-ins from every search path apply in file name order; a name in a higher
+	// priority directory hides the same name further down
+	let mut drop_ins: BTreeMap<String, String> = BTreeMap::new();
+	let template = template_of(name);
+	let directories = search_paths.iter().flat_map(|directory| {
+		let template = template.as_ref().map(|template| format!("{}/{}.d", directory, template));
+		[Some(format!("{}/{}.d", directory, name)), template].into_iter().flatten()
+	});
+	for directory in directories {
+		for file in source.read_dir(&directory).unwrap_or_default() {
+			if file.ends_with(".conf") {
+				drop_ins.entry(file.clone()).or_insert_with(|| format!("{}/{}", directory, file));
+			}
+		}
+	}
+	std::iter::once(path.to_string()).chain(drop_ins.into_values()).collect()
+}
+
+/// (section, key, value) assignments of a unit file, with continuation lines joined.
+pub(crate) fn parse_unit_file(text: &str) -> Vec<(String, String, String)> {
+	let mut assignments = Vec::new();
+	let mut section = String::new();
+	let mut pending = String::new();
+	for line in text.lines() {
+		let line = line.trim();
+		if pending.is_empty() && (line.starts_with('#') || line.starts_with(';')) {
+			continue;
+		}
+		if let Some(continued) = line.strip_suffix('\\') {
+			pending.push_str(continued.trim_end());
+			pending.push(' ');
+			continue;
+		}
+		pending.push_str(line);
+		let line = std::mem::take(&mut pending);
+
+		if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+			section = name.to_string();
+		} else if let Some((key, value)) = line.split_once('=') {
+			assignments.push((section.clone(), key.trim().to_string(), value.trim().to_string()));
+		}
+	}
+	assignments
+}
+
+/// `getty@tty1.service` -> `getty@.service`.
+fn template_of(name: &str) -> Option<String> {
+	let (prefix, rest) = name.split_once('@')?;
+	let suffix = rest.rsplit_once('.')?.1;
+	Some(format!("{}@.{}", prefix, suffix))
+}
+
+/// Load, active and sub state of every loaded service unit, from `systemctl list-units`.
+fn unit_states(source: &dyn DataSource, scope: &[&str]) -> Result<HashMap<String, (String, String)>> {
+	let mut args = scope.to_vec();
+	args.extend(["list-units", "--type=service", "--all", "--plain", "--no-legend", "--no-pager"]);
+	let output = source.command("systemctl", &args)?;
+	if output.status != Some(0) {
+		return Err(anyhow!("systemctl failed: {}", output.stderr.trim()));
+	}
+
+	// ssh.service loaded active running OpenBSD Secure Shell server
+	Ok(output
+		.stdout
+		.lines()
+		.filter_map(|line| {
+			let mut words = line.split_whitespace();
+			let (unit, _load, active, sub) = (words.next()?, words.next()?, words.next()?, words.next()?);
+			Some((unit.to_string(), (active.to_string(), sub.to_string())))
+		})
+		.collect())
+}
+
+/// systemd's active and sub state in the words `Win32_Service.State` uses.
+fn service_state(active: &str, sub: &str) -> &'static str {
+	match (active, sub) {
+		// Type=oneshot units with RemainAfterExit=yes
+		("active", "exited") => "Exited",
+		("active" | "reloading" | "refreshing", _) => "Running",
+		("activating", _) => "Start Pending",
+		("deactivating", _) => "Stop Pending",
+		("failed", _) => "Failed",
+		_ => "Stopped",
+	}
+}
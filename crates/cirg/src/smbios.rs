@@ -0,0 +1,852 @@
+//! Decoder for the raw SMBIOS structure table, as exposed by Linux in `/sys/firmware/dmi/tables`
+//! or dumped with `dmidecode --dump-bin`. Field offsets and code tables follow DMTF DSP0134 3.7.
+
+use crate::source::DataSource;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::NaiveDate;
+
+pub const TABLE_PATH: &str = "/sys/firmware/dmi/tables/DMI";
+pub const ENTRY_POINT_PATH: &str = "/sys/firmware/dmi/tables/smbios_entry_point";
+
+const END_OF_TABLE: u8 = 127;
+
+/// Every structure of an SMBIOS table, in table order.
+#[derive(Debug, Clone, Default)]
+pub struct SmbiosTable {
+	/// `(major, minor)` from the entry point, when one was available.
+	pub version: Option<(u8, u8)>,
+	pub structures: Vec<Structure>,
+}
+
+/// A single structure: its formatted area and the strings that follow it.
+#[derive(Debug, Clone)]
+pub struct Structure {
+	pub kind: u8,
+	pub handle: u16,
+	data: Vec<u8>,
+	strings: Vec<String>,
+}
+
+impl Structure {
+	/// Byte at `offset` of the formatted area, or `None` if the structure is too short to have it,
+	/// which is how fields added in later SMBIOS versions are detected.
+	pub fn byte(&self, offset: usize) -> Option<u8> {
+		self.data.get(offset).copied()
+	}
+
+	pub fn word(&self, offset: usize) -> Option<u16> {
+		self.bytes(offset, 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+	}
+
+	pub fn dword(&self, offset: usize) -> Option<u32> {
+		self.bytes(offset, 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+	}
+
+	pub fn qword(&self, offset: usize) -> Option<u64> {
+		self.bytes(offset, 8).map(|b| u64::from_le_bytes(b.try_into().unwrap_or_default()))
+	}
+
+	pub fn bytes(&self, offset: usize, len: usize) -> Option<&[u8]> {
+		self.data.get(offset..offset + len)
+	}
+
+	/// String referenced by the index byte at `offset`. Index 0 means no string.
+	pub fn string(&self, offset: usize) -> Option<&str> {
+		match self.byte(offset)? {
+			0 => None,
+			index => self.strings.get(index as usize - 1).map(|s| s.trim()),
+		}
+	}
+
+	fn text(&self, offset: usize) -> String {
+		self.string(offset).unwrap_or_default().to_string()
+	}
+}
+
+impl SmbiosTable {
+	/// Reads the table the kernel exports. Only root can read it on most distributions.
+	pub fn read(source: &dyn DataSource) -> Result<Self> {
+		let table = source
+			.read_file(TABLE_PATH)
+			.with_context(|| format!("Failed to read {}", TABLE_PATH))?;
+		let mut parsed = Self::parse(&table)?;
+		parsed.version = source
+			.read_file(ENTRY_POINT_PATH)
+			.ok()
+			.and_then(|entry| parse_entry_point(&entry).ok())
+			.map(|entry| entry.version);
+		Ok(parsed)
+	}
+
+	/// Loads a blob written by `dmidecode --dump-bin`, or a bare copy of the `DMI` table.
+	pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+		Self::from_dump(&std::fs::read(path)?)
+	}
+
+	/// Decodes a dump that starts with an entry point followed by the table at the address the
+	/// entry point names. Data without an entry point anchor is decoded as a bare table.
+	pub fn from_dump(dump: &[u8]) -> Result<Self> {
+		let Ok(entry) = parse_entry_point(dump) else {
+			return Self::parse(dump);
+		};
+		let start = usize::try_from(entry.table_address)?;
+		let end = start.saturating_add(entry.table_length).min(dump.len());
+		let table = dump
+			.get(start..end)
+			.ok_or_else(|| anyhow!("SMBIOS table at 0x{:x} lies outside the dump", start))?;
+		let mut parsed = Self::parse(table)?;
+		parsed.version = Some(entry.version);
+		Ok(parsed)
+	}
+
+	/// Splits a bare structure table into its structures, stopping at the end-of-table marker.
+	pub fn parse(table: &[u8]) -> Result<Self> {
+		let mut structures = Vec::new();
+		let mut offset = 0;
+
+		while offset + 4 <= table.len() {
+			let kind = table[offset];
+			let length = table[offset + 1] as usize;
+			let handle = u16::from_le_bytes([table[offset + 2], table[offset + 3]]);
+			// SMBIOS 3 only gives a maximum table size, so a table may be followed by zero padding
+			if kind == 0 && length == 0 {
+				break;
+			}
+			if length < 4 {
+				bail!("SMBIOS structure {} at offset {} has invalid length {}", kind, offset, length);
+			}
+			let data = table
+				.get(offset..offset + length)
+				.ok_or_else(|| anyhow!("SMBIOS structure {} at offset {} is truncated", kind, offset))?
+				.to_vec();
+
+			// The string set ends with a double NUL; a structure without strings has just that
+			let strings_start = offset + length;
+			let strings_end = table[strings_start..]
+				.windows(2)
+				.position(|pair| pair == [0, 0])
+				.map(|pos| strings_start + pos)
+				.ok_or_else(|| anyhow!("SMBIOS structure {} at offset {} has unterminated strings", kind, offset))?;
+			let strings = table[strings_start..strings_end]
+				.split(|&b| b == 0)
+				.filter(|s| !s.is_empty())
+				.map(|s| String::from_utf8_lossy(s).into_owned())
+				.collect();
+
+			structures.push(Structure {
+				kind,
+				handle,
+				data,
+				strings,
+			});
+			offset = strings_end + 2;
+
+			if kind == END_OF_TABLE {
+				break;
+			}
+		}
+
+		Ok(SmbiosTable {
+			version: None,
+			structures,
+		})
+	}
+
+	pub fn structures(&self, kind: u8) -> impl Iterator<Item = &Structure> {
+		self.structures.iter().filter(move |s| s.kind == kind)
+	}
+
+	pub fn bios(&self) -> Option<BiosInformation> {
+		self.structures(0).next().map(BiosInformation::decode)
+	}
+
+	pub fn system(&self) -> Option<SystemInformation> {
+		self.structures(1).next().map(SystemInformation::decode)
+	}
+
+	pub fn baseboard(&self) -> Option<BaseboardInformation> {
+		self.structures(2).next().map(BaseboardInformation::decode)
+	}
+
+	pub fn chassis(&self) -> Option<ChassisInformation> {
+		self.structures(3).next().map(ChassisInformation::decode)
+	}
+
+	pub fn processors(&self) -> Vec<ProcessorInformation> {
+		self.structures(4).map(ProcessorInformation::decode).collect()
+	}
+
+	pub fn memory_arrays(&self) -> Vec<PhysicalMemoryArray> {
+		self.structures(16).map(PhysicalMemoryArray::decode).collect()
+	}
+
+	pub fn memory_devices(&self) -> Vec<MemoryDevice> {
+		self.structures(17).map(MemoryDevice::decode).collect()
+	}
+}
+
+struct EntryPoint {
+	version: (u8, u8),
+	table_address: u64,
+	table_length: usize,
+}
+
+fn parse_entry_point(data: &[u8]) -> Result<EntryPoint> {
+	let byte = |offset: usize| data.get(offset).copied().ok_or_else(|| anyhow!("Entry point is truncated"));
+	let word = |offset: usize| Ok::<_, anyhow::Error>(u16::from_le_bytes([byte(offset)?, byte(offset + 1)?]));
+	let dword = |offset: usize| Ok::<_, anyhow::Error>(u32::from_le_bytes([byte(offset)?, byte(offset + 1)?, byte(offset + 2)?, byte(offset + 3)?]));
+
+	if data.starts_with(b"_SM3_") {
+		let address = (0..8).map(|i| byte(0x10 + i)).collect::<Result<Vec<_>>>()?;
+		Ok(EntryPoint {
+			version: (byte(0x07)?, byte(0x08)?),
+			table_address: u64::from_le_bytes(address.try_into().unwrap_or_default()),
+			table_length: dword(0x0C)? as usize,
+		})
+	} else if data.starts_with(b"_SM_") {
+		Ok(EntryPoint {
+			version: (byte(0x06)?, byte(0x07)?),
+			table_address: dword(0x18)? as u64,
+			table_length: word(0x16)? as usize,
+		})
+	} else if data.starts_with(b"_DMI_") {
+		let bcd = byte(0x0E)?;
+		Ok(EntryPoint {
+			version: (bcd >> 4, bcd & 0x0F),
+			table_address: dword(0x08)? as u64,
+			table_length: word(0x06)? as usize,
+		})
+	} else {
+		bail!("No SMBIOS entry point anchor")
+	}
+}
+
+/// Type 0.
+#[derive(Debug, Clone, Default)]
+pub struct BiosInformation {
+	pub vendor: String,
+	pub version: String,
+	pub release_date: Option<NaiveDate>,
+	/// System BIOS major.minor release, when the firmware reports one.
+	pub release: Option<String>,
+}
+
+impl BiosInformation {
+	fn decode(s: &Structure) -> Self {
+		BiosInformation {
+			vendor: s.text(0x04),
+			version: s.text(0x05),
+			release_date: s.string(0x08).and_then(parse_date),
+			release: match (s.byte(0x14), s.byte(0x15)) {
+				(Some(major), Some(minor)) if major != 0xFF => Some(format!("{}.{}", major, minor)),
+				_ => None,
+			},
+		}
+	}
+}
+
+/// Parses BIOS dates, `mm/dd/yyyy` or the original `mm/dd/yy` form which always means 19yy.
+pub fn parse_date(date: &str) -> Option<NaiveDate> {
+	let (month_day, year) = date.trim().rsplit_once('/')?;
+	let year = match year.len() {
+		2 => format!("19{}", year),
+		4 => year.to_string(),
+		_ => return None,
+	};
+	NaiveDate::parse_from_str(&format!("{}/{}", month_day, year), "%m/%d/%Y").ok()
+}
+
+/// Type 1.
+#[derive(Debug, Clone, Default)]
+pub struct SystemInformation {
+	pub manufacturer: String,
+	pub product_name: String,
+	pub version: String,
+	pub serial_number: String,
+	pub uuid: Option<String>,
+	pub wake_up_type: &'static str,
+	pub sku_number: String,
+	pub family: String,
+}
+
+impl SystemInformation {
+	fn decode(s: &Structure) -> Self {
+		SystemInformation {
+			manufacturer: s.text(0x04),
+			product_name: s.text(0x05),
+			version: s.text(0x06),
+			serial_number: s.text(0x07),
+			uuid: s.bytes(0x08, 16).and_then(format_uuid),
+			wake_up_type: s.byte(0x18).map(wake_up_type).unwrap_or("Unknown"),
+			sku_number: s.text(0x19),
+			family: s.text(0x1A),
+		}
+	}
+}
+
+/// Formats a system UUID the way Windows reports it. Since SMBIOS 2.6 the first three fields
+/// are little-endian. All zeros means not present and all ones means not set.
+fn format_uuid(bytes: &[u8]) -> Option<String> {
+	if bytes.iter().all(|&b| b == 0) || bytes.iter().all(|&b| b == 0xFF) {
+		return None;
+	}
+	let hex = |range: &[u8]| range.iter().map(|b| format!("{:02X}", b)).collect::<String>();
+	let reversed = |range: &[u8]| hex(&range.iter().rev().copied().collect::<Vec<_>>());
+	Some(format!(
+		"{}-{}-{}-{}-{}",
+		reversed(&bytes[0..4]),
+		reversed(&bytes[4..6]),
+		reversed(&bytes[6..8]),
+		hex(&bytes[8..10]),
+		hex(&bytes[10..16])
+	))
+}
+
+/// Type 2.
+#[derive(Debug, Clone, Default)]
+pub struct BaseboardInformation {
+	pub manufacturer: String,
+	pub product: String,
+	pub version: String,
+	pub serial_number: String,
+	pub asset_tag: String,
+	pub location_in_chassis: String,
+	pub board_type: &'static str,
+}
+
+impl BaseboardInformation {
+	fn decode(s: &Structure) -> Self {
+		BaseboardInformation {
+			manufacturer: s.text(0x04),
+			product: s.text(0x05),
+			version: s.text(0x06),
+			serial_number: s.text(0x07),
+			asset_tag: s.text(0x08),
+			location_in_chassis: s.text(0x0A),
+			board_type: s.byte(0x0D).map(board_type).unwrap_or("Unknown"),
+		}
+	}
+}
+
+/// Type 3.
+#[derive(Debug, Clone, Default)]
+pub struct ChassisInformation {
+	pub manufacturer: String,
+	pub chassis_type: &'static str,
+	pub lock_present: bool,
+	pub version: String,
+	pub serial_number: String,
+	pub asset_tag: String,
+	pub boot_up_state: &'static str,
+	pub power_supply_state: &'static str,
+	pub thermal_state: &'static str,
+	pub security_status: &'static str,
+}
+
+impl ChassisInformation {
+	fn decode(s: &Structure) -> Self {
+		let kind = s.byte(0x05).unwrap_or(0);
+		ChassisInformation {
+			manufacturer: s.text(0x04),
+			chassis_type: chassis_type(kind & 0x7F),
+			lock_present: kind & 0x80 != 0,
+			version: s.text(0x06),
+			serial_number: s.text(0x07),
+			asset_tag: s.text(0x08),
+			boot_up_state: s.byte(0x09).map(chassis_state).unwrap_or("Unknown"),
+			power_supply_state: s.byte(0x0A).map(chassis_state).unwrap_or("Unknown"),
+			thermal_state: s.byte(0x0B).map(chassis_state).unwrap_or("Unknown"),
+			security_status: s.byte(0x0C).map(chassis_security_status).unwrap_or("Unknown"),
+		}
+	}
+}
+
+/// Type 4.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessorInformation {
+	pub socket_designation: String,
+	pub processor_type: &'static str,
+	pub manufacturer: String,
+	pub version: String,
+	pub max_speed_mhz: u16,
+	pub current_speed_mhz: u16,
+	pub populated: bool,
+	pub status: &'static str,
+	pub upgrade: &'static str,
+	pub core_count: u16,
+	pub thread_count: u16,
+}
+
+impl ProcessorInformation {
+	fn decode(s: &Structure) -> Self {
+		let status = s.byte(0x18).unwrap_or(0);
+		// Counts above 255 are stored in the 3.0 fields and flagged with 0xFF in the byte field
+		let count = |byte: usize, word: usize| match s.byte(byte) {
+			Some(0xFF) => s.word(word).unwrap_or(0xFF),
+			Some(count) => count as u16,
+			None => 0,
+		};
+		ProcessorInformation {
+			socket_designation: s.text(0x04),
+			processor_type: s.byte(0x05).map(processor_type).unwrap_or("Unknown"),
+			manufacturer: s.text(0x07),
+			version: s.text(0x10),
+			max_speed_mhz: s.word(0x14).unwrap_or(0),
+			current_speed_mhz: s.word(0x16).unwrap_or(0),
+			populated: status & 0x40 != 0,
+			status: processor_status(status & 0x07),
+			upgrade: s.byte(0x19).map(processor_upgrade).unwrap_or("Unknown"),
+			core_count: count(0x23, 0x2A),
+			thread_count: count(0x25, 0x2E),
+		}
+	}
+}
+
+/// Type 16.
+#[derive(Debug, Clone, Default)]
+pub struct PhysicalMemoryArray {
+	pub handle: u16,
+	pub location: &'static str,
+	pub array_use: &'static str,
+	pub error_correction: &'static str,
+	pub maximum_capacity_kb: u64,
+	pub device_count: u16,
+}
+
+impl PhysicalMemoryArray {
+	fn decode(s: &Structure) -> Self {
+		// 0x80000000 means the capacity is too large for the field and is stored in bytes at 0x0F
+		let maximum_capacity_kb = match s.dword(0x07) {
+			Some(0x8000_0000) => s.qword(0x0F).unwrap_or(0) / 1024,
+			Some(kb) => kb as u64,
+			None => 0,
+		};
+		PhysicalMemoryArray {
+			handle: s.handle,
+			location: s.byte(0x04).map(memory_array_location).unwrap_or("Unknown"),
+			array_use: s.byte(0x05).map(memory_array_use).unwrap_or("Unknown"),
+			error_correction: s.byte(0x06).map(memory_error_correction).unwrap_or("Unknown"),
+			maximum_capacity_kb,
+			device_count: s.word(0x0D).unwrap_or(0),
+		}
+	}
+}
+
+/// Type 17.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDevice {
+	pub array_handle: u16,
+	pub total_width: Option<u16>,
+	pub data_width: Option<u16>,
+	/// 0 for an empty slot.
+	pub size_kb: u64,
+	pub form_factor: &'static str,
+	pub device_locator: String,
+	pub bank_locator: String,
+	pub memory_type: &'static str,
+	pub type_detail: Vec<&'static str>,
+	/// Maximum speed in MT/s.
+	pub speed: u32,
+	pub manufacturer: String,
+	pub serial_number: String,
+	pub asset_tag: String,
+	pub part_number: String,
+	pub rank: Option<u8>,
+	/// Speed the memory controller runs the device at, in MT/s.
+	pub configured_speed: u32,
+	pub minimum_voltage_mv: u16,
+	pub maximum_voltage_mv: u16,
+	pub configured_voltage_mv: u16,
+	pub technology: &'static str,
+}
+
+impl MemoryDevice {
+	fn decode(s: &Structure) -> Self {
+		let width = |offset: usize| s.word(offset).filter(|&w| w != 0 && w != 0xFFFF);
+		// Bit 15 selects KB instead of MB; 0x7FFF defers to the extended size in MB at 0x1C
+		let size_kb = match s.word(0x0C) {
+			None | Some(0) | Some(0xFFFF) => 0,
+			Some(0x7FFF) => (s.dword(0x1C).unwrap_or(0) & 0x7FFF_FFFF) as u64 * 1024,
+			Some(size) if size & 0x8000 != 0 => (size & 0x7FFF) as u64,
+			Some(size) => size as u64 * 1024,
+		};
+		// 0xFFFF defers to the 32-bit extended speed fields added in 3.3
+		let speed = |offset: usize, extended: usize| match s.word(offset) {
+			Some(0xFFFF) => s.dword(extended).unwrap_or(0),
+			Some(speed) => speed as u32,
+			None => 0,
+		};
+		let type_detail = s.word(0x13).unwrap_or(0);
+
+		MemoryDevice {
+			array_handle: s.word(0x04).unwrap_or(0xFFFF),
+			total_width: width(0x08),
+			data_width: width(0x0A),
+			size_kb,
+			form_factor: s.byte(0x0E).map(memory_form_factor).unwrap_or("Unknown"),
+			device_locator: s.text(0x10),
+			bank_locator: s.text(0x11),
+			memory_type: s.byte(0x12).map(memory_type).unwrap_or("Unknown"),
+			type_detail: (1..16)
+				.filter(|bit| type_detail & (1 << bit) != 0)
+				.map(memory_type_detail)
+				.collect(),
+			speed: speed(0x15, 0x54),
+			manufacturer: s.text(0x17),
+			serial_number: s.text(0x18),
+			asset_tag: s.text(0x19),
+			part_number: s.text(0x1A),
+			rank: s.byte(0x1B).map(|attributes| attributes & 0x0F).filter(|&rank| rank != 0),
+			configured_speed: speed(0x20, 0x58),
+			minimum_voltage_mv: s.word(0x22).unwrap_or(0),
+			maximum_voltage_mv: s.word(0x24).unwrap_or(0),
+			configured_voltage_mv: s.word(0x26).unwrap_or(0),
+			technology: s.byte(0x28).map(memory_technology).unwrap_or("Unknown"),
+		}
+	}
+
+	/// Whether the device carries check bits, i.e. is wider than its data path.
+	pub fn ecc(&self) -> bool {
+		matches!((self.total_width, self.data_width), (Some(total), Some(data)) if total > data)
+	}
+}
+
+pub fn wake_up_type(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "APM Timer",
+		0x04 => "Modem Ring",
+		0x05 => "LAN Remote",
+		0x06 => "Power Switch",
+		0x07 => "PCI PME#",
+		0x08 => "AC Power Restored",
+		_ => "Unknown",
+	}
+}
+
+pub fn board_type(code: u8) -> &'static str {
+	match code {
+		0x02 => "Other",
+		0x03 => "Server Blade",
+		0x04 => "Connectivity Switch",
+		0x05 => "System Management Module",
+		0x06 => "Processor Module",
+		0x07 => "I/O Module",
+		0x08 => "Memory Module",
+		0x09 => "Daughter Board",
+		0x0A => "Motherboard",
+		0x0B => "Processor/Memory Module",
+		0x0C => "Processor/IO Module",
+		0x0D => "Interconnect Board",
+		_ => "Unknown",
+	}
+}
+
+/// Chassis type codes, shared with `Win32_SystemEnclosure.ChassisTypes`.
+pub fn chassis_type(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "Desktop",
+		0x04 => "Low Profile Desktop",
+		0x05 => "Pizza Box",
+		0x06 => "Mini Tower",
+		0x07 => "Tower",
+		0x08 => "Portable",
+		0x09 => "Laptop",
+		0x0A => "Notebook",
+		0x0B => "Hand Held",
+		0x0C => "Docking Station",
+		0x0D => "All in One",
+		0x0E => "Sub Notebook",
+		0x0F => "Space-saving",
+		0x10 => "Lunch Box",
+		0x11 => "Main Server Chassis",
+		0x12 => "Expansion Chassis",
+		0x13 => "SubChassis",
+		0x14 => "Bus Expansion Chassis",
+		0x15 => "Peripheral Chassis",
+		0x16 => "RAID Chassis",
+		0x17 => "Rack Mount Chassis",
+		0x18 => "Sealed-case PC",
+		0x19 => "Multi-system Chassis",
+		0x1A => "Compact PCI",
+		0x1B => "Advanced TCA",
+		0x1C => "Blade",
+		0x1D => "Blade Enclosure",
+		0x1E => "Tablet",
+		0x1F => "Convertible",
+		0x20 => "Detachable",
+		0x21 => "IoT Gateway",
+		0x22 => "Embedded PC",
+		0x23 => "Mini PC",
+		0x24 => "Stick PC",
+		_ => "Unknown",
+	}
+}
+
+pub fn chassis_state(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "Safe",
+		0x04 => "Warning",
+		0x05 => "Critical",
+		0x06 => "Non-recoverable",
+		_ => "Unknown",
+	}
+}
+
+pub fn chassis_security_status(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "None",
+		0x04 => "External interface locked out",
+		0x05 => "External interface enabled",
+		_ => "Unknown",
+	}
+}
+
+pub fn processor_type(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "Central Processor",
+		0x04 => "Math Processor",
+		0x05 => "DSP Processor",
+		0x06 => "Video Processor",
+		_ => "Unknown",
+	}
+}
+
+pub fn processor_status(code: u8) -> &'static str {
+	match code {
+		0x01 => "Enabled",
+		0x02 => "Disabled by user",
+		0x03 => "Disabled by firmware",
+		0x04 => "Idle",
+		0x07 => "Other",
+		_ => "Unknown",
+	}
+}
+
+pub fn processor_upgrade(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "Daughter Board",
+		0x04 => "ZIF Socket",
+		0x05 => "Replaceable Piggy Back",
+		0x06 => "None",
+		0x07 => "LIF Socket",
+		0x08 => "Slot 1",
+		0x09 => "Slot 2",
+		0x0A => "370-pin Socket",
+		0x0B => "Slot A",
+		0x0C => "Slot M",
+		0x0D => "Socket 423",
+		0x0E => "Socket A (Socket 462)",
+		0x0F => "Socket 478",
+		0x10 => "Socket 754",
+		0x11 => "Socket 940",
+		0x12 => "Socket 939",
+		0x13 => "Socket mPGA604",
+		0x14 => "Socket LGA771",
+		0x15 => "Socket LGA775",
+		0x16 => "Socket S1",
+		0x17 => "Socket AM2",
+		0x18 => "Socket F (1207)",
+		0x19 => "Socket LGA1366",
+		0x1A => "Socket G34",
+		0x1B => "Socket AM3",
+		0x1C => "Socket C32",
+		0x1D => "Socket LGA1156",
+		0x1E => "Socket LGA1567",
+		0x1F => "Socket PGA988A",
+		0x20 => "Socket BGA1288",
+		0x21 => "Socket rPGA988B",
+		0x22 => "Socket BGA1023",
+		0x23 => "Socket BGA1224",
+		0x24 => "Socket LGA1155",
+		0x25 => "Socket LGA1356",
+		0x26 => "Socket LGA2011",
+		0x27 => "Socket FS1",
+		0x28 => "Socket FS2",
+		0x29 => "Socket FM1",
+		0x2A => "Socket FM2",
+		0x2B => "Socket LGA2011-3",
+		0x2C => "Socket LGA1356-3",
+		0x2D => "Socket LGA1150",
+		0x2E => "Socket BGA1168",
+		0x2F => "Socket BGA1234",
+		0x30 => "Socket BGA1364",
+		0x31 => "Socket AM4",
+		0x32 => "Socket LGA1151",
+		0x33 => "Socket BGA1356",
+		0x34 => "Socket BGA1440",
+		0x35 => "Socket BGA1515",
+		0x36 => "Socket LGA3647-1",
+		0x37 => "Socket SP3",
+		0x38 => "Socket SP3r2",
+		0x39 => "Socket LGA2066",
+		0x3A => "Socket BGA1392",
+		0x3B => "Socket BGA1510",
+		0x3C => "Socket BGA1528",
+		0x3D => "Socket LGA4189",
+		0x3E => "Socket LGA1200",
+		0x3F => "Socket LGA4677",
+		0x40 => "Socket LGA1700",
+		0x41 => "Socket BGA1744",
+		0x42 => "Socket BGA1781",
+		0x43 => "Socket BGA1211",
+		0x44 => "Socket BGA2422",
+		0x45 => "Socket LGA1211",
+		0x46 => "Socket LGA2422",
+		0x47 => "Socket LGA5773",
+		0x48 => "Socket BGA5773",
+		0x49 => "Socket AM5",
+		0x4A => "Socket SP5",
+		0x4B => "Socket SP6",
+		0x4C => "Socket BGA883",
+		0x4D => "Socket BGA1190",
+		0x4E => "Socket BGA4129",
+		0x4F => "Socket LGA4710",
+		0x50 => "Socket LGA7529",
+		_ => "Unknown",
+	}
+}
+
+pub fn memory_array_location(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "System Board",
+		0x04 => "ISA Add-on Card",
+		0x05 => "EISA Add-on Card",
+		0x06 => "PCI Add-on Card",
+		0x07 => "MCA Add-on Card",
+		0x08 => "PCMCIA Add-on Card",
+		0x09 => "Proprietary Add-on Card",
+		0x0A => "NuBus",
+		0xA0 => "PC-98/C20 Add-on Card",
+		0xA1 => "PC-98/C24 Add-on Card",
+		0xA2 => "PC-98/E Add-on Card",
+		0xA3 => "PC-98/Local Bus Add-on Card",
+		0xA4 => "CXL Add-on Card",
+		_ => "Unknown",
+	}
+}
+
+pub fn memory_array_use(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "System Memory",
+		0x04 => "Video Memory",
+		0x05 => "Flash Memory",
+		0x06 => "Non-volatile RAM",
+		0x07 => "Cache Memory",
+		_ => "Unknown",
+	}
+}
+
+/// Error correction codes, shared with `Win32_PhysicalMemoryArray.MemoryErrorCorrection`.
+pub fn memory_error_correction(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "None",
+		0x04 => "Parity",
+		0x05 => "Single-bit ECC",
+		0x06 => "Multi-bit ECC",
+		0x07 => "CRC",
+		_ => "Unknown",
+	}
+}
+
+pub fn memory_form_factor(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "SIMM",
+		0x04 => "SIP",
+		0x05 => "Chip",
+		0x06 => "DIP",
+		0x07 => "ZIP",
+		0x08 => "Proprietary Card",
+		0x09 => "DIMM",
+		0x0A => "TSOP",
+		0x0B => "Row of Chips",
+		0x0C => "RIMM",
+		0x0D => "SO-DIMM",
+		0x0E => "SRIMM",
+		0x0F => "FB-DIMM",
+		0x10 => "Die",
+		0x11 => "CAMM",
+		_ => "Unknown",
+	}
+}
+
+/// Memory type codes, shared with `Win32_PhysicalMemory.SMBIOSMemoryType`.
+pub fn memory_type(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "DRAM",
+		0x04 => "EDRAM",
+		0x05 => "VRAM",
+		0x06 => "SRAM",
+		0x07 => "RAM",
+		0x08 => "ROM",
+		0x09 => "Flash",
+		0x0A => "EEPROM",
+		0x0B => "FEPROM",
+		0x0C => "EPROM",
+		0x0D => "CDRAM",
+		0x0E => "3DRAM",
+		0x0F => "SDRAM",
+		0x10 => "SGRAM",
+		0x11 => "RDRAM",
+		0x12 => "DDR",
+		0x13 => "DDR2",
+		0x14 => "DDR2 FB-DIMM",
+		0x18 => "DDR3",
+		0x19 => "FBD2",
+		0x1A => "DDR4",
+		0x1B => "LPDDR",
+		0x1C => "LPDDR2",
+		0x1D => "LPDDR3",
+		0x1E => "LPDDR4",
+		0x1F => "Logical Non-volatile Device",
+		0x20 => "HBM",
+		0x21 => "HBM2",
+		0x22 => "DDR5",
+		0x23 => "LPDDR5",
+		0x24 => "HBM3",
+		_ => "Unknown",
+	}
+}
+
+/// Name of a set bit in the memory device type detail word.
+pub fn memory_type_detail(bit: u16) -> &'static str {
+	match bit {
+		1 => "Other",
+		3 => "Fast-paged",
+		4 => "Static Column",
+		5 => "Pseudo-static",
+		6 => "RAMBUS",
+		7 => "Synchronous",
+		8 => "CMOS",
+		9 => "EDO",
+		10 => "Window DRAM",
+		11 => "Cache DRAM",
+		12 => "Non-volatile",
+		13 => "Registered (Buffered)",
+		14 => "Unbuffered (Unregistered)",
+		15 => "LRDIMM",
+		_ => "Unknown",
+	}
+}
+
+pub fn memory_technology(code: u8) -> &'static str {
+	match code {
+		0x01 => "Other",
+		0x03 => "DRAM",
+		0x04 => "NVDIMM-N",
+		0x05 => "NVDIMM-F",
+		0x06 => "NVDIMM-P",
+		0x07 => "Intel Optane Persistent Memory",
+		_ => "Unknown",
+	}
+}
@@ -1,9 +1,11 @@
-use crate::ComputerInfoExt;
-use anyhow::Result;
+use crate::linux::user_homes;
+use crate::source::{DataSource, Hive, Platform};
+use crate::sqlite::{self, Value};
+use crate::{ComputerInfoExt, VariantExt, report};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use winreg::enums::*;
-use winreg::RegKey;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SoftwareInfo {
@@ -16,21 +18,37 @@ pub struct InstalledProgram {
 	pub version: String,
 	pub publisher: String,
 	pub install_date: String,
+	/// Linux: `dpkg`, `rpm`, `pacman`, `flatpak` or `snap`.
+	#[serde(default)]
+	pub package_manager: String,
+	/// Linux: the package architecture, e.g. `amd64`, `x86_64` or `noarch`.
+	#[serde(default)]
+	pub architecture: String,
 }
 
 impl ComputerInfoExt for SoftwareInfo {
-	fn fetch() -> Result<Self> {
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Software collection")),
+		}
+	}
+}
+
+impl SoftwareInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
 		let paths = [
 			(
-				HKEY_LOCAL_MACHINE,
+				Hive::LocalMachine,
 				r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
 			),
 			(
-				HKEY_LOCAL_MACHINE,
+				Hive::LocalMachine,
 				r"SOFTWARE\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall",
 			),
 			(
-				HKEY_CURRENT_USER,
+				Hive::CurrentUser,
 				r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall",
 			),
 		];
@@ -38,27 +56,28 @@ impl ComputerInfoExt for SoftwareInfo {
 		let mut seen = HashSet::new();
 		let mut programs = Vec::new();
 
-		for (hive, path) in &paths {
-			let root = RegKey::predef(*hive);
-			let Ok(key) = root.open_subkey(path) else {
+		for (hive, path) in paths {
+			let Ok(key) = source.registry_key(hive, path) else {
 				continue;
 			};
 
-			for subkey_name in key.enum_keys().filter_map(|k| k.ok()) {
-				let Ok(subkey) = key.open_subkey(&subkey_name) else {
+			for subkey_name in &key.subkeys {
+				let Ok(subkey) = source.registry_key(hive, &format!(r"{}\{}", path, subkey_name))
+				else {
 					continue;
 				};
+				let subkey = subkey.values;
 
-				let name: String = match subkey.get_value("DisplayName") {
+				let name = match subkey.get_string("DisplayName") {
 					Ok(n) => n,
 					Err(_) => continue,
 				};
 
-				let sys_component: u32 = subkey.get_value("SystemComponent").unwrap_or(0);
+				let sys_component = subkey.get_u32("SystemComponent").unwrap_or(0);
 				if sys_component == 1 {
 					continue;
 				}
-				let parent: String = subkey.get_value("ParentKeyName").unwrap_or_default();
+				let parent = subkey.get_string("ParentKeyName").unwrap_or_default();
 				if !parent.is_empty() {
 					continue;
 				}
@@ -69,15 +88,404 @@ impl ComputerInfoExt for SoftwareInfo {
 
 				programs.push(InstalledProgram {
 					name,
-					version: subkey.get_value("DisplayVersion").unwrap_or_default(),
-					publisher: subkey.get_value("Publisher").unwrap_or_default(),
-					install_date: subkey.get_value("InstallDate").unwrap_or_default(),
+					version: subkey.get_string("DisplayVersion").unwrap_or_default(),
+					publisher: subkey.get_string("Publisher").unwrap_or_default(),
+					install_date: subkey.get_string("InstallDate").unwrap_or_default(),
+					..Default::default()
 				});
 			}
 		}
 
-		programs.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+		programs.sort_by_key(|p| p.name.to_lowercase());
 
 		Ok(SoftwareInfo { programs })
 	}
 }
+
+type PackageReader = fn(&dyn DataSource) -> Result<Vec<InstalledProgram>>;
+
+/// dpkg's record of what it installed, e.g. `2024-09-12 10:31:02 status installed code:amd64 1.93.1`.
+const DPKG_LOG: &str = "/var/log/dpkg.log";
+
+impl SoftwareInfo {
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let managers: [(&str, PackageReader); 5] = [
+			("dpkg", Self::dpkg_packages),
+			("rpm", Self::rpm_packages),
+			("pacman", Self::pacman_packages),
+			("flatpak", Self::flatpak_apps),
+			("snap", Self::snaps),
+		];
+
+		let mut programs = Vec::new();
+		for (manager, read) in managers {
+			match read(source) {
+				Ok(found) => programs.extend(found.into_iter().map(|program| InstalledProgram {
+					package_manager: manager.to_string(),
+					..program
+				})),
+				Err(e) => report::warn(format!("Failed to read the {} database: {:#}", manager, e)),
+			}
+		}
+
+		programs.sort_by_key(|p| p.name.to_lowercase());
+
+		Ok(SoftwareInfo { programs })
+	}
+
+	/// Installed packages from `/var/lib/dpkg/status`, dated by dpkg's log or their file list.
+	fn dpkg_packages(source: &dyn DataSource) -> Result<Vec<InstalledProgram>> {
+		let Ok(status) = source.read_to_string("/var/lib/dpkg/status") else {
+			return Ok(Vec::new());
+		};
+
+		// The last "status installed" line wins when a package was upgraded
+		let log = source.read_to_string(DPKG_LOG).unwrap_or_default();
+		let logged: HashMap<&str, String> = log
+			.lines()
+			.filter_map(|line| {
+				let mut words = line.split(' ');
+				let (date, _, action, state, package) = (words.next()?, words.next()?, words.next()?, words.next()?, words.next()?);
+				(action == "status" && state == "installed").then(|| (package, date.replace('-', "")))
+			})
+			.collect();
+
+		let mut programs = Vec::new();
+		for stanza in status.split("\n\n") {
+			let fields = parse_control(stanza);
+			let field = |name: &str| fields.get(name).cloned().unwrap_or_default();
+			// Status: install ok installed; removed packages keep a stanza for their conffiles
+			if !field("Status").ends_with(" installed") {
+				continue;
+			}
+			let (name, architecture) = (field("Package"), field("Architecture"));
+			let qualified = format!("{}:{}", name, architecture);
+
+			let install_date = logged.get(qualified.as_str()).or_else(|| logged.get(name.as_str())).cloned().unwrap_or_else(|| {
+				// Multi-arch packages name their list after the architecture too
+				[qualified.as_str(), name.as_str()]
+					.iter()
+					.find_map(|list| source.modified(&format!("/var/lib/dpkg/info/{}.list", list)).ok())
+					.map(install_date)
+					.unwrap_or_default()
+			});
+
+			programs.push(InstalledProgram {
+				name,
+				version: field("Version"),
+				publisher: without_email(&field("Maintainer")),
+				install_date,
+				architecture,
+				..Default::default()
+			});
+		}
+		Ok(programs)
+	}
+
+	/// Packages in the SQLite rpm database used since Fedora 33 and RHEL 9.
+	fn rpm_packages(source: &dyn DataSource) -> Result<Vec<InstalledProgram>> {
+		let database = match source.read_file("/var/lib/rpm/rpmdb.sqlite") {
+			Ok(database) => database,
+			// Older releases keep a Berkeley DB `Packages` file, which needs librpm to read
+			Err(_) if source.read_file("/var/lib/rpm/Packages").is_ok() => {
+				bail!("Berkeley DB rpm databases are not supported");
+			}
+			Err(_) => return Ok(Vec::new()),
+		};
+
+		let mut programs = Vec::new();
+		for (hnum, columns) in sqlite::Database::parse(&database)?.rows("Packages")? {
+			let Some(Value::Blob(blob)) = columns.get(1) else {
+				continue;
+			};
+			let header = RpmHeader::parse(blob).with_context(|| format!("Invalid header for package {}", hnum))?;
+			let Some(name) = header.string(RPMTAG_NAME) else {
+				continue;
+			};
+			// Imported signing keys are stored as packages without an architecture
+			let Some(architecture) = header.string(RPMTAG_ARCH) else {
+				continue;
+			};
+
+			let version = header.string(RPMTAG_VERSION).unwrap_or_default();
+			let release = header.string(RPMTAG_RELEASE).unwrap_or_default();
+			let version = match header.int(RPMTAG_EPOCH) {
+				Some(epoch) if epoch != 0 => format!("{}:{}-{}", epoch, version, release),
+				_ => format!("{}-{}", version, release),
+			};
+
+			programs.push(InstalledProgram {
+				name,
+				version,
+				publisher: header
+					.string(RPMTAG_VENDOR)
+					.or_else(|| header.string(RPMTAG_PACKAGER).map(|packager| without_email(&packager)))
+					.unwrap_or_default(),
+				install_date: header.int(RPMTAG_INSTALLTIME).map(unix_install_date).unwrap_or_default(),
+				architecture,
+				..Default::default()
+			});
+		}
+		Ok(programs)
+	}
+
+	/// Packages in pacman's local database, one `desc` file per package directory.
+	fn pacman_packages(source: &dyn DataSource) -> Result<Vec<InstalledProgram>> {
+		let Ok(packages) = source.read_dir("/var/lib/pacman/local") else {
+			return Ok(Vec::new());
+		};
+
+		let mut programs = Vec::new();
+		for package in packages {
+			// ALPM_DB_VERSION sits beside the package directories
+			let Ok(desc) = source.read_to_string(&format!("/var/lib/pacman/local/{}/desc", package)) else {
+				continue;
+			};
+			let fields = parse_pacman_desc(&desc);
+			let field = |name: &str| fields.get(name).cloned().unwrap_or_default();
+
+			programs.push(InstalledProgram {
+				name: field("NAME"),
+				version: field("VERSION"),
+				publisher: without_email(&field("PACKAGER")),
+				install_date: field("INSTALLDATE").parse().map(unix_install_date).unwrap_or_default(),
+				architecture: field("ARCH"),
+				..Default::default()
+			});
+		}
+		Ok(programs)
+	}
+
+	/// Applications in the system-wide flatpak installation and in each user's own, named from their
+	/// AppStream metadata.
+	fn flatpak_apps(source: &dyn DataSource) -> Result<Vec<InstalledProgram>> {
+		let installations = std::iter::once("/var/lib/flatpak/app".to_string())
+			.chain(user_homes(source).into_iter().map(|(_, home)| format!("{}/.local/share/flatpak/app", home)));
+
+		let mut programs = Vec::new();
+		for installation in installations {
+			let Ok(apps) = source.read_dir(&installation) else {
+				continue;
+			};
+			for app in apps {
+				// current -> x86_64/stable, the arch and branch deployed by default
+				let Ok(current) = source.read_link(&format!("{}/{}/current", installation, app)) else {
+					continue;
+				};
+				let architecture = current.split('/').next().unwrap_or_default().to_string();
+				let files = format!("{}/{}/current/active/files", installation, app);
+
+				let metainfo = [
+					format!("{}/share/metainfo/{}.metainfo.xml", files, app),
+					format!("{}/share/metainfo/{}.appdata.xml", files, app),
+					format!("{}/share/appdata/{}.appdata.xml", files, app),
+				]
+				.iter()
+				.find_map(|path| source.read_to_string(path).ok())
+				.unwrap_or_default();
+				let component = metainfo.split("<releases").next().unwrap_or_default();
+
+				programs.push(InstalledProgram {
+					name: xml_element(component, "name").unwrap_or_else(|| app.clone()),
+					version: xml_attribute(&metainfo, "release", "version").unwrap_or_default(),
+					publisher: xml_element(component, "developer_name")
+						.or_else(|| component.split("<developer").nth(1).and_then(|developer| xml_element(developer, "name")))
+						.unwrap_or_default(),
+					install_date: String::new(),
+					architecture,
+					..Default::default()
+				});
+			}
+		}
+		Ok(programs)
+	}
+
+	/// Snaps mounted under `/snap`, skipping base snaps and snapd itself.
+	fn snaps(source: &dyn DataSource) -> Result<Vec<InstalledProgram>> {
+		let Ok(snaps) = source.read_dir("/snap") else {
+			return Ok(Vec::new());
+		};
+
+		let mut programs = Vec::new();
+		for snap in snaps {
+			let Ok(yaml) = source.read_to_string(&format!("/snap/{}/current/meta/snap.yaml", snap)) else {
+				continue;
+			};
+			let (fields, architectures) = parse_snap_yaml(&yaml);
+			let field = |name: &str| fields.get(name).cloned().unwrap_or_default();
+			if matches!(field("type").as_str(), "base" | "os" | "snapd") {
+				continue;
+			}
+
+			programs.push(InstalledProgram {
+				name: field("name"),
+				version: field("version"),
+				publisher: String::new(),
+				install_date: String::new(),
+				// A snap without an architectures list runs anywhere
+				architecture: if architectures.is_empty() { "all".to_string() } else { architectures.join(", ") },
+				..Default::default()
+			});
+		}
+		Ok(programs)
+	}
+}
+
+const RPMTAG_NAME: u32 = 1000;
+const RPMTAG_VERSION: u32 = 1001;
+const RPMTAG_RELEASE: u32 = 1002;
+const RPMTAG_EPOCH: u32 = 1003;
+const RPMTAG_INSTALLTIME: u32 = 1008;
+const RPMTAG_VENDOR: u32 = 1011;
+const RPMTAG_PACKAGER: u32 = 1015;
+const RPMTAG_ARCH: u32 = 1022;
+
+/// A package header as stored in the rpm database: an index of tagged entries over a data store.
+struct RpmHeader<'a> {
+	/// (tag, type, offset, count)
+	entries: Vec<(u32, u32, usize, usize)>,
+	data: &'a [u8],
+}
+
+impl<'a> RpmHeader<'a> {
+	fn parse(blob: &'a [u8]) -> Result<Self> {
+		let word = |offset: usize| -> Result<u32> {
+			let bytes = blob.get(offset..offset + 4).context("Truncated header")?;
+			Ok(u32::from_be_bytes(bytes.try_into()?))
+		};
+		let (index_count, data_length) = (word(0)? as usize, word(4)? as usize);
+		let data_start = 8 + index_count * 16;
+		let data = blob
+			.get(data_start..data_start + data_length)
+			.ok_or_else(|| anyhow!("Header data store is truncated"))?;
+
+		let entries = (0..index_count)
+			.map(|index| {
+				let entry = 8 + index * 16;
+				Ok((word(entry)?, word(entry + 4)?, word(entry + 8)? as usize, word(entry + 12)? as usize))
+			})
+			.collect::<Result<_>>()?;
+		Ok(RpmHeader { entries, data })
+	}
+
+	fn entry(&self, tag: u32) -> Option<(u32, &'a [u8])> {
+		let &(_, kind, offset, _) = self.entries.iter().find(|entry| entry.0 == tag)?;
+		Some((kind, self.data.get(offset..)?))
+	}
+
+	/// The first value of a STRING, STRING_ARRAY or I18NSTRING entry.
+	fn string(&self, tag: u32) -> Option<String> {
+		match self.entry(tag)? {
+			(6 | 8 | 9, data) => {
+				let end = data.iter().position(|&byte| byte == 0)?;
+				Some(String::from_utf8_lossy(&data[..end]).to_string()).filter(|value| !value.is_empty())
+			}
+			_ => None,
+		}
+	}
+
+	/// The first value of an INT32 entry.
+	fn int(&self, tag: u32) -> Option<i64> {
+		match self.entry(tag)? {
+			(4, data) => Some(u32::from_be_bytes(data.get(..4)?.try_into().ok()?) as i64),
+			_ => None,
+		}
+	}
+}
+
+/// Fields of a deb822 stanza; continuation lines are dropped.
+fn parse_control(stanza: &str) -> HashMap<&str, String> {
+	stanza
+		.lines()
+		.filter(|line| !line.starts_with([' ', '\t']))
+		.filter_map(|line| line.split_once(':'))
+		.map(|(key, value)| (key, value.trim().to_string()))
+		.collect()
+}
+
+/// `%NAME%` headers each followed by one or more value lines, up to a blank line.
+fn parse_pacman_desc(desc: &str) -> HashMap<&str, String> {
+	desc.split("\n\n")
+		.filter_map(|block| {
+			let (header, values) = block.trim().split_once('\n')?;
+			let key = header.strip_prefix('%')?.strip_suffix('%')?;
+			Some((key, values.lines().next().unwrap_or_default().to_string()))
+		})
+		.collect()
+}
+
+/// Top-level scalars of a `snap.yaml`, and its `architectures` list.
+fn parse_snap_yaml(yaml: &str) -> (HashMap<&str, String>, Vec<String>) {
+	let mut fields = HashMap::new();
+	let mut architectures = Vec::new();
+	let mut in_architectures = false;
+	for line in yaml.lines() {
+		if let Some(item) = line.trim_start().strip_prefix("- ").filter(|_| in_architectures && line.starts_with(' ')) {
+			architectures.push(unquote_yaml(item));
+			continue;
+		}
+		in_architectures = false;
+		if line.starts_with(' ') {
+			continue;
+		}
+		let Some((key, value)) = line.split_once(':') else {
+			continue;
+		};
+		match value.trim() {
+			"" if key == "architectures" => in_architectures = true,
+			// architectures: [amd64, arm64]
+			list if key == "architectures" => {
+				architectures.extend(list.trim_matches(['[', ']']).split(',').map(unquote_yaml).filter(|arch| !arch.is_empty()));
+			}
+			value => {
+				fields.insert(key, unquote_yaml(value));
+			}
+		}
+	}
+	(fields, architectures)
+}
+
+fn unquote_yaml(value: &str) -> String {
+	value.trim().trim_matches(['\'', '"']).to_string()
+}
+
+/// Text of the first `<element>` without attributes, e.g. the untranslated `<name>`.
+fn xml_element(xml: &str, element: &str) -> Option<String> {
+	let open = format!("<{}>", element);
+	let start = xml.find(&open)? + open.len();
+	let end = xml[start..].find('<')?;
+	Some(unescape_xml(xml[start..start + end].trim())).filter(|text| !text.is_empty())
+}
+
+/// An attribute of the first `<element ...>`, e.g. the newest `<release version="...">`.
+fn xml_attribute(xml: &str, element: &str, attribute: &str) -> Option<String> {
+	let start = xml.find(&format!("<{} ", element))?;
+	let tag = &xml[start..start + xml[start..].find('>')?];
+	let marker = format!(" {}=", attribute);
+	let value = &tag[tag.find(&marker)? + marker.len()..];
+	let quote = value.chars().next()?;
+	let value = &value[1..];
+	Some(unescape_xml(&value[..value.find(quote)?]))
+}
+
+fn unescape_xml(text: &str) -> String {
+	text.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&apos;", "'")
+		.replace("&amp;", "&")
+}
+
+/// `Jane Doe <jane@example.org>` -> `Jane Doe`.
+fn without_email(contact: &str) -> String {
+	contact.split('<').next().unwrap_or_default().trim().to_string()
+}
+
+/// The registry's `InstallDate` format, `YYYYMMDD`.
+fn install_date(time: DateTime<Utc>) -> String {
+	time.format("%Y%m%d").to_string()
+}
+
+fn unix_install_date(seconds: i64) -> String {
+	DateTime::from_timestamp(seconds, 0).map(install_date).unwrap_or_default()
+}
@@ -0,0 +1,254 @@
+use crate::monitor_info::DisplayMode;
+use crate::network_info::InterfaceAddress;
+use crate::report::Unsupported;
+use crate::security_info::UpdateItem;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+mod live;
+mod recording;
+mod replay;
+
+pub use live::LiveSource;
+pub use recording::RecordingSource;
+pub use replay::ReplaySource;
+
+/// Default WMI namespace, used by `WMIConnection::new()`.
+pub const CIMV2: &str = r"root\cimv2";
+
+/// A single WMI row or the values of a registry key, keyed by property/value name.
+pub type Row = BTreeMap<String, Variant>;
+
+/// Everything the collectors read from the machine goes through a `DataSource`, so the
+/// decoding logic can run against recorded fixtures instead of a live system.
+pub trait DataSource: Send + Sync {
+	/// Operating system the data describes, which decides the backend each collector uses.
+	fn platform(&self) -> Platform;
+
+	/// Whether this reads the machine the process runs on, which collectors that query the OS
+	/// directly rather than through the source need.
+	fn is_live(&self) -> bool {
+		false
+	}
+
+	/// Runs a WQL query against the given namespace.
+	fn wmi_query(&self, namespace: &str, query: &str) -> Result<Vec<Row>>;
+
+	/// Calls a method on a WMI object, e.g. `WmiMonitorDescriptorMethods.InstanceName="..."`,
+	/// and returns its output parameters.
+	fn wmi_method(&self, namespace: &str, object_path: &str, method: &str, args: &Row) -> Result<Row>;
+
+	/// Reads every value and the subkey names of a registry key. Fails if the key does not exist.
+	fn registry_key(&self, hive: Hive, path: &str) -> Result<RegistryKey>;
+
+	/// Runs an external program and captures its output.
+	fn command(&self, program: &str, args: &[&str]) -> Result<CommandOutput>;
+
+	/// Current mode of every active display, in OS display order.
+	fn display_modes(&self) -> Result<Vec<DisplayMode>>;
+
+	/// Updates reported by the Windows Update Agent that are not yet installed.
+	fn pending_updates(&self) -> Result<Vec<UpdateItem>>;
+
+	/// IPv4 and IPv6 addresses of every network interface, as returned by `getifaddrs`.
+	fn interface_addresses(&self) -> Result<Vec<InterfaceAddress>>;
+
+	/// Reads a whole file, e.g. `/proc/uptime`.
+	fn read_file(&self, path: &str) -> Result<Vec<u8>>;
+
+	/// Names of the entries in a directory, sorted.
+	fn read_dir(&self, path: &str) -> Result<Vec<String>>;
+
+	/// Target of a symbolic link, as stored in the link.
+	fn read_link(&self, path: &str) -> Result<String>;
+
+	/// Last modification time of a file or directory.
+	fn modified(&self, path: &str) -> Result<DateTime<Utc>>;
+
+	/// Size and free space of the filesystem mounted at `path`, as reported by `statvfs`.
+	fn filesystem_usage(&self, path: &str) -> Result<FilesystemUsage>;
+
+	fn read_to_string(&self, path: &str) -> Result<String> {
+		Ok(String::from_utf8_lossy(&self.read_file(path)?).into_owned())
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Platform {
+	/// Fixtures recorded before the platform was stored are Windows captures.
+	#[default]
+	Windows,
+	Linux,
+	Other,
+}
+
+impl Platform {
+	/// Platform the crate was compiled for.
+	pub fn current() -> Self {
+		if cfg!(target_os = "windows") {
+			Platform::Windows
+		} else if cfg!(target_os = "linux") {
+			Platform::Linux
+		} else {
+			Platform::Other
+		}
+	}
+
+	/// Error for a collector that has no backend for this platform.
+	pub fn unsupported(self, what: &str) -> anyhow::Error {
+		Unsupported(format!("{} not supported on {}", what, self)).into()
+	}
+}
+
+impl fmt::Display for Platform {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Platform::Windows => "Windows",
+			Platform::Linux => "Linux",
+			Platform::Other => "this platform",
+		})
+	}
+}
+
+/// Owned mirror of `wmi::Variant` that can be recorded and replayed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Variant {
+	Empty,
+	Null,
+	String(String),
+	I1(i8),
+	I2(i16),
+	I4(i32),
+	I8(i64),
+	R4(f32),
+	R8(f64),
+	Bool(bool),
+	UI1(u8),
+	UI2(u16),
+	UI4(u32),
+	UI8(u64),
+	Array(Vec<Variant>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Hive {
+	#[serde(rename = "HKLM")]
+	LocalMachine,
+	#[serde(rename = "HKCU")]
+	CurrentUser,
+}
+
+impl Hive {
+	pub fn prefix(self) -> &'static str {
+		match self {
+			Hive::LocalMachine => "HKLM",
+			Hive::CurrentUser => "HKCU",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegistryKey {
+	pub values: Row,
+	pub subkeys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommandOutput {
+	pub status: Option<i32>,
+	pub stdout: String,
+	pub stderr: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilesystemUsage {
+	pub total_bytes: u64,
+	/// Space available to unprivileged users, which excludes blocks reserved for root.
+	pub available_bytes: u64,
+}
+
+/// File contents as recorded; text stays readable in the fixture, anything else is kept as bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FileContents {
+	Text(String),
+	Binary(Vec<u8>),
+}
+
+impl FileContents {
+	pub fn new(bytes: Vec<u8>) -> Self {
+		match String::from_utf8(bytes) {
+			Ok(text) => FileContents::Text(text),
+			Err(e) => FileContents::Binary(e.into_bytes()),
+		}
+	}
+
+	pub fn into_bytes(self) -> Vec<u8> {
+		match self {
+			FileContents::Text(text) => text.into_bytes(),
+			FileContents::Binary(bytes) => bytes,
+		}
+	}
+}
+
+/// Raw results captured by a `RecordingSource`. Failed calls are kept as their error message
+/// so a replay fails the same way the live query did.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Fixture {
+	#[serde(default)]
+	pub platform: Platform,
+	/// namespace -> query -> rows
+	pub wmi: BTreeMap<String, BTreeMap<String, Result<Vec<Row>, String>>>,
+	/// `namespace object method(args)` -> output parameters
+	#[serde(default)]
+	pub wmi_methods: BTreeMap<String, Result<Row, String>>,
+	/// `HKLM\path` -> key
+	pub registry: BTreeMap<String, Result<RegistryKey, String>>,
+	/// command line -> output
+	pub commands: BTreeMap<String, Result<CommandOutput, String>>,
+	pub display_modes: Option<Result<Vec<DisplayMode>, String>>,
+	pub pending_updates: Option<Result<Vec<UpdateItem>, String>>,
+	pub interface_addresses: Option<Result<Vec<InterfaceAddress>, String>>,
+	/// path -> contents
+	#[serde(default)]
+	pub files: BTreeMap<String, Result<FileContents, String>>,
+	#[serde(default)]
+	pub dirs: BTreeMap<String, Result<Vec<String>, String>>,
+	#[serde(default)]
+	pub links: BTreeMap<String, Result<String, String>>,
+	#[serde(default)]
+	pub modified: BTreeMap<String, Result<DateTime<Utc>, String>>,
+	/// mount point -> usage
+	#[serde(default)]
+	pub filesystems: BTreeMap<String, Result<FilesystemUsage, String>>,
+}
+
+impl Fixture {
+	pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+		let file = std::fs::File::open(path)?;
+		Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+	}
+
+	pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+		let file = std::fs::File::create(path)?;
+		serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+		Ok(())
+	}
+
+	pub(crate) fn wmi_method_key(namespace: &str, object_path: &str, method: &str, args: &Row) -> String {
+		let args = args.iter().map(|(name, value)| format!("{}={:?}", name, value)).collect::<Vec<_>>();
+		format!("{} {} {}({})", namespace, object_path, method, args.join(", "))
+	}
+
+	pub(crate) fn registry_key(hive: Hive, path: &str) -> String {
+		format!(r"{}\{}", hive.prefix(), path)
+	}
+
+	pub(crate) fn command_key(program: &str, args: &[&str]) -> String {
+		std::iter::once(program).chain(args.iter().copied()).collect::<Vec<_>>().join(" ")
+	}
+}
@@ -0,0 +1,330 @@
+use super::{CommandOutput, DataSource, FilesystemUsage, Hive, Platform, RegistryKey, Row};
+use crate::monitor_info::DisplayMode;
+use crate::network_info::InterfaceAddress;
+use crate::security_info::UpdateItem;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::process::Command;
+
+#[cfg(target_os = "windows")]
+use {
+	super::Variant,
+	crate::{monitor_info, security_info},
+	std::cell::RefCell,
+	std::collections::HashMap,
+	std::os::windows::process::CommandExt,
+	winreg::RegKey,
+	winreg::enums::*,
+	wmi::WMIConnection,
+};
+
+/// Prevents console windows from flashing up for every external command.
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+#[cfg(target_os = "windows")]
+thread_local! {
+	// WMI connections are not `Send`; keep one per namespace on each collector thread
+	static CONNECTIONS: RefCell<HashMap<String, WMIConnection>> = RefCell::new(HashMap::new());
+}
+
+/// Reads from the machine the process is running on.
+#[derive(Debug, Clone, Default)]
+pub struct LiveSource {
+	root: Option<PathBuf>,
+}
+
+impl LiveSource {
+	pub fn new() -> Self {
+		LiveSource::default()
+	}
+
+	/// Reads files from a copy of a Linux filesystem, e.g. a captured `/proc` and `/sys` tree,
+	/// instead of `/`. Commands and Windows APIs are unavailable on a rooted source.
+	pub fn with_root(root: impl Into<PathBuf>) -> Self {
+		LiveSource {
+			root: Some(root.into()),
+		}
+	}
+
+	fn path(&self, path: &str) -> PathBuf {
+		match &self.root {
+			Some(root) => root.join(path.trim_start_matches('/')),
+			None => PathBuf::from(path),
+		}
+	}
+
+	#[cfg(target_os = "windows")]
+	fn is_local_windows(&self) -> bool {
+		self.root.is_none() && cfg!(target_os = "windows")
+	}
+}
+
+impl DataSource for LiveSource {
+	fn platform(&self) -> Platform {
+		match self.root {
+			Some(_) => Platform::Linux,
+			None => Platform::current(),
+		}
+	}
+
+	fn is_live(&self) -> bool {
+		self.root.is_none()
+	}
+
+	#[cfg(not(target_os = "windows"))]
+	fn wmi_query(&self, _namespace: &str, _query: &str) -> Result<Vec<Row>> {
+		Err(self.platform().unsupported("WMI"))
+	}
+
+	#[cfg(target_os = "windows")]
+	fn wmi_query(&self, namespace: &str, query: &str) -> Result<Vec<Row>> {
+		if !self.is_local_windows() {
+			return Err(self.platform().unsupported("WMI"));
+		}
+		with_connection(namespace, |com| {
+			let results: Vec<HashMap<String, wmi::Variant>> = com.raw_query(query)?;
+			Ok(results
+				.into_iter()
+				.map(|row| row.into_iter().map(|(k, v)| (k, convert_variant(v))).collect())
+				.collect())
+		})
+	}
+
+	#[cfg(not(target_os = "windows"))]
+	fn wmi_method(&self, _namespace: &str, _object_path: &str, _method: &str, _args: &Row) -> Result<Row> {
+		Err(self.platform().unsupported("WMI"))
+	}
+
+	#[cfg(target_os = "windows")]
+	fn wmi_method(&self, namespace: &str, object_path: &str, method: &str, args: &Row) -> Result<Row> {
+		if !self.is_local_windows() {
+			return Err(self.platform().unsupported("WMI"));
+		}
+		with_connection(namespace, |com| {
+			// The input signature comes from the class, not the instance the path points at
+			let class = object_path.split('.').next().unwrap_or(object_path);
+			let in_params = match com.get_object(class)?.get_method(method)? {
+				Some(signature) => {
+					let params = signature.spawn_instance()?;
+					for (name, value) in args {
+						params.put_property(name, to_wmi_variant(value))?;
+					}
+					Some(params)
+				}
+				None => None,
+			};
+			let Some(output) = com.exec_method(object_path, method, in_params.as_ref())? else {
+				return Ok(Row::new());
+			};
+			output
+				.list_properties()?
+				.into_iter()
+				.map(|name| {
+					let value = output.get_property(&name)?;
+					Ok((name, convert_variant(value)))
+				})
+				.collect()
+		})
+	}
+
+	#[cfg(not(target_os = "windows"))]
+	fn registry_key(&self, _hive: Hive, _path: &str) -> Result<RegistryKey> {
+		Err(self.platform().unsupported("The registry"))
+	}
+
+	#[cfg(target_os = "windows")]
+	fn registry_key(&self, hive: Hive, path: &str) -> Result<RegistryKey> {
+		if !self.is_local_windows() {
+			return Err(self.platform().unsupported("The registry"));
+		}
+		let root = RegKey::predef(match hive {
+			Hive::LocalMachine => HKEY_LOCAL_MACHINE,
+			Hive::CurrentUser => HKEY_CURRENT_USER,
+		});
+		let key = root.open_subkey(path)?;
+
+		let values = key
+			.enum_values()
+			.filter_map(|v| v.ok())
+			.map(|(name, value)| (name, convert_reg_value(&value)))
+			.collect();
+		let subkeys = key.enum_keys().filter_map(|k| k.ok()).collect();
+
+		Ok(RegistryKey { values, subkeys })
+	}
+
+	fn command(&self, program: &str, args: &[&str]) -> Result<CommandOutput> {
+		if self.root.is_some() {
+			return Err(anyhow::anyhow!("Cannot run {} against a captured filesystem", program));
+		}
+		let mut command = Command::new(program);
+		command.args(args);
+		#[cfg(target_os = "windows")]
+		command.creation_flags(CREATE_NO_WINDOW);
+		let output = command.output()?;
+
+		Ok(CommandOutput {
+			status: output.status.code(),
+			stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+			stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+		})
+	}
+
+	fn display_modes(&self) -> Result<Vec<DisplayMode>> {
+		#[cfg(target_os = "windows")]
+		if self.is_local_windows() {
+			return Ok(monitor_info::fetch_display_modes());
+		}
+		#[cfg(target_os = "linux")]
+		if self.root.is_none() {
+			return crate::gpu_info::query_drm_modes();
+		}
+		Err(self.platform().unsupported("Display mode enumeration"))
+	}
+
+	fn pending_updates(&self) -> Result<Vec<UpdateItem>> {
+		#[cfg(target_os = "windows")]
+		if self.is_local_windows() {
+			return Ok(security_info::query_pending_updates()?);
+		}
+		Err(self.platform().unsupported("Windows Update"))
+	}
+
+	fn interface_addresses(&self) -> Result<Vec<InterfaceAddress>> {
+		#[cfg(unix)]
+		if self.root.is_none() {
+			return crate::network_info::query_interface_addresses();
+		}
+		if self.root.is_some() {
+			return Err(anyhow::anyhow!("Cannot list interface addresses of a captured filesystem"));
+		}
+		Err(self.platform().unsupported("Interface address enumeration"))
+	}
+
+	fn read_file(&self, path: &str) -> Result<Vec<u8>> {
+		Ok(std::fs::read(self.path(path))?)
+	}
+
+	fn read_dir(&self, path: &str) -> Result<Vec<String>> {
+		let mut names = std::fs::read_dir(self.path(path))?
+			.filter_map(|entry| entry.ok())
+			.map(|entry| entry.file_name().to_string_lossy().into_owned())
+			.collect::<Vec<_>>();
+		names.sort();
+		Ok(names)
+	}
+
+	fn read_link(&self, path: &str) -> Result<String> {
+		Ok(std::fs::read_link(self.path(path))?.to_string_lossy().into_owned())
+	}
+
+	fn modified(&self, path: &str) -> Result<DateTime<Utc>> {
+		Ok(std::fs::metadata(self.path(path))?.modified()?.into())
+	}
+
+	#[cfg(unix)]
+	fn filesystem_usage(&self, path: &str) -> Result<FilesystemUsage> {
+		if self.root.is_some() {
+			return Err(anyhow::anyhow!("Cannot stat {} on a captured filesystem", path));
+		}
+		let c_path = std::ffi::CString::new(path)?;
+		// SAFETY: statvfs only writes into the zeroed struct we pass it
+		let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+		if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+			return Err(std::io::Error::last_os_error().into());
+		}
+		let fragment = stat.f_frsize as u64;
+		Ok(FilesystemUsage {
+			total_bytes: stat.f_blocks as u64 * fragment,
+			available_bytes: stat.f_bavail as u64 * fragment,
+		})
+	}
+
+	#[cfg(not(unix))]
+	fn filesystem_usage(&self, _path: &str) -> Result<FilesystemUsage> {
+		Err(self.platform().unsupported("statvfs"))
+	}
+}
+
+#[cfg(target_os = "windows")]
+fn with_connection<T>(namespace: &str, f: impl FnOnce(&WMIConnection) -> Result<T>) -> Result<T> {
+	CONNECTIONS.with(|connections| {
+		let mut connections = connections.borrow_mut();
+		let com = match connections.get(namespace) {
+			Some(com) => com,
+			None => {
+				let com = WMIConnection::with_namespace_path(namespace)?;
+				connections.entry(namespace.to_string()).or_insert(com)
+			}
+		};
+		f(com)
+	})
+}
+
+#[cfg(target_os = "windows")]
+fn convert_variant(variant: wmi::Variant) -> Variant {
+	match variant {
+		wmi::Variant::Empty => Variant::Empty,
+		wmi::Variant::Null => Variant::Null,
+		wmi::Variant::String(s) => Variant::String(s),
+		wmi::Variant::I1(v) => Variant::I1(v),
+		wmi::Variant::I2(v) => Variant::I2(v),
+		wmi::Variant::I4(v) => Variant::I4(v),
+		wmi::Variant::I8(v) => Variant::I8(v),
+		wmi::Variant::R4(v) => Variant::R4(v),
+		wmi::Variant::R8(v) => Variant::R8(v),
+		wmi::Variant::Bool(v) => Variant::Bool(v),
+		wmi::Variant::UI1(v) => Variant::UI1(v),
+		wmi::Variant::UI2(v) => Variant::UI2(v),
+		wmi::Variant::UI4(v) => Variant::UI4(v),
+		wmi::Variant::UI8(v) => Variant::UI8(v),
+		wmi::Variant::Array(arr) => Variant::Array(arr.into_iter().map(convert_variant).collect()),
+		// COM interface pointers cannot be recorded; none of the collectors read them
+		wmi::Variant::Unknown(_) | wmi::Variant::Object(_) => Variant::Null,
+	}
+}
+
+#[cfg(target_os = "windows")]
+fn to_wmi_variant(variant: &Variant) -> wmi::Variant {
+	match variant {
+		Variant::Empty => wmi::Variant::Empty,
+		Variant::Null => wmi::Variant::Null,
+		Variant::String(s) => wmi::Variant::String(s.clone()),
+		Variant::I1(v) => wmi::Variant::I1(*v),
+		Variant::I2(v) => wmi::Variant::I2(*v),
+		Variant::I4(v) => wmi::Variant::I4(*v),
+		Variant::I8(v) => wmi::Variant::I8(*v),
+		Variant::R4(v) => wmi::Variant::R4(*v),
+		Variant::R8(v) => wmi::Variant::R8(*v),
+		Variant::Bool(v) => wmi::Variant::Bool(*v),
+		Variant::UI1(v) => wmi::Variant::UI1(*v),
+		Variant::UI2(v) => wmi::Variant::UI2(*v),
+		Variant::UI4(v) => wmi::Variant::UI4(*v),
+		Variant::UI8(v) => wmi::Variant::UI8(*v),
+		Variant::Array(arr) => wmi::Variant::Array(arr.iter().map(to_wmi_variant).collect()),
+	}
+}
+
+#[cfg(target_os = "windows")]
+fn convert_reg_value(value: &winreg::RegValue) -> Variant {
+	use winreg::types::FromRegValue;
+
+	match value.vtype {
+		REG_SZ | REG_EXPAND_SZ => String::from_reg_value(value)
+			.map(Variant::String)
+			.unwrap_or(Variant::Null),
+		REG_MULTI_SZ => Vec::<String>::from_reg_value(value)
+			.map(|items| Variant::Array(items.into_iter().map(Variant::String).collect()))
+			.unwrap_or(Variant::Null),
+		REG_DWORD => u32::from_reg_value(value)
+			.map(Variant::UI4)
+			.unwrap_or(Variant::Null),
+		REG_QWORD => u64::from_reg_value(value)
+			.map(Variant::UI8)
+			.unwrap_or(Variant::Null),
+		REG_BINARY => Variant::Array(value.bytes.iter().copied().map(Variant::UI1).collect()),
+		_ => Variant::Null,
+	}
+}
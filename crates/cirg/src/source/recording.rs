@@ -0,0 +1,134 @@
+use super::{CommandOutput, DataSource, FileContents, FilesystemUsage, Fixture, Hive, Platform, RegistryKey, Row};
+use crate::monitor_info::DisplayMode;
+use crate::network_info::InterfaceAddress;
+use crate::security_info::UpdateItem;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+/// Passes every call through to another source and keeps the raw results in a `Fixture`.
+pub struct RecordingSource {
+	inner: Arc<dyn DataSource>,
+	fixture: Mutex<Fixture>,
+}
+
+impl RecordingSource {
+	pub fn new(inner: Arc<dyn DataSource>) -> Self {
+		let fixture = Fixture {
+			platform: inner.platform(),
+			..Fixture::default()
+		};
+		RecordingSource {
+			inner,
+			fixture: Mutex::new(fixture),
+		}
+	}
+
+	/// Snapshot of everything recorded so far.
+	pub fn fixture(&self) -> Fixture {
+		self.fixture.lock().unwrap_or_else(|e| e.into_inner()).clone()
+	}
+
+	fn record<T: Clone>(&self, result: Result<T>, store: impl FnOnce(&mut Fixture, Result<T, String>)) -> Result<T> {
+		let recorded = result.as_ref().map(T::clone).map_err(|e| format!("{:#}", e));
+		store(&mut self.fixture.lock().unwrap_or_else(|e| e.into_inner()), recorded);
+		result
+	}
+}
+
+impl DataSource for RecordingSource {
+	fn platform(&self) -> Platform {
+		self.inner.platform()
+	}
+
+	fn is_live(&self) -> bool {
+		self.inner.is_live()
+	}
+
+	fn wmi_query(&self, namespace: &str, query: &str) -> Result<Vec<Row>> {
+		self.record(self.inner.wmi_query(namespace, query), |fixture, recorded| {
+			fixture
+				.wmi
+				.entry(namespace.to_string())
+				.or_default()
+				.insert(query.to_string(), recorded);
+		})
+	}
+
+	fn wmi_method(&self, namespace: &str, object_path: &str, method: &str, args: &Row) -> Result<Row> {
+		self.record(self.inner.wmi_method(namespace, object_path, method, args), |fixture, recorded| {
+			fixture
+				.wmi_methods
+				.insert(Fixture::wmi_method_key(namespace, object_path, method, args), recorded);
+		})
+	}
+
+	fn registry_key(&self, hive: Hive, path: &str) -> Result<RegistryKey> {
+		self.record(self.inner.registry_key(hive, path), |fixture, recorded| {
+			fixture.registry.insert(Fixture::registry_key(hive, path), recorded);
+		})
+	}
+
+	fn command(&self, program: &str, args: &[&str]) -> Result<CommandOutput> {
+		self.record(self.inner.command(program, args), |fixture, recorded| {
+			fixture.commands.insert(Fixture::command_key(program, args), recorded);
+		})
+	}
+
+	fn display_modes(&self) -> Result<Vec<DisplayMode>> {
+		self.record(self.inner.display_modes(), |fixture, recorded| {
+			fixture.display_modes = Some(recorded);
+		})
+	}
+
+	fn pending_updates(&self) -> Result<Vec<UpdateItem>> {
+		self.record(self.inner.pending_updates(), |fixture, recorded| {
+			fixture.pending_updates = Some(recorded);
+		})
+	}
+
+	fn interface_addresses(&self) -> Result<Vec<InterfaceAddress>> {
+		self.record(self.inner.interface_addresses(), |fixture, recorded| {
+			fixture.interface_addresses = Some(recorded);
+		})
+	}
+
+	fn read_file(&self, path: &str) -> Result<Vec<u8>> {
+		self.record(self.inner.read_file(path), |fixture, recorded| {
+			fixture
+				.files
+				.insert(path.to_string(), recorded.map(FileContents::new));
+		})
+	}
+
+	fn read_dir(&self, path: &str) -> Result<Vec<String>> {
+		self.record(self.inner.read_dir(path), |fixture, recorded| {
+			fixture.dirs.insert(path.to_string(), recorded);
+		})
+	}
+
+	fn read_link(&self, path: &str) -> Result<String> {
+		self.record(self.inner.read_link(path), |fixture, recorded| {
+			fixture.links.insert(path.to_string(), recorded);
+		})
+	}
+
+	fn modified(&self, path: &str) -> Result<DateTime<Utc>> {
+		self.record(self.inner.modified(path), |fixture, recorded| {
+			fixture.modified.insert(path.to_string(), recorded);
+		})
+	}
+
+	fn filesystem_usage(&self, path: &str) -> Result<FilesystemUsage> {
+		self.record(self.inner.filesystem_usage(path), |fixture, recorded| {
+			fixture.filesystems.insert(path.to_string(), recorded);
+		})
+	}
+}
+
+impl std::fmt::Debug for RecordingSource {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("RecordingSource").finish_non_exhaustive()
+	}
+}
+
@@ -0,0 +1,91 @@
+use super::{CommandOutput, DataSource, FilesystemUsage, Fixture, Hive, Platform, RegistryKey, Row};
+use crate::monitor_info::DisplayMode;
+use crate::network_info::InterfaceAddress;
+use crate::security_info::UpdateItem;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+/// Answers every call from a previously recorded `Fixture`.
+#[derive(Debug, Clone, Default)]
+pub struct ReplaySource {
+	fixture: Fixture,
+}
+
+impl ReplaySource {
+	pub fn new(fixture: Fixture) -> Self {
+		ReplaySource { fixture }
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		Ok(Self::new(Fixture::load(path)?))
+	}
+}
+
+fn replay<T: Clone>(recorded: Option<&Result<T, String>>, what: impl FnOnce() -> String) -> Result<T> {
+	match recorded {
+		Some(Ok(value)) => Ok(value.clone()),
+		Some(Err(e)) => Err(anyhow!("{}", e)),
+		None => Err(anyhow!("No recorded result for {}", what())),
+	}
+}
+
+impl DataSource for ReplaySource {
+	fn platform(&self) -> Platform {
+		self.fixture.platform
+	}
+
+	fn wmi_query(&self, namespace: &str, query: &str) -> Result<Vec<Row>> {
+		replay(
+			self.fixture.wmi.get(namespace).and_then(|queries| queries.get(query)),
+			|| format!("WMI query '{}' in {}", query, namespace),
+		)
+	}
+
+	fn wmi_method(&self, namespace: &str, object_path: &str, method: &str, args: &Row) -> Result<Row> {
+		let key = Fixture::wmi_method_key(namespace, object_path, method, args);
+		replay(self.fixture.wmi_methods.get(&key), || format!("WMI method call {}", key))
+	}
+
+	fn registry_key(&self, hive: Hive, path: &str) -> Result<RegistryKey> {
+		let key = Fixture::registry_key(hive, path);
+		replay(self.fixture.registry.get(&key), || format!("registry key {}", key))
+	}
+
+	fn command(&self, program: &str, args: &[&str]) -> Result<CommandOutput> {
+		let key = Fixture::command_key(program, args);
+		replay(self.fixture.commands.get(&key), || format!("command '{}'", key))
+	}
+
+	fn display_modes(&self) -> Result<Vec<DisplayMode>> {
+		replay(self.fixture.display_modes.as_ref(), || "display modes".to_string())
+	}
+
+	fn pending_updates(&self) -> Result<Vec<UpdateItem>> {
+		replay(self.fixture.pending_updates.as_ref(), || "pending updates".to_string())
+	}
+
+	fn interface_addresses(&self) -> Result<Vec<InterfaceAddress>> {
+		replay(self.fixture.interface_addresses.as_ref(), || "interface addresses".to_string())
+	}
+
+	fn read_file(&self, path: &str) -> Result<Vec<u8>> {
+		replay(self.fixture.files.get(path), || format!("file {}", path)).map(|c| c.into_bytes())
+	}
+
+	fn read_dir(&self, path: &str) -> Result<Vec<String>> {
+		replay(self.fixture.dirs.get(path), || format!("directory {}", path))
+	}
+
+	fn read_link(&self, path: &str) -> Result<String> {
+		replay(self.fixture.links.get(path), || format!("link {}", path))
+	}
+
+	fn modified(&self, path: &str) -> Result<DateTime<Utc>> {
+		replay(self.fixture.modified.get(path), || format!("modification time of {}", path))
+	}
+
+	fn filesystem_usage(&self, path: &str) -> Result<FilesystemUsage> {
+		replay(self.fixture.filesystems.get(path), || format!("filesystem usage of {}", path))
+	}
+}
@@ -0,0 +1,205 @@
+//! Read-only access to the rows of a table in an SQLite database file, enough to read package
+//! databases such as `rpmdb.sqlite` through a `DataSource` without linking SQLite.
+//!
+//! Only table b-trees are walked; indexes, the write-ahead log and free pages are ignored, so rows
+//! that have not been checkpointed yet are not seen.
+
+use anyhow::{Context, Result, anyhow, bail};
+
+const MAGIC: &[u8] = b"SQLite format 3\0";
+const LEAF_TABLE: u8 = 0x0d;
+const INTERIOR_TABLE: u8 = 0x05;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+	Null,
+	Integer(i64),
+	Real(f64),
+	Text(String),
+	Blob(Vec<u8>),
+}
+
+pub(crate) struct Database<'a> {
+	data: &'a [u8],
+	page_size: usize,
+	usable_size: usize,
+}
+
+impl<'a> Database<'a> {
+	pub(crate) fn parse(data: &'a [u8]) -> Result<Self> {
+		if data.len() < 100 || !data.starts_with(MAGIC) {
+			bail!("Not an SQLite 3 database");
+		}
+		let page_size = match u16::from_be_bytes([data[16], data[17]]) {
+			1 => 65536,
+			size => size as usize,
+		};
+		if page_size < 512 || !page_size.is_power_of_two() {
+			bail!("Invalid SQLite page size {}", page_size);
+		}
+		Ok(Database {
+			data,
+			page_size,
+			usable_size: page_size - data[20] as usize,
+		})
+	}
+
+	/// Every row of a table as (rowid, columns). An `INTEGER PRIMARY KEY` column reads as Null;
+	/// its value is the rowid.
+	pub(crate) fn rows(&self, table: &str) -> Result<Vec<(i64, Vec<Value>)>> {
+		// sqlite_schema: type, name, tbl_name, rootpage, sql
+		let root = self
+			.walk(1)?
+			.into_iter()
+			.find_map(|(_, columns)| match columns.as_slice() {
+				[Value::Text(kind), Value::Text(name), _, Value::Integer(root), ..] if kind == "table" && name == table => {
+					Some(*root as u32)
+				}
+				_ => None,
+			})
+			.ok_or_else(|| anyhow!("No table named {}", table))?;
+		self.walk(root)
+	}
+
+	fn page(&self, number: u32) -> Result<&'a [u8]> {
+		let start = (number as usize)
+			.checked_sub(1)
+			.map(|index| index * self.page_size)
+			.ok_or_else(|| anyhow!("Invalid page number 0"))?;
+		self.data
+			.get(start..start + self.page_size)
+			.ok_or_else(|| anyhow!("Page {} is past the end of the file", number))
+	}
+
+	fn walk(&self, root: u32) -> Result<Vec<(i64, Vec<Value>)>> {
+		let mut rows = Vec::new();
+		let mut pending = vec![root];
+		let page_count = self.data.len() / self.page_size;
+		let mut visited = 0;
+
+		while let Some(number) = pending.pop() {
+			visited += 1;
+			if visited > page_count {
+				bail!("Table b-tree at page {} has a cycle", root);
+			}
+			let page = self.page(number)?;
+			// Page 1 starts with the 100-byte file header
+			let header = if number == 1 { 100 } else { 0 };
+			let kind = page[header];
+			let cells = u16::from_be_bytes([page[header + 3], page[header + 4]]) as usize;
+			let pointers = header + if kind == INTERIOR_TABLE { 12 } else { 8 };
+
+			let mut children = Vec::new();
+			for index in 0..cells {
+				let pointer = page
+					.get(pointers + index * 2..pointers + index * 2 + 2)
+					.context("Truncated cell pointer array")?;
+				let offset = u16::from_be_bytes([pointer[0], pointer[1]]) as usize;
+				match kind {
+					LEAF_TABLE => rows.push(self.leaf_cell(page, offset)?),
+					INTERIOR_TABLE => children.push(read_u32(page, offset)?),
+					kind => bail!("Page {} is not a table b-tree page (type {:#04x})", number, kind),
+				}
+			}
+			if kind == INTERIOR_TABLE {
+				children.push(read_u32(page, header + 8)?);
+			}
+			// Popped in reverse, so rows come out in rowid order
+			pending.extend(children.into_iter().rev());
+		}
+		Ok(rows)
+	}
+
+	fn leaf_cell(&self, page: &[u8], offset: usize) -> Result<(i64, Vec<Value>)> {
+		let (payload_size, used) = read_varint(page, offset)?;
+		let (rowid, used_rowid) = read_varint(page, offset + used)?;
+		let start = offset + used + used_rowid;
+		let payload_size = payload_size as usize;
+
+		let local = self.local_payload(payload_size);
+		let mut payload = page.get(start..start + local).context("Truncated cell")?.to_vec();
+		let mut overflow = if local < payload_size { read_u32(page, start + local)? } else { 0 };
+		while payload.len() < payload_size {
+			if overflow == 0 {
+				bail!("Overflow chain ends before the end of row {}", rowid as i64);
+			}
+			let page = self.page(overflow)?;
+			let take = (payload_size - payload.len()).min(self.usable_size - 4);
+			payload.extend_from_slice(&page[4..4 + take]);
+			overflow = read_u32(page, 0)?;
+		}
+		Ok((rowid as i64, parse_record(&payload)?))
+	}
+
+	/// Bytes of a table leaf payload stored on the page itself; the rest goes to overflow pages.
+	fn local_payload(&self, payload_size: usize) -> usize {
+		let max_local = self.usable_size - 35;
+		if payload_size <= max_local {
+			return payload_size;
+		}
+		let min_local = (self.usable_size - 12) * 32 / 255 - 23;
+		let local = min_local + (payload_size - min_local) % (self.usable_size - 4);
+		if local <= max_local { local } else { min_local }
+	}
+}
+
+fn parse_record(payload: &[u8]) -> Result<Vec<Value>> {
+	let (header_size, mut offset) = read_varint(payload, 0)?;
+	let mut types = Vec::new();
+	while offset < header_size as usize {
+		let (serial_type, used) = read_varint(payload, offset)?;
+		types.push(serial_type);
+		offset += used;
+	}
+
+	let mut body = header_size as usize;
+	let mut field = |size: usize| -> Result<&[u8]> {
+		let bytes = payload.get(body..body + size).context("Truncated record")?;
+		body += size;
+		Ok(bytes)
+	};
+	let integer = |bytes: &[u8]| {
+		// Big-endian two's complement of 1 to 8 bytes
+		let sign = if bytes[0] & 0x80 != 0 { -1i64 } else { 0 };
+		bytes.iter().fold(sign, |value, &byte| (value << 8) | byte as i64)
+	};
+
+	types
+		.into_iter()
+		.map(|serial_type| {
+			Ok(match serial_type {
+				0 => Value::Null,
+				1..=4 => Value::Integer(integer(field(serial_type as usize)?)),
+				5 => Value::Integer(integer(field(6)?)),
+				6 => Value::Integer(integer(field(8)?)),
+				7 => Value::Real(f64::from_be_bytes(field(8)?.try_into()?)),
+				8 => Value::Integer(0),
+				9 => Value::Integer(1),
+				n if n >= 12 && n % 2 == 0 => Value::Blob(field((n as usize - 12) / 2)?.to_vec()),
+				n if n >= 13 => Value::Text(String::from_utf8_lossy(field((n as usize - 13) / 2)?).to_string()),
+				n => bail!("Reserved serial type {}", n),
+			})
+		})
+		.collect()
+}
+
+/// SQLite's big-endian varint: up to eight bytes of seven bits, then a ninth of eight.
+fn read_varint(data: &[u8], offset: usize) -> Result<(u64, usize)> {
+	let mut value = 0u64;
+	for index in 0..9 {
+		let byte = *data.get(offset + index).context("Truncated varint")?;
+		if index == 8 {
+			return Ok(((value << 8) | byte as u64, 9));
+		}
+		value = (value << 7) | (byte & 0x7f) as u64;
+		if byte & 0x80 == 0 {
+			return Ok((value, index + 1));
+		}
+	}
+	unreachable!()
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+	let bytes = data.get(offset..offset + 4).context("Truncated page")?;
+	Ok(u32::from_be_bytes(bytes.try_into()?))
+}
@@ -1,8 +1,23 @@
+use crate::linux::user_homes;
+use crate::service_info::{SYSTEM_UNIT_PATHS, USER_UNIT_PATHS, unit_command};
+use crate::source::{CIMV2, DataSource, Platform};
 use crate::{ComputerInfoExt, VariantExt};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use wmi::Variant;
+
+/// Autostart entries every desktop session runs, unless a user's own entry of the same name hides it.
+const XDG_AUTOSTART: &str = "/etc/xdg/autostart";
+
+/// Shell init files in a home directory, roughly in the order a login shell reads them.
+const SHELL_INIT_FILES: &[&str] = &[
+	".profile",
+	".bash_profile",
+	".bash_login",
+	".bashrc",
+	".zprofile",
+	".zshrc",
+	".config/fish/config.fish",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct StartupInfo {
@@ -18,10 +33,18 @@ pub struct StartupItem {
 }
 
 impl ComputerInfoExt for StartupInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
-		let results: Vec<HashMap<String, Variant>> =
-			com.raw_query("SELECT * FROM Win32_StartupCommand")?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("Startup item collection")),
+		}
+	}
+}
+
+impl StartupInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let results = source.wmi_query(CIMV2, "SELECT * FROM Win32_StartupCommand")?;
 
 		let items = results
 			.iter()
@@ -36,3 +59,199 @@ impl ComputerInfoExt for StartupInfo {
 		Ok(StartupInfo { items })
 	}
 }
+
+impl StartupInfo {
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let users = user_homes(source);
+
+		let mut items = autostart_items(source, &users);
+
+		// systemctl enable links units from /etc; vendor links in /usr/lib are part of the OS
+		items.extend(enabled_units(source, SYSTEM_UNIT_PATHS[0], SYSTEM_UNIT_PATHS, "root"));
+		items.extend(enabled_units(source, USER_UNIT_PATHS[0], USER_UNIT_PATHS, "All users"));
+		for (user, home) in &users {
+			let own_units = format!("{}/.config/systemd/user", home);
+			let search_paths: Vec<&str> = std::iter::once(own_units.as_str()).chain(USER_UNIT_PATHS.iter().copied()).collect();
+			items.extend(enabled_units(source, &own_units, &search_paths, user));
+		}
+
+		items.extend(rc_local(source));
+		for (user, home) in &users {
+			items.extend(shell_init_commands(source, user, home));
+		}
+
+		Ok(StartupInfo { items })
+	}
+}
+
+/// A `.desktop` file name and its entry, None when it is disabled or hidden.
+type AutostartEntry = (String, Option<StartupItem>);
+
+/// Enabled XDG autostart entries, resolved by file name with each user's own directory first. A
+/// system entry some users replace or hide is reported for everyone else.
+fn autostart_items(source: &dyn DataSource, users: &[(String, String)]) -> Vec<StartupItem> {
+	let own: Vec<(&str, Vec<AutostartEntry>)> = users
+		.iter()
+		.map(|(user, home)| (user.as_str(), autostart_entries(source, &format!("{}/.config/autostart", home), user)))
+		.collect();
+
+	let mut items = Vec::new();
+	for (file, item) in autostart_entries(source, XDG_AUTOSTART, "All users") {
+		let Some(item) = item else {
+			continue;
+		};
+		let overriding: Vec<&str> = own
+			.iter()
+			.filter(|(_, entries)| entries.iter().any(|(name, _)| *name == file))
+			.map(|(user, _)| *user)
+			.collect();
+		if overriding.is_empty() {
+			items.push(item);
+		} else if overriding.len() < own.len() {
+			items.push(StartupItem {
+				user: format!("All users except {}", overriding.join(", ")),
+				..item
+			});
+		}
+	}
+	for (_, entries) in own {
+		items.extend(entries.into_iter().filter_map(|(_, item)| item));
+	}
+	items
+}
+
+/// Every `.desktop` file in an XDG autostart directory.
+fn autostart_entries(source: &dyn DataSource, directory: &str, user: &str) -> Vec<AutostartEntry> {
+	let mut entries = Vec::new();
+	for file in source.read_dir(directory).unwrap_or_default() {
+		if !file.ends_with(".desktop") {
+			continue;
+		}
+		let location = format!("{}/{}", directory, file);
+		let Ok(text) = source.read_to_string(&location) else {
+			continue;
+		};
+		let entry = desktop_entry(&text);
+		let value = |key: &str| entry.iter().find(|(name, _)| *name == key).map(|(_, value)| *value);
+
+		// Hidden=true is how a user deletes an entry they cannot remove from /etc
+		let hidden = value("Hidden") == Some("true") || value("X-GNOME-Autostart-enabled") == Some("false");
+		let item = value("Exec").filter(|_| !hidden).map(|exec| StartupItem {
+			name: value("Name").unwrap_or(file.trim_end_matches(".desktop")).to_string(),
+			command: without_field_codes(exec),
+			location,
+			user: user.to_string(),
+		});
+		entries.push((file, item));
+	}
+	entries
+}
+
+/// Untranslated keys of the `[Desktop Entry]` group.
+fn desktop_entry(text: &str) -> Vec<(&str, &str)> {
+	let mut in_entry = false;
+	let mut keys = Vec::new();
+	for line in text.lines().map(str::trim) {
+		if line.starts_with('[') {
+			in_entry = line == "[Desktop Entry]";
+		} else if in_entry && !line.starts_with('#') {
+			// Name[de]=... is a translation of Name=
+			if let Some((key, value)) = line.split_once('=').filter(|(key, _)| !key.contains('[')) {
+				keys.push((key.trim(), value.trim()));
+			}
+		}
+	}
+	keys
+}
+
+/// Drops the `%f`, `%U` and similar placeholders a launcher would fill in from an `Exec=` line.
+fn without_field_codes(exec: &str) -> String {
+	exec.split_whitespace()
+		.filter(|word| !(word.len() == 2 && word.starts_with('%') && word != &"%%"))
+		.collect::<Vec<_>>()
+		.join(" ")
+		.replace("%%", "%")
+}
+
+/// Units enabled by links in the `.wants` and `.requires` directories under `directory`.
+fn enabled_units(source: &dyn DataSource, directory: &str, search_paths: &[&str], user: &str) -> Vec<StartupItem> {
+	let mut items = Vec::new();
+	for entry in source.read_dir(directory).unwrap_or_default() {
+		if !entry.ends_with(".wants") && !entry.ends_with(".requires") {
+			continue;
+		}
+		let links = format!("{}/{}", directory, entry);
+		for unit in source.read_dir(&links).unwrap_or_default() {
+			items.push(StartupItem {
+				command: unit_command(source, search_paths, &unit).unwrap_or_default(),
+				location: format!("{}/{}", links, unit),
+				name: unit,
+				user: user.to_string(),
+			});
+		}
+	}
+	items
+}
+
+/// Commands in `/etc/rc.local`, which systemd's rc-local generator runs at boot.
+fn rc_local(source: &dyn DataSource) -> Vec<StartupItem> {
+	const RC_LOCAL: &str = "/etc/rc.local";
+	let text = source.read_to_string(RC_LOCAL).unwrap_or_default();
+	text.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with('#') && *line != "exit 0")
+		.map(|line| StartupItem {
+			name: program_name(line.split_whitespace().next().unwrap_or_default()),
+			command: line.to_string(),
+			location: RC_LOCAL.to_string(),
+			user: "root".to_string(),
+		})
+		.collect()
+}
+
+/// Lines of a user's shell init files that start a program in the background or replace the shell.
+fn shell_init_commands(source: &dyn DataSource, user: &str, home: &str) -> Vec<StartupItem> {
+	let mut items = Vec::new();
+	for file in SHELL_INIT_FILES {
+		let path = format!("{}/{}", home, file);
+		let Ok(text) = source.read_to_string(&path) else {
+			continue;
+		};
+		for (number, line) in text.lines().enumerate() {
+			let line = line.trim();
+			if line.starts_with('#') {
+				continue;
+			}
+			if let Some(program) = launched_program(line) {
+				items.push(StartupItem {
+					name: program_name(program),
+					command: line.to_string(),
+					location: format!("{}:{}", path, number + 1),
+					user: user.to_string(),
+				});
+			}
+		}
+	}
+	items
+}
+
+/// The program a shell line launches, if it backgrounds it (`cmd &`) or hands it the session.
+fn launched_program(line: &str) -> Option<&str> {
+	let background = line.ends_with('&') && !line.ends_with("&&");
+	// Leading VAR=value assignments only set the command's environment
+	let mut words = line
+		.trim_start_matches('(')
+		.split_whitespace()
+		.skip_while(|word| word.split_once('=').is_some_and(|(name, _)| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')));
+	match words.next()? {
+		"nohup" | "setsid" | "exec" => words.next(),
+		first if background => Some(first),
+		_ => None,
+	}
+}
+
+/// `~/bin/tunnel.sh` -> `tunnel.sh`.
+fn program_name(program: &str) -> String {
+	let program = program.trim_matches(['"', '\'', '(', ')', '&']);
+	program.rsplit('/').next().unwrap_or(program).to_string()
+}
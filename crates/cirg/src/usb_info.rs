@@ -1,8 +1,8 @@
+use crate::linux::{lookup_ids, read_optional, read_usb_ids};
+use crate::source::{CIMV2, DataSource, Platform};
 use crate::{ComputerInfoExt, VariantExt};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use wmi::Variant;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct UsbInfo {
@@ -15,12 +15,38 @@ pub struct UsbDevice {
 	pub device_id: String,
 	pub manufacturer: String,
 	pub status: String,
+	/// Vendor and product id in hex, e.g. `046D` and `C52B`.
+	pub vendor_id: String,
+	pub product_id: String,
+	/// Bus number and port chain, e.g. ports `2.1` for a device on port 1 of the hub on port 2.
+	/// Empty for root hubs. Linux only.
+	pub bus: u32,
+	pub ports: String,
+	/// `device_id` of the hub the device is plugged into, empty for root hubs.
+	pub parent: String,
+	/// Negotiated link speed, e.g. `High Speed (480 Mbps)`.
+	pub speed: String,
+	/// USB class, or the interface classes of a composite device, e.g. `Audio, HID`.
+	pub device_class: String,
+	pub serial_number: String,
+	/// Most current the device asked the bus for.
+	pub max_power_ma: u32,
 }
 
 impl ComputerInfoExt for UsbInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
-		let results: Vec<HashMap<String, Variant>> = com.raw_query(
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("USB device collection")),
+		}
+	}
+}
+
+impl UsbInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let results = source.wmi_query(
+			CIMV2,
 			"SELECT Name, PNPDeviceID, Manufacturer, Status FROM Win32_PnPEntity WHERE PNPDeviceID LIKE 'USB%'",
 		)?;
 
@@ -32,14 +58,165 @@ impl ComputerInfoExt for UsbInfo {
 					&& !name.contains("Generic Hub")
 					&& !name.contains("USB Composite Device")
 			})
-			.map(|data| UsbDevice {
-				name: data.get_string("Name").unwrap_or_default(),
-				device_id: data.get_string("PNPDeviceID").unwrap_or_default(),
-				manufacturer: data.get_string("Manufacturer").unwrap_or_default(),
-				status: data.get_string("Status").unwrap_or_default(),
+			.map(|data| {
+				let device_id = data.get_string("PNPDeviceID").unwrap_or_default();
+				let (vendor_id, product_id) = parse_pnp_usb_ids(&device_id);
+				UsbDevice {
+					name: data.get_string("Name").unwrap_or_default(),
+					manufacturer: data.get_string("Manufacturer").unwrap_or_default(),
+					status: data.get_string("Status").unwrap_or_default(),
+					device_id,
+					vendor_id,
+					product_id,
+					..UsbDevice::default()
+				}
 			})
 			.collect();
 
 		Ok(UsbInfo { devices })
 	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let Ok(entries) = source.read_dir("/sys/bus/usb/devices") else {
+			return Ok(UsbInfo::default());
+		};
+		let usb_ids = read_usb_ids(source);
+
+		// Interfaces are listed next to devices as `<device>:<config>.<interface>`
+		let mut devices: Vec<UsbDevice> = entries
+			.iter()
+			.filter(|entry| !entry.contains(':'))
+			.filter_map(|name| Self::read_usb_device(source, name, &entries, usb_ids.as_deref()))
+			.collect();
+
+		// Tree order: every hub is followed by the devices plugged into it
+		devices.sort_by_cached_key(|device| {
+			let ports: Vec<u32> = device.ports.split('.').filter_map(|port| port.parse().ok()).collect();
+			(device.bus, ports)
+		});
+
+		Ok(UsbInfo { devices })
+	}
+
+	/// Describes `/sys/bus/usb/devices/<name>`, where `name` is `usb<bus>` for a root hub and
+	/// `<bus>-<port>[.<port>...]` for everything else.
+	fn read_usb_device(source: &dyn DataSource, name: &str, entries: &[String], usb_ids: Option<&str>) -> Option<UsbDevice> {
+		let dir = format!("/sys/bus/usb/devices/{}", name);
+		let read = |file: &str| read_optional(source, &format!("{}/{}", dir, file));
+
+		let vendor_id = read("idVendor")?.to_lowercase();
+		let product_id = read("idProduct").unwrap_or_default().to_lowercase();
+		let (bus, ports, parent) = match name.strip_prefix("usb") {
+			Some(bus) => (bus.parse().ok()?, String::new(), String::new()),
+			None => {
+				let (bus, ports) = name.split_once('-')?;
+				let parent = match ports.rsplit_once('.') {
+					Some((hub_ports, _)) => format!("{}-{}", bus, hub_ports),
+					None => format!("usb{}", bus),
+				};
+				(bus.parse().ok()?, ports.to_string(), parent)
+			}
+		};
+
+		let (vendor_name, product_name) = usb_ids.map(|ids| lookup_ids(ids, &vendor_id, &product_id)).unwrap_or_default();
+		let name_from_ids = product_name.map(|product| match &vendor_name {
+			Some(vendor) => format!("{} {}", vendor, product),
+			None => product,
+		});
+
+		let device_class = match read("bDeviceClass").and_then(|class| u8::from_str_radix(&class, 16).ok()) {
+			// Composite devices declare their classes per interface
+			Some(0x00 | 0xEF) | None => {
+				let prefix = format!("{}:", name);
+				let mut classes: Vec<&str> = Vec::new();
+				for interface in entries.iter().filter(|entry| entry.starts_with(&prefix)) {
+					let class = read_optional(source, &format!("/sys/bus/usb/devices/{}/bInterfaceClass", interface))
+						.and_then(|class| u8::from_str_radix(&class, 16).ok())
+						.map(usb_class_name);
+					if let Some(class) = class.filter(|class| !classes.contains(class)) {
+						classes.push(class);
+					}
+				}
+				classes.join(", ")
+			}
+			Some(class) => usb_class_name(class).to_string(),
+		};
+
+		Some(UsbDevice {
+			name: read("product")
+				.or(name_from_ids)
+				.unwrap_or_else(|| format!("USB device [{}:{}]", vendor_id.to_uppercase(), product_id.to_uppercase())),
+			device_id: name.to_string(),
+			manufacturer: read("manufacturer").or(vendor_name).unwrap_or_default(),
+			// Devices blocked by USBGuard or `authorized_default` stay enumerated but unconfigured
+			status: if read("authorized").as_deref() == Some("0") { "Unauthorized" } else { "OK" }.to_string(),
+			vendor_id: vendor_id.to_uppercase(),
+			product_id: product_id.to_uppercase(),
+			bus,
+			ports,
+			parent,
+			speed: read("speed").map(|speed| usb_speed_name(&speed)).unwrap_or_default(),
+			device_class,
+			serial_number: read("serial").unwrap_or_default(),
+			max_power_ma: read("bMaxPower")
+				.and_then(|power| power.trim_end_matches("mA").trim().parse().ok())
+				.unwrap_or(0),
+		})
+	}
+}
+
+/// Vendor and product id from a PnP id such as `USB\VID_046D&PID_C52B\5&1A2B3C4D&0&2`.
+fn parse_pnp_usb_ids(pnp_device_id: &str) -> (String, String) {
+	let id = |prefix: &str| {
+		pnp_device_id
+			.split(['\\', '&'])
+			.find_map(|part| part.strip_prefix(prefix))
+			.map(|id| id.to_uppercase())
+			.unwrap_or_default()
+	};
+	(id("VID_"), id("PID_"))
+}
+
+/// Speed in Mbps as sysfs reports it, with its USB marketing name.
+fn usb_speed_name(mbps: &str) -> String {
+	let name = match mbps {
+		"1.5" => "Low Speed",
+		"12" => "Full Speed",
+		"480" => "High Speed",
+		"5000" => "SuperSpeed",
+		"10000" | "20000" => "SuperSpeed+",
+		_ => return format!("{} Mbps", mbps),
+	};
+	match mbps.parse::<u32>() {
+		Ok(mbps) if mbps >= 1000 => format!("{} ({} Gbps)", name, mbps / 1000),
+		_ => format!("{} ({} Mbps)", name, mbps),
+	}
+}
+
+/// Base class codes from usb.org's defined class list.
+fn usb_class_name(class: u8) -> &'static str {
+	match class {
+		0x01 => "Audio",
+		0x02 => "Communications",
+		0x03 => "HID",
+		0x05 => "Physical",
+		0x06 => "Image",
+		0x07 => "Printer",
+		0x08 => "Mass Storage",
+		0x09 => "Hub",
+		0x0A => "CDC Data",
+		0x0B => "Smart Card",
+		0x0D => "Content Security",
+		0x0E => "Video",
+		0x0F => "Personal Healthcare",
+		0x10 => "Audio/Video",
+		0x11 => "Billboard",
+		0x12 => "USB-C Bridge",
+		0xDC => "Diagnostic",
+		0xE0 => "Wireless Controller",
+		0xEF => "Miscellaneous",
+		0xFE => "Application Specific",
+		0xFF => "Vendor Specific",
+		_ => "Unknown",
+	}
 }
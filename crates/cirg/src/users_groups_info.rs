@@ -1,8 +1,25 @@
-use crate::{ComputerInfoExt, VariantExt};
-use anyhow::Result;
+use crate::linux::{gid_range, uid_range};
+use crate::source::{CIMV2, DataSource, Platform};
+use crate::sqlite::{self, Value};
+use crate::{ComputerInfoExt, VariantExt, report};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use wmi::Variant;
+
+const PASSWD: &str = "/etc/passwd";
+const SHADOW: &str = "/etc/shadow";
+const GROUP: &str = "/etc/group";
+const GSHADOW: &str = "/etc/gshadow";
+const SUDOERS: &str = "/etc/sudoers";
+/// Groups the distributions' default sudoers grant full rights to: Debian, Red Hat and older Ubuntu.
+const SUDO_GROUPS: &[&str] = &["sudo", "wheel", "admin"];
+/// util-linux's SQLite replacement for lastlog, used by newer distributions.
+const LASTLOG2_DB: &str = "/var/lib/lastlog/lastlog2.db";
+/// Login and logout records in the glibc `struct utmp` layout.
+const WTMP: &str = "/var/log/wtmp";
+const UTMP_RECORD_SIZE: usize = 384;
+const UTMP_USER_PROCESS: u16 = 7;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct UsersGroupsInfo {
@@ -15,6 +32,34 @@ pub struct LocalUser {
 	pub name: String,
 	pub disabled: bool,
 	pub description: String,
+	/// Numeric user ID on Linux.
+	#[serde(default)]
+	pub uid: Option<u32>,
+	#[serde(default)]
+	pub home: String,
+	#[serde(default)]
+	pub shell: String,
+	/// Outside the UID range `/etc/login.defs` gives human accounts, i.e. a service account or root.
+	#[serde(default)]
+	pub system_account: bool,
+	/// The shadow password starts with `!` or is `*`, so password logins are refused.
+	#[serde(default)]
+	pub locked: bool,
+	/// The password is past its maximum age or must be changed at the next login.
+	#[serde(default)]
+	pub password_expired: bool,
+	/// Day the password was last changed, as YYYY-MM-DD.
+	#[serde(default)]
+	pub password_last_set: String,
+	/// Day the account expires, as YYYY-MM-DD, if it has an expiry date.
+	#[serde(default)]
+	pub account_expires: String,
+	/// Granted commands through sudoers, directly or through a group.
+	#[serde(default)]
+	pub sudo: bool,
+	/// Most recent login in UTC, from lastlog and wtmp; empty if none is recorded.
+	#[serde(default)]
+	pub last_login: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -22,13 +67,31 @@ pub struct LocalGroup {
 	pub name: String,
 	pub description: String,
 	pub members: Vec<String>,
+	/// Numeric group ID on Linux.
+	#[serde(default)]
+	pub gid: Option<u32>,
+	/// Outside the GID range `/etc/login.defs` gives user groups.
+	#[serde(default)]
+	pub system_group: bool,
+	/// Members allowed to manage the group with `gpasswd`, from `/etc/gshadow`.
+	#[serde(default)]
+	pub administrators: Vec<String>,
 }
 
 impl ComputerInfoExt for UsersGroupsInfo {
-	fn fetch() -> Result<Self> {
-		let com = wmi::WMIConnection::new()?;
+	fn fetch_from(source: &dyn DataSource) -> Result<Self> {
+		match source.platform() {
+			Platform::Windows => Self::fetch_windows(source),
+			Platform::Linux => Self::fetch_linux(source),
+			platform => Err(platform.unsupported("User and group collection")),
+		}
+	}
+}
 
-		let user_results: Vec<HashMap<String, Variant>> = com.raw_query(
+impl UsersGroupsInfo {
+	fn fetch_windows(source: &dyn DataSource) -> Result<Self> {
+		let user_results = source.wmi_query(
+			CIMV2,
 			"SELECT Name, Disabled, Description FROM Win32_UserAccount WHERE LocalAccount=True",
 		)?;
 
@@ -38,15 +101,17 @@ impl ComputerInfoExt for UsersGroupsInfo {
 				name: data.get_string("Name").unwrap_or_default(),
 				disabled: data.get_bool("Disabled").unwrap_or(false),
 				description: data.get_string("Description").unwrap_or_default(),
+				..Default::default()
 			})
 			.collect();
 
-		let group_results: Vec<HashMap<String, Variant>> = com.raw_query(
+		let group_results = source.wmi_query(
+			CIMV2,
 			"SELECT Name, Description FROM Win32_Group WHERE LocalAccount=True",
 		)?;
 
-		let member_results: Vec<HashMap<String, Variant>> = com
-			.raw_query("SELECT GroupComponent, PartComponent FROM Win32_GroupUser")
+		let member_results = source
+			.wmi_query(CIMV2, "SELECT GroupComponent, PartComponent FROM Win32_GroupUser")
 			.unwrap_or_default();
 
 		let mut group_members: HashMap<String, Vec<String>> = HashMap::new();
@@ -75,12 +140,114 @@ impl ComputerInfoExt for UsersGroupsInfo {
 					name,
 					description: data.get_string("Description").unwrap_or_default(),
 					members,
+					..Default::default()
 				}
 			})
 			.collect();
 
 		Ok(UsersGroupsInfo { users, groups })
 	}
+
+	fn fetch_linux(source: &dyn DataSource) -> Result<Self> {
+		let passwd = source.read_to_string(PASSWD).with_context(|| format!("Failed to read {}", PASSWD))?;
+		let group = source.read_to_string(GROUP).with_context(|| format!("Failed to read {}", GROUP))?;
+		// Only root can read the shadow files
+		let shadow = source
+			.read_to_string(SHADOW)
+			.inspect_err(|e| report::warn(format!("Cannot read {}, so password states are unknown: {}", SHADOW, e)))
+			.unwrap_or_default();
+		let gshadow = source.read_to_string(GSHADOW).unwrap_or_default();
+		let (uids, gids) = (uid_range(source), gid_range(source));
+		let shadow = records(&shadow);
+		let today = Utc::now().date_naive();
+
+		let mut users = Vec::new();
+		let mut primary_groups = Vec::new();
+		for line in passwd.lines() {
+			// alex:x:1000:1000:Alex Moreau,,,:/home/alex:/bin/bash
+			let fields: Vec<&str> = line.split(':').collect();
+			let [name, _, uid, gid, gecos, home, shell] = fields[..] else {
+				continue;
+			};
+			let (Ok(uid), Ok(gid)) = (uid.parse::<u32>(), gid.parse::<u32>()) else {
+				continue;
+			};
+			let mut user = LocalUser {
+				name: name.to_string(),
+				// The full name comes before the room and phone numbers
+				description: gecos.split(',').next().unwrap_or_default().to_string(),
+				uid: Some(uid),
+				home: home.to_string(),
+				shell: shell.to_string(),
+				system_account: !uids.contains(&uid),
+				..Default::default()
+			};
+			if let Some(fields) = shadow.get(name) {
+				apply_shadow(&mut user, fields, today);
+			}
+			primary_groups.push(gid);
+			users.push(user);
+		}
+
+		let gshadow = records(&gshadow);
+		let mut groups = Vec::new();
+		for line in group.lines() {
+			// sudo:x:27:alex,sam
+			let fields: Vec<&str> = line.split(':').collect();
+			let [name, _, gid, members] = fields[..] else {
+				continue;
+			};
+			let Ok(gid) = gid.parse::<u32>() else {
+				continue;
+			};
+			let mut members = split_list(members);
+			// gshadow: name, password, administrators, members
+			let shadow_fields = gshadow.get(name);
+			let field = |index: usize| shadow_fields.and_then(|fields| fields.get(index).copied()).unwrap_or_default();
+			// Users whose primary group it is belong to it without being listed
+			let primary = users.iter().zip(&primary_groups).filter(|&(_, &primary)| primary == gid);
+			for member in split_list(field(3)).into_iter().chain(primary.map(|(user, _)| user.name.clone())) {
+				if !members.contains(&member) {
+					members.push(member);
+				}
+			}
+			groups.push(LocalGroup {
+				name: name.to_string(),
+				members,
+				gid: Some(gid),
+				system_group: !gids.contains(&gid),
+				administrators: split_list(field(2)),
+				..Default::default()
+			});
+		}
+
+		let grantees = match sudoers_grantees(source) {
+			Ok(grantees) => grantees,
+			// sudo is not installed
+			Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) => Vec::new(),
+			Err(e) => {
+				report::warn(format!(
+					"Cannot read {} ({}), so sudo rights are taken from membership of the {} groups",
+					SUDOERS,
+					e,
+					SUDO_GROUPS.join("/")
+				));
+				SUDO_GROUPS.iter().map(|group| format!("%{}", group)).collect()
+			}
+		};
+		let last_logins = last_logins(source);
+		for user in &mut users {
+			user.sudo = grantees.iter().any(|grantee| match grantee.strip_prefix('%') {
+				Some(group) => groups.iter().any(|g| g.name == group && g.members.contains(&user.name)),
+				None => *grantee == user.name || grantee == "ALL",
+			});
+			if let Some(time) = last_logins.get(&user.name) {
+				user.last_login = time.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+			}
+		}
+
+		Ok(UsersGroupsInfo { users, groups })
+	}
 }
 
 fn extract_name(wmi_path: &str) -> String {
@@ -92,3 +259,204 @@ fn extract_name(wmi_path: &str) -> String {
 	}
 	String::new()
 }
+
+/// Fills in the password state from a shadow entry: name, password, day of the last change, minimum
+/// and maximum age, warning and inactivity periods, and expiry day, with days counted from 1970.
+fn apply_shadow(user: &mut LocalUser, fields: &[&str], today: NaiveDate) {
+	let field = |index: usize| fields.get(index).copied().unwrap_or_default();
+	let day = |index: usize| {
+		let days = field(index).parse::<u32>().ok()?;
+		NaiveDate::from_ymd_opt(1970, 1, 1)?.checked_add_signed(Duration::days(days.into()))
+	};
+
+	// `!` prefixes a hash usermod -L locked; `*` and `!!` never held a password
+	user.locked = field(1).starts_with(['!', '*']);
+	if field(2) == "0" {
+		// Forces a new password at the next login
+		user.password_expired = true;
+	} else if let Some(changed) = day(2) {
+		user.password_last_set = changed.format("%Y-%m-%d").to_string();
+		user.password_expired = field(4)
+			.parse::<u32>()
+			.ok()
+			.and_then(|max| changed.checked_add_signed(Duration::days(max.into())))
+			.is_some_and(|expiry| expiry < today);
+	}
+	if let Some(expires) = day(7) {
+		user.account_expires = expires.format("%Y-%m-%d").to_string();
+		user.disabled = expires <= today;
+	}
+	user.disabled |= user.locked;
+}
+
+/// Entries of a colon-separated database such as `/etc/shadow`, keyed by their first field.
+fn records(text: &str) -> HashMap<&str, Vec<&str>> {
+	text.lines()
+		.map(|line| line.split(':').collect::<Vec<_>>())
+		.filter(|fields| fields.len() > 1)
+		.map(|fields| (fields[0], fields))
+		.collect()
+}
+
+fn split_list(field: &str) -> Vec<String> {
+	field
+		.split(',')
+		.map(str::trim)
+		.filter(|item| !item.is_empty())
+		.map(String::from)
+		.collect()
+}
+
+/// Users and `%group`s the rules of `/etc/sudoers` and the files it includes grant commands to, with
+/// `User_Alias` names expanded. `ALL` stands for every user.
+fn sudoers_grantees(source: &dyn DataSource) -> Result<Vec<String>> {
+	let mut aliases = HashMap::new();
+	let mut grantees = Vec::new();
+	read_sudoers(source, SUDOERS, 0, &mut aliases, &mut grantees)?;
+	Ok(grantees)
+}
+
+fn read_sudoers(
+	source: &dyn DataSource,
+	path: &str,
+	depth: usize,
+	aliases: &mut HashMap<String, Vec<String>>,
+	grantees: &mut Vec<String>,
+) -> Result<()> {
+	// A trailing backslash continues the line
+	let text = source.read_to_string(path)?.replace("\\\n", " ");
+	for line in text.lines().map(str::trim) {
+		// #include is a directive despite looking like a comment; @include is the newer spelling
+		if let Some(include) = line.strip_prefix("@include").or_else(|| line.strip_prefix("#include")) {
+			// sudo stops at 128 levels; loops are the only way to get near that
+			if depth >= 8 {
+				continue;
+			}
+			let files = match include.strip_prefix("dir") {
+				Some(directory) => {
+					let directory = directory.trim();
+					source
+						.read_dir(directory)
+						.unwrap_or_default()
+						.into_iter()
+						// Names with a dot or ending in ~ are skipped, e.g. editor backups and .dpkg-old
+						.filter(|name| !name.contains('.') && !name.ends_with('~'))
+						.map(|name| format!("{}/{}", directory, name))
+						.collect()
+				}
+				None => vec![include.trim().to_string()],
+			};
+			for file in files {
+				// An include sudo itself could not read would be an error there; here it only loses rules
+				let _ = read_sudoers(source, &file, depth + 1, aliases, grantees);
+			}
+			continue;
+		}
+
+		// # starts a comment unless it is part of a #uid
+		let comment = line
+			.char_indices()
+			.find(|&(i, c)| c == '#' && !line[i + 1..].starts_with(|c: char| c.is_ascii_digit()))
+			.map_or(line.len(), |(i, _)| i);
+		let line = line[..comment].trim();
+		if line.is_empty() || line.starts_with("Defaults") {
+			continue;
+		}
+		if let Some(definitions) = line.strip_prefix("User_Alias") {
+			// User_Alias ADMINS = alex, %wheel : OPERATORS = sam
+			for definition in definitions.split(':') {
+				if let Some((name, members)) = definition.split_once('=') {
+					aliases.insert(name.trim().to_string(), split_list(members));
+				}
+			}
+			continue;
+		}
+		if ["Host_Alias", "Runas_Alias", "Cmnd_Alias", "Cmd_Alias"].iter().any(|alias| line.starts_with(alias)) {
+			continue;
+		}
+
+		// alex, %admins ALL=(ALL:ALL) ALL: the user list is the words up to the first without a
+		// trailing comma, the host list follows
+		let Some((spec, _)) = line.split_once('=') else {
+			continue;
+		};
+		let mut users = String::new();
+		for word in spec.split_whitespace() {
+			users.push_str(word);
+			if !word.ends_with(',') {
+				break;
+			}
+		}
+		for user in split_list(&users) {
+			// Negated entries only carve exceptions out of a list
+			if user.starts_with('!') {
+				continue;
+			}
+			match aliases.get(&user) {
+				Some(members) => grantees.extend(members.iter().filter(|member| !member.starts_with('!')).cloned()),
+				None => grantees.push(user),
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Newest login of each user. lastlog keeps each user's latest login but not who logged in
+/// elsewhere first, while wtmp holds every login but is rotated, so both are merged.
+fn last_logins(source: &dyn DataSource) -> HashMap<String, DateTime<Utc>> {
+	let mut logins: HashMap<String, DateTime<Utc>> = HashMap::new();
+	let mut record = |name: &str, time: DateTime<Utc>| {
+		let newest = logins.entry(name.to_string()).or_insert(time);
+		*newest = time.max(*newest);
+	};
+
+	if let Ok(data) = source.read_file(LASTLOG2_DB) {
+		// Lastlog2: Name, Time, TTY, RemoteHost, Service
+		match sqlite::Database::parse(&data).and_then(|database| database.rows("Lastlog2")) {
+			Ok(rows) => {
+				for (_, columns) in rows {
+					if let [Value::Text(name), Value::Integer(time), ..] = columns.as_slice()
+						&& *time > 0
+						&& let Some(time) = DateTime::from_timestamp(*time, 0)
+					{
+						record(name, time);
+					}
+				}
+			}
+			Err(e) => report::warn(format!("Failed to read {}: {:#}", LASTLOG2_DB, e)),
+		}
+	}
+
+	// /var/log/lastlog is a sparse file indexed by UID, so directory users with large UIDs make it
+	// far too big to read whole; the command seeks to each account's record instead
+	if let Ok(output) = source.command("lastlog", &[]) {
+		// alex             pts/0    192.168.1.20     Fri Oct 16 21:44:10 +0000 2026
+		for line in output.stdout.lines().skip(1) {
+			let words: Vec<&str> = line.split_whitespace().collect();
+			if words.len() < 7 {
+				continue;
+			}
+			let time = words[words.len() - 6..].join(" ");
+			if let Ok(time) = DateTime::parse_from_str(&time, "%a %b %d %H:%M:%S %z %Y") {
+				record(words[0], time.with_timezone(&Utc));
+			}
+		}
+	}
+
+	if let Ok(data) = source.read_file(WTMP) {
+		for entry in data.chunks_exact(UTMP_RECORD_SIZE) {
+			// ut_type and ut_pid, ut_line[32], ut_id[4], ut_user[32], ut_host[256], ut_exit and
+			// ut_session, then the 32-bit seconds of ut_tv
+			if u16::from_le_bytes([entry[0], entry[1]]) != UTMP_USER_PROCESS {
+				continue;
+			}
+			let user = &entry[44..76];
+			let user = String::from_utf8_lossy(&user[..user.iter().position(|&byte| byte == 0).unwrap_or(user.len())]);
+			let seconds = u32::from_le_bytes([entry[340], entry[341], entry[342], entry[343]]);
+			if let Some(time) = DateTime::from_timestamp(seconds.into(), 0) {
+				record(&user, time);
+			}
+		}
+	}
+	logins
+}
@@ -0,0 +1,5 @@
+# Set by the openjdk-21 package
+if [ -d /usr/lib/jvm/java-21-openjdk-amd64 ]; then
+	export JAVA_HOME=/usr/lib/jvm/java-21-openjdk-amd64
+	export PATH="$PATH:${JAVA_HOME}/bin"
+fi
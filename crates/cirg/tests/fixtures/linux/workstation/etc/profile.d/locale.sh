@@ -0,0 +1,3 @@
+export LANG='C.UTF-8'
+export PROMPT_COMMAND='printf "\033]0;%s\007" "$PWD"'
+export PS1
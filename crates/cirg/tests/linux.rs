@@ -0,0 +1,1417 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, Utc, Weekday};
+use cirg::audio_info::AudioInfo;
+use cirg::calendar::Schedule;
+use cirg::computer_info::{BIOSInfo, ComputerInfo};
+use cirg::cpu_info::CpuInfo;
+use cirg::disk_info::DiskInfo;
+use cirg::environment_info::EnvironmentInfo;
+use cirg::event_log_info::EventLogInfo;
+use cirg::gpu_info::GpuInfo;
+use cirg::hotfix_info::HotfixInfo;
+use cirg::memory_info::MemoryInfo;
+use cirg::monitor_info::{DisplayMode, MonitorInfo};
+use cirg::network_info::{InterfaceAddress, NetworkInfo};
+use cirg::power_info::PowerInfo;
+use cirg::process_info::{ProcessCount, ProcessInfo, ProcessOptions, ProcessSort};
+use cirg::scheduled_task_info::ScheduledTaskInfo;
+use cirg::security_info::{SecurityInfo, UpdateItem};
+use cirg::service_info::ServiceInfo;
+use cirg::smbios::SmbiosTable;
+use cirg::software_info::SoftwareInfo;
+use cirg::startup_info::StartupInfo;
+use cirg::source::{
+	CommandOutput, DataSource, FilesystemUsage, Fixture, Hive, LiveSource, Platform, RecordingSource, RegistryKey, ReplaySource, Row,
+};
+use cirg::usb_info::UsbInfo;
+use cirg::users_groups_info::UsersGroupsInfo;
+use cirg::{ComputerInfoExt, Section, SectionStatus};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+fn source(machine: &str) -> LiveSource {
+	LiveSource::with_root(format!("{}/tests/fixtures/linux/{}", env!("CARGO_MANIFEST_DIR"), machine))
+}
+
+#[test]
+fn computer_info_from_proc_and_dmi() {
+	let computer = ComputerInfo::fetch_from(&source("workstation")).unwrap();
+
+	assert_eq!(computer.name, "workstation");
+	assert_eq!(computer.domain, "N/A");
+	assert_eq!(computer.manufacturer, "Micro-Star International Co., Ltd.");
+	assert_eq!(computer.model, "MS-7C56");
+	assert_eq!(computer.system_type, "x86_64-based PC");
+
+	let os = &computer.operating_system;
+	assert_eq!(os.name, "Ubuntu 24.04.1 LTS");
+	assert_eq!(os.version, "24.04");
+	assert_eq!(os.build_lab, "6.8.0-45-generic #45-Ubuntu SMP PREEMPT_DYNAMIC Fri Aug 30 12:02:04 UTC 2024");
+	assert_eq!(os.architecture, "64-bit");
+	assert_eq!(os.uptime, 12345);
+	assert_eq!(os.timezone, "Europe/Berlin");
+
+	assert_eq!(computer.bios.manufacturer, "American Megatrends International, LLC.");
+	assert_eq!(computer.bios.version, "1.A0");
+	assert_eq!(computer.bios.release_date.to_string(), "2024-03-15");
+	assert_eq!(computer.bios.uuid, "03C00218-044D-05A0-3B06-7A0700080009");
+	assert_eq!(computer.bios.board.product, "B550-A PRO (MS-7C56)");
+	assert_eq!(computer.bios.board.serial_number, "07C5611_N11E123456");
+	assert_eq!(computer.bios.chassis.chassis_type, "Desktop");
+	assert_eq!(computer.bios.chassis.serial_number, "Chassis Serial 42");
+}
+
+#[test]
+fn memory_slots_from_smbios() {
+	let memory = MemoryInfo::fetch_from(&source("workstation")).unwrap();
+
+	assert_eq!(memory.total_slots, 4);
+	assert_eq!(memory.max_capacity_gb, 128);
+	assert_eq!(memory.error_correction, "None");
+	assert_eq!(memory.slots.len(), 2);
+
+	let slot = &memory.slots[0];
+	assert_eq!(slot.bank_label, "DIMM 1");
+	assert_eq!(slot.capacity_gb, 16.0);
+	assert_eq!(slot.memory_type, "DDR4");
+	assert_eq!(slot.form_factor, "DIMM");
+	assert_eq!(slot.speed_mhz, 3600);
+	assert_eq!(slot.configured_speed_mhz, 3200);
+	assert_eq!(slot.voltage_mv, 1350);
+	assert_eq!(slot.rank, 2);
+	assert_eq!(slot.serial_number, "12AB34CD");
+	assert_eq!(slot.part_number, "KF3600C18D4/16GX");
+	assert!(!slot.ecc);
+
+	let usage = &memory.usage;
+	assert_eq!(usage.total_mb, 32000);
+	assert_eq!(usage.available_mb, 20000);
+	assert_eq!(usage.used_mb, 12000);
+	assert_eq!(usage.used_pct, 37.5);
+	assert_eq!(usage.cached_mb, 14000);
+	assert_eq!(usage.buffers_mb, 500);
+	assert_eq!(usage.swap_total_mb, 8191);
+	assert_eq!(usage.swap_used_mb, 1024);
+	assert_eq!(usage.commit_limit_mb, Some(24191));
+	assert_eq!(usage.committed_mb, Some(18000));
+}
+
+#[test]
+fn memory_usage_without_mem_available() {
+	let usage = MemoryInfo::fetch_from(&source("server")).unwrap().usage;
+
+	// Older kernels: free + buffers + cache
+	assert_eq!(usage.total_mb, 16000);
+	assert_eq!(usage.available_mb, 8000);
+	assert_eq!(usage.used_pct, 50.0);
+	assert_eq!(usage.swap_total_mb, 0);
+}
+
+#[test]
+fn legacy_smbios_structures() {
+	let memory = MemoryInfo::fetch_from(&source("server")).unwrap();
+
+	// The video memory array and its device are not system memory
+	assert_eq!(memory.total_slots, 2);
+	assert_eq!(memory.max_capacity_gb, 64);
+	assert_eq!(memory.error_correction, "Multi-bit ECC");
+	assert_eq!(memory.slots.len(), 2);
+	assert_eq!(memory.slots[0].capacity_gb, 8.0);
+	assert_eq!(memory.slots[0].memory_type, "RAM");
+	assert_eq!(memory.slots[0].rank, 0);
+	assert_eq!(memory.slots[0].configured_speed_mhz, 0);
+	assert!(memory.slots[0].ecc);
+
+	let bios = BIOSInfo::fetch_from(&source("server")).unwrap();
+	assert_eq!(bios.manufacturer, "Phoenix Technologies LTD");
+	assert_eq!(bios.release_date.to_string(), "1999-04-05");
+	assert_eq!(bios.uuid, "N/A");
+	assert_eq!(bios.chassis.chassis_type, "Main Server Chassis");
+}
+
+#[test]
+fn smbios_dump_with_entry_point() {
+	let table = SmbiosTable::load(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/smbios/server.bin")).unwrap();
+
+	assert_eq!(table.version, Some((2, 3)));
+	let processors = table.processors();
+	assert_eq!(processors.len(), 2);
+	assert_eq!(processors[1].socket_designation, "CPU #001");
+	assert_eq!(processors[1].max_speed_mhz, 4096);
+	assert!(processors[1].populated);
+	assert_eq!(MemoryInfo::from_smbios(&table).slots.len(), 2);
+}
+
+#[test]
+fn block_devices_from_sysfs() {
+	let disks = DiskInfo::fetch_from(&source("workstation")).unwrap().physical_disks;
+
+	// loop0 is virtual, sr0 is an optical drive
+	assert_eq!(disks.len(), 3);
+
+	assert_eq!(disks[0].model, "Samsung SSD 980 PRO 500GB");
+	assert_eq!(disks[0].interface_type, "NVMe");
+	assert_eq!(disks[0].disk_type, "SSD");
+	assert_eq!(disks[0].status, "OK");
+	assert_eq!(format!("{:.1}", disks[0].size_gb), "476.9");
+
+	assert_eq!(disks[1].model, "ST2000DM008-2FR102");
+	assert_eq!(disks[1].interface_type, "SATA");
+	assert_eq!(disks[1].disk_type, "HDD");
+	assert_eq!(disks[1].media_type, "Fixed hard disk media");
+
+	assert_eq!(disks[2].model, "SanDisk Ultra");
+	assert_eq!(disks[2].interface_type, "USB");
+	assert_eq!(disks[2].media_type, "Removable Media");
+	assert!(disks[2].removable);
+
+	// virtio-blk has no model and reports the PCI vendor id as its vendor
+	let disks = DiskInfo::fetch_from(&source("server")).unwrap().physical_disks;
+	assert_eq!(disks.len(), 1);
+	assert_eq!(disks[0].model, "vda");
+	assert_eq!(disks[0].interface_type, "VirtIO");
+	assert_eq!(disks[0].disk_type, "HDD");
+	assert_eq!(disks[0].status, "Unknown");
+	assert_eq!(format!("{:.1}", disks[0].size_gb), "40.0");
+}
+
+#[test]
+fn mounted_filesystems_skip_pseudo_and_bind_mounts() {
+	// A captured tree cannot be stat'ed, so record it and add the statvfs results by hand
+	let recorder = RecordingSource::new(Arc::new(source("workstation")));
+	let disks = DiskInfo::fetch_from(&recorder).unwrap().logical_disks;
+	let mounts: Vec<_> = disks.iter().map(|d| d.device_id.as_str()).collect();
+	assert_eq!(mounts, ["/", "/boot/efi", "/mnt/Data Disk", "/media/alex/USB"]);
+	assert_eq!(disks[0].total_gb, 0.0);
+
+	let mut fixture = recorder.fixture();
+	assert!(fixture.filesystems["/"].is_err());
+	fixture.filesystems.insert(
+		"/".to_string(),
+		Ok(FilesystemUsage {
+			total_bytes: 400 << 30,
+			available_bytes: 100 << 30,
+		}),
+	);
+	let disks = DiskInfo::fetch_from(&ReplaySource::new(fixture)).unwrap().logical_disks;
+
+	assert_eq!(disks[0].file_system, "ext4");
+	assert_eq!(disks[0].total_gb, 400.0);
+	assert_eq!(disks[0].free_gb, 100.0);
+	assert_eq!(disks[0].used_gb, 300.0);
+	assert_eq!(disks[0].usage_pct, 75.0);
+	assert_eq!(disks[1].file_system, "vfat");
+	assert_eq!(disks[2].volume_name, "Data Disk");
+	assert_eq!(disks[3].volume_name, "USB");
+	assert_eq!(disks[3].file_system, "exfat");
+}
+
+/// A captured tree plus what `getifaddrs`, the DRM mode ioctls and commands returned on that machine.
+#[derive(Default)]
+struct Captured {
+	tree: LiveSource,
+	addresses: Vec<(&'static str, &'static str)>,
+	display_modes: Vec<DisplayMode>,
+	/// Command line and its stdout, or its stderr when it failed.
+	commands: Vec<(&'static str, Result<&'static str, &'static str>)>,
+}
+
+impl DataSource for Captured {
+	fn platform(&self) -> Platform {
+		self.tree.platform()
+	}
+	fn wmi_query(&self, namespace: &str, query: &str) -> Result<Vec<Row>> {
+		self.tree.wmi_query(namespace, query)
+	}
+	fn wmi_method(&self, namespace: &str, object_path: &str, method: &str, args: &Row) -> Result<Row> {
+		self.tree.wmi_method(namespace, object_path, method, args)
+	}
+	fn registry_key(&self, hive: Hive, path: &str) -> Result<RegistryKey> {
+		self.tree.registry_key(hive, path)
+	}
+	fn command(&self, program: &str, args: &[&str]) -> Result<CommandOutput> {
+		let line = std::iter::once(program).chain(args.iter().copied()).collect::<Vec<_>>().join(" ");
+		match self.commands.iter().find(|(command, _)| *command == line) {
+			Some((_, Ok(stdout))) => Ok(CommandOutput { status: Some(0), stdout: stdout.to_string(), stderr: String::new() }),
+			Some((_, Err(stderr))) => Ok(CommandOutput { status: Some(1), stdout: String::new(), stderr: stderr.to_string() }),
+			None => self.tree.command(program, args),
+		}
+	}
+	fn display_modes(&self) -> Result<Vec<DisplayMode>> {
+		Ok(self.display_modes.clone())
+	}
+	fn pending_updates(&self) -> Result<Vec<UpdateItem>> {
+		self.tree.pending_updates()
+	}
+	fn interface_addresses(&self) -> Result<Vec<InterfaceAddress>> {
+		Ok(self
+			.addresses
+			.iter()
+			.map(|(interface, address)| InterfaceAddress {
+				interface: interface.to_string(),
+				address: address.parse().unwrap(),
+			})
+			.collect())
+	}
+	fn read_file(&self, path: &str) -> Result<Vec<u8>> {
+		self.tree.read_file(path)
+	}
+	fn read_dir(&self, path: &str) -> Result<Vec<String>> {
+		self.tree.read_dir(path)
+	}
+	fn read_link(&self, path: &str) -> Result<String> {
+		self.tree.read_link(path)
+	}
+	fn modified(&self, path: &str) -> Result<DateTime<Utc>> {
+		self.tree.modified(path)
+	}
+	fn filesystem_usage(&self, path: &str) -> Result<FilesystemUsage> {
+		self.tree.filesystem_usage(path)
+	}
+}
+
+#[test]
+fn network_adapters_routes_and_resolver() {
+	// Without addresses only the link that is up is listed
+	let adapters = NetworkInfo::fetch_from(&source("workstation")).unwrap().adapters;
+	assert_eq!(adapters.len(), 1);
+
+	let source = Captured {
+		tree: source("workstation"),
+		addresses: vec![
+			("lo", "127.0.0.1"),
+			("enp5s0", "192.168.1.50"),
+			("docker0", "172.17.0.1"),
+			("enp5s0", "fe80::dabb:c1ff:fe0a:1b2c"),
+		],
+		..Captured::default()
+	};
+	let adapters = NetworkInfo::fetch_from(&source).unwrap().adapters;
+	let names: Vec<_> = adapters.iter().map(|a| a.name.as_str()).collect();
+	assert_eq!(names, ["docker0", "enp5s0"]);
+
+	let docker = &adapters[0];
+	assert_eq!(docker.description, "Bridge");
+	assert_eq!(docker.speed, "N/A");
+	assert_eq!(docker.gateway, "");
+	assert!(docker.dns_servers.is_empty());
+	assert!(!docker.dhcp_enabled);
+
+	let ethernet = &adapters[1];
+	assert_eq!(ethernet.description, "Ethernet (igc)");
+	assert_eq!(ethernet.mac_address, "D8:BB:C1:0A:1B:2C");
+	assert_eq!(ethernet.speed, "1.0 Gbps");
+	assert_eq!(ethernet.ipv4_addresses, ["192.168.1.50"]);
+	assert_eq!(ethernet.ipv6_addresses, ["fe80::dabb:c1ff:fe0a:1b2c"]);
+	assert_eq!(ethernet.gateway, "192.168.1.1");
+	// resolv.conf points at the systemd-resolved stub
+	assert_eq!(ethernet.dns_servers, ["192.168.1.1", "fd00::1"]);
+	assert!(ethernet.dhcp_enabled);
+}
+
+#[test]
+fn gpu_from_drm_sysfs() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let report = runtime.block_on(cirg::collect_from(Arc::new(source("workstation")), &[Section::Gpu]));
+	let adapters = report.gpu.data.unwrap().adapters;
+
+	// card1 is the boot framebuffer
+	assert_eq!(adapters.len(), 1);
+	let gpu = &adapters[0];
+	assert_eq!(gpu.name, "AMD Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]");
+	assert_eq!(gpu.pci_id, "1002:73BF");
+	assert_eq!(gpu.driver, "amdgpu");
+	assert_eq!(gpu.driver_version, "6.8.0-45-generic");
+	assert_eq!(gpu.adapter_ram_mb, 16368);
+	assert_eq!(gpu.availability, "Running/Full Power");
+
+	// No DRM device to ask, so the preferred mode stands in for the current one
+	assert_eq!(report.gpu.warnings.len(), 1);
+	let connectors: Vec<_> = gpu.outputs.iter().map(|o| o.connector.as_str()).collect();
+	assert_eq!(connectors, ["card0-DP-1", "card0-HDMI-A-1"]);
+	assert_eq!(gpu.resolution, "2560x1440");
+	assert_eq!(gpu.refresh_rate, 0);
+
+	let source = Captured {
+		tree: source("workstation"),
+		display_modes: vec![DisplayMode {
+			device_name: "card0-HDMI-A-1".to_string(),
+			width: 1280,
+			height: 1024,
+			refresh_rate: 75,
+			..DisplayMode::default()
+		}],
+		..Captured::default()
+	};
+	let gpu = GpuInfo::fetch_from(&source).unwrap().adapters.remove(0);
+	assert_eq!(gpu.outputs[0].resolution, "2560x1440");
+	assert_eq!(gpu.outputs[1].resolution, "1280x1024");
+	assert_eq!(gpu.outputs[1].refresh_rate, 75);
+}
+
+#[test]
+fn gpus_without_pci_ids_database() {
+	let adapters = GpuInfo::fetch_from(&source("server")).unwrap().adapters;
+
+	assert_eq!(adapters[0].name, "PCI device [1A03:2000]");
+	assert_eq!(adapters[0].driver_version, "5.14.0-427.13.1.el9_4.x86_64");
+	assert_eq!(adapters[0].resolution, "1024x768");
+	assert_eq!(adapters[1].name, "NVIDIA [10DE:20B5]");
+	assert_eq!(adapters[1].driver, "nvidia");
+	assert_eq!(adapters[1].driver_version, "550.90.07");
+	assert_eq!(adapters[1].adapter_ram_mb, 0);
+	assert_eq!(adapters[1].availability, "Power Save - Standby");
+	assert!(adapters[1].outputs.is_empty());
+}
+
+#[test]
+fn monitors_from_connector_edid() {
+	let source = Captured {
+		tree: source("workstation"),
+		display_modes: vec![DisplayMode {
+			device_name: "card0-DP-1".to_string(),
+			width: 2560,
+			height: 1440,
+			refresh_rate: 144,
+			..DisplayMode::default()
+		}],
+		..Captured::default()
+	};
+	let monitors = MonitorInfo::fetch_from(&source).unwrap().monitors;
+	assert_eq!(monitors.len(), 2);
+
+	let dell = &monitors[0];
+	assert_eq!(dell.manufacturer, "DEL");
+	assert_eq!(dell.name, "DELL S2721DGF");
+	assert_eq!(dell.serial_number, "7KQ2X93");
+	assert_eq!(dell.year_of_manufacture, 2021);
+	assert_eq!(dell.connector, "card0-DP-1");
+	assert_eq!(dell.connection_type, "DisplayPort");
+	assert_eq!(dell.resolution, "2560x1440");
+	assert_eq!(dell.refresh_rate, 144);
+	assert_eq!(dell.native_resolution, "2560x1440");
+	assert_eq!((dell.width_mm, dell.height_mm), (597, 336));
+	assert_eq!(dell.diagonal_inches, 27.0);
+	assert_eq!(dell.bit_depth, 10);
+	assert_eq!(dell.hdr_formats, ["HDR10"]);
+	assert_eq!(dell.supported_modes[..3], ["2560x1440@60", "1920x1080@120", "1920x1080@60"]);
+	assert_eq!(dell.supported_modes.last().unwrap(), "640x480@60");
+
+	// EDID 1.3 has no bit depth field; the HDMI vendor block's deep color flags stand in
+	let lg = &monitors[1];
+	assert_eq!(lg.name, "LG FULL HD");
+	assert_eq!(lg.connection_type, "HDMI");
+	assert_eq!(lg.resolution, "1920x1080");
+	assert_eq!(lg.refresh_rate, 0);
+	assert_eq!(lg.diagonal_inches, 21.5);
+	assert_eq!(lg.bit_depth, 12);
+	assert!(lg.hdr_formats.is_empty());
+	assert!(lg.supported_modes.contains(&"720x480@60".to_string()));
+	// 1080i is listed as both a detailed timing and VIC 5, which name the same field rate
+	assert_eq!(lg.supported_modes.iter().filter(|mode| mode.starts_with("1920x1080i")).collect::<Vec<_>>(), ["1920x1080i@60"]);
+}
+
+#[test]
+fn sound_cards_from_alsa() {
+	let devices = AudioInfo::fetch_from(&source("workstation")).unwrap().devices;
+	assert_eq!(devices.len(), 3);
+
+	assert_eq!(devices[0].name, "Navi 21/23 HDMI/DP Audio Controller");
+	assert_eq!(devices[0].manufacturer, "Advanced Micro Devices, Inc. [AMD/ATI]");
+	assert_eq!(devices[0].device_id, "0000:0c:00.1");
+	assert_eq!(devices[0].codecs, ["ATI R6xx HDMI"]);
+	assert_eq!((devices[0].playback_devices, devices[0].capture_devices), (2, 0));
+
+	assert_eq!(devices[1].name, "Starship/Matisse HD Audio Controller");
+	assert_eq!(devices[1].driver, "snd_hda_intel");
+	assert_eq!(devices[1].codecs, ["Realtek ALC1220"]);
+	assert_eq!((devices[1].playback_devices, devices[1].capture_devices), (2, 2));
+
+	let headset = &devices[2];
+	assert_eq!(headset.name, "SteelSeries Arctis 7");
+	assert_eq!(headset.manufacturer, "SteelSeries");
+	assert_eq!(headset.driver, "snd-usb-audio");
+	assert_eq!(headset.device_id, "1-4");
+	assert!(headset.codecs.is_empty());
+	assert_eq!((headset.playback_devices, headset.capture_devices), (1, 1));
+
+	// No ALSA at all on the server
+	assert!(AudioInfo::fetch_from(&source("server")).unwrap().devices.is_empty());
+}
+
+#[test]
+fn usb_tree_from_sysfs() {
+	let devices = UsbInfo::fetch_from(&source("workstation")).unwrap().devices;
+
+	let tree: Vec<_> = devices.iter().map(|d| (d.device_id.as_str(), d.parent.as_str())).collect();
+	assert_eq!(
+		tree,
+		[("usb1", ""), ("1-2", "usb1"), ("1-2.1", "1-2"), ("1-2.3", "1-2"), ("1-4", "usb1"), ("usb2", ""), ("2-1", "usb2")]
+	);
+
+	let root_hub = &devices[0];
+	assert_eq!(root_hub.name, "xHCI Host Controller");
+	assert_eq!(root_hub.device_class, "Hub");
+	assert_eq!(root_hub.speed, "High Speed (480 Mbps)");
+
+	let receiver = &devices[2];
+	assert_eq!(receiver.name, "USB Receiver");
+	assert_eq!((receiver.vendor_id.as_str(), receiver.product_id.as_str()), ("046D", "C52B"));
+	assert_eq!((receiver.bus, receiver.ports.as_str()), (1, "2.1"));
+	assert_eq!(receiver.device_class, "HID");
+	assert_eq!(receiver.speed, "Full Speed (12 Mbps)");
+	assert_eq!(receiver.max_power_ma, 98);
+
+	// No descriptor strings, so the names come from usb.ids
+	let led = &devices[3];
+	assert_eq!(led.name, "ASUSTek Computer, Inc. AURA LED Controller");
+	assert_eq!(led.manufacturer, "ASUSTek Computer, Inc.");
+	assert_eq!(led.status, "Unauthorized");
+
+	assert_eq!(devices[4].device_class, "Audio, HID");
+
+	let stick = &devices[6];
+	assert_eq!(stick.name, "SanDisk 3.2Gen1");
+	assert_eq!(stick.manufacturer, "USB");
+	assert_eq!(stick.speed, "SuperSpeed (5 Gbps)");
+	assert_eq!(stick.device_class, "Mass Storage");
+	assert_eq!(stick.serial_number, "4C530001230812116443");
+	assert_eq!(stick.max_power_ma, 896);
+}
+
+#[test]
+fn laptop_battery_and_power_profile() {
+	let power = PowerInfo::fetch_from(&source("laptop")).unwrap();
+	let battery = power.battery.expect("system battery");
+
+	// The mouse battery has Device scope and is not the system battery
+	assert_eq!(battery.name, "SMP 5B10W13975");
+	assert_eq!(battery.status, "Discharging");
+	assert_eq!(battery.charge_pct, "76");
+	assert_eq!(battery.run_time_mins, "240");
+	assert_eq!(battery.design_capacity, "57000");
+	assert_eq!(battery.full_charge_capacity, "45120");
+	assert_eq!(battery.chemistry, "Lithium Polymer");
+	assert_eq!(battery.cycle_count, "312");
+	assert_eq!(power.power_source, "Battery");
+	assert_eq!(power.plan, "Power saver");
+}
+
+#[test]
+fn power_plan_falls_back_to_cpufreq_governor() {
+	let power = PowerInfo::fetch_from(&source("workstation")).unwrap();
+	assert_eq!(power.plan, "schedutil (cpufreq governor)");
+	assert_eq!(power.power_source, "AC");
+	assert!(power.battery.is_none());
+
+	assert_eq!(PowerInfo::fetch_from(&source("server")).unwrap().plan, "Unknown");
+}
+
+#[test]
+fn laptop_secure_boot_tpm_and_luks_under_lvm() {
+	let laptop = Captured {
+		tree: source("laptop"),
+		commands: vec![
+			("nft list ruleset", Err("Operation not permitted (you must be root)")),
+			("iptables -S", Err("iptables v1.8.10 (nf_tables): Could not fetch rule set generation id: Permission denied (you must be root)")),
+		],
+		..Default::default()
+	};
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let report = runtime.block_on(cirg::collect_from(Arc::new(laptop), &[Section::Security]));
+	let security = report.security.data.expect("security section");
+
+	assert!(security.secure_boot);
+	let tpm = security.tpm.expect("TPM");
+	assert_eq!(tpm.version, "2.0");
+	assert_eq!(tpm.manufacturer, "Unknown");
+	assert!(tpm.ready);
+
+	// /boot is a plain partition; / and /home are logical volumes inside the LUKS container
+	assert!(security.bit_locker);
+	assert_eq!(security.encrypted_volumes, ["/", "/home"]);
+	assert_eq!(security.mandatory_access_control.as_deref(), Some("AppArmor (3 enforcing, 1 complain)"));
+
+	// Without root neither nft nor iptables can list the rules
+	assert!(security.firewall.is_none());
+	assert_eq!(report.security.warnings.len(), 1);
+	assert!(report.security.warnings[0].contains("you must be root"));
+}
+
+#[test]
+fn server_selinux_firewalld_and_tpm_1_2() {
+	let server = Captured {
+		tree: source("server"),
+		commands: vec![(
+			"nft list ruleset",
+			Ok("table inet firewalld {
+	set blocked {
+		type ipv4_addr
+		flags interval
+	}
+
+	chain filter_INPUT {
+		type filter hook input priority filter + 10; policy accept;
+		ct state { established, related } accept
+		iifname \"lo\" accept
+		jump filter_INPUT_ZONES
+		reject with icmpx admin-prohibited
+	}
+
+	chain filter_OUTPUT {
+		type filter hook output priority filter + 10; policy accept;
+		oifname \"lo\" accept
+		jump filter_OUTPUT_POLICIES_pre
+	}
+
+	chain filter_INPUT_ZONES {
+		iifname \"eth0\" goto filter_IN_public
+		goto filter_IN_public
+	}
+}
+"),
+		)],
+		..Default::default()
+	};
+	let security = SecurityInfo::fetch_from(&server).unwrap();
+
+	assert!(!security.secure_boot);
+	let tpm = security.tpm.expect("TPM");
+	assert_eq!(tpm.version, "1.2");
+	assert_eq!(tpm.manufacturer, "IFX");
+	assert!(tpm.enabled && tpm.activated);
+	assert!(!tpm.ready);
+
+	let firewall = security.firewall.expect("firewall");
+	assert_eq!(firewall.backend.as_deref(), Some("nftables"));
+	assert_eq!(firewall.inbound.as_deref(), Some("Block"));
+	assert_eq!(firewall.outbound.as_deref(), Some("Allow"));
+	assert_eq!(security.mandatory_access_control.as_deref(), Some("SELinux (enforcing, targeted)"));
+	assert!(!security.bit_locker);
+}
+
+#[test]
+fn firewall_falls_back_to_legacy_iptables() {
+	let workstation = Captured {
+		tree: source("workstation"),
+		commands: vec![
+			("nft list ruleset", Ok("")),
+			(
+				"iptables -S",
+				Ok("-P INPUT DROP\n-P FORWARD DROP\n-P OUTPUT ACCEPT\n-N ufw-before-input\n-A INPUT -j ufw-before-input\n"),
+			),
+		],
+		..Default::default()
+	};
+	let security = SecurityInfo::fetch_from(&workstation).unwrap();
+
+	let firewall = security.firewall.expect("firewall");
+	assert_eq!(firewall.backend.as_deref(), Some("iptables"));
+	assert_eq!(firewall.inbound.as_deref(), Some("Block"));
+	assert_eq!(firewall.outbound.as_deref(), Some("Allow"));
+	assert!(security.tpm.is_none());
+	assert!(security.mandatory_access_control.is_none());
+	assert!(security.encrypted_volumes.is_empty());
+}
+
+#[test]
+fn dpkg_flatpak_and_snap_packages() {
+	let programs = SoftwareInfo::fetch_from(&source("workstation")).unwrap().programs;
+	let names: Vec<(&str, &str, &str)> = programs
+		.iter()
+		.map(|p| (p.name.as_str(), p.package_manager.as_str(), p.architecture.as_str()))
+		.collect();
+
+	// Removed packages with leftover conffiles, base snaps and snapd itself are not listed
+	assert_eq!(
+		names,
+		[
+			("bash", "dpkg", "amd64"),
+			("code", "dpkg", "amd64"),
+			("firefox", "snap", "amd64"),
+			("GNU Image Manipulation Program", "flatpak", "x86_64"),
+			// Installed into alex's home rather than system-wide
+			("Krita", "flatpak", "x86_64"),
+			("libc6", "dpkg", "amd64"),
+			("libc6", "dpkg", "i386"),
+			("tzdata", "dpkg", "all"),
+			("yq", "snap", "all"),
+		]
+	);
+
+	let code = &programs[1];
+	assert_eq!(code.version, "1.93.1-1726079302");
+	assert_eq!(code.publisher, "Microsoft Corporation");
+	assert_eq!(code.install_date, "20240914");
+
+	// The i386 library was upgraded after it was first installed
+	assert_eq!(programs[6].install_date, "20240920");
+	assert_eq!(programs[7].install_date, "20240920");
+	// Without a log entry the date comes from the package's file list
+	assert_eq!(programs[0].install_date.len(), 8);
+
+	let gimp = &programs[3];
+	assert_eq!(gimp.version, "2.10.38");
+	assert_eq!(gimp.publisher, "The GIMP Team");
+	let krita = &programs[4];
+	assert_eq!(krita.version, "5.2.6");
+	assert_eq!(krita.publisher, "Krita Foundation");
+	assert_eq!(programs[2].version, "131.0.3-1");
+	assert_eq!(programs[8].version, "v4.44.3");
+}
+
+#[test]
+fn rpm_packages_from_sqlite_database() {
+	let programs = SoftwareInfo::fetch_from(&source("server")).unwrap().programs;
+
+	// The signing key pseudo-package is skipped
+	assert_eq!(programs.len(), 44);
+	assert!(programs.iter().all(|p| p.package_manager == "rpm"));
+
+	let kernel = programs.iter().find(|p| p.name == "kernel-core").unwrap();
+	assert_eq!(kernel.version, "5.14.0-427.37.1.el9_4");
+	assert_eq!(kernel.publisher, "Red Hat, Inc.");
+	assert_eq!(kernel.architecture, "x86_64");
+	assert_eq!(kernel.install_date, "20240917");
+
+	let openssl = programs.iter().find(|p| p.name == "openssl-libs").unwrap();
+	assert_eq!(openssl.version, "1:3.0.7-27.el9");
+
+	let zabbix = programs.iter().find(|p| p.name == "zabbix-agent2").unwrap();
+	assert_eq!(zabbix.publisher, "Zabbix Support Team");
+
+	assert_eq!(programs.iter().filter(|p| p.architecture == "noarch").count(), 41);
+	assert_eq!(programs.last().unwrap().name, "zabbix-agent2");
+}
+
+#[test]
+fn pacman_local_database() {
+	let programs = SoftwareInfo::fetch_from(&source("raspberry-pi")).unwrap().programs;
+
+	assert_eq!(programs.len(), 3);
+	let kernel = &programs[0];
+	assert_eq!(kernel.name, "linux-rpi");
+	assert_eq!(kernel.version, "6.6.51-1");
+	assert_eq!(kernel.publisher, "Arch Linux ARM Build System");
+	assert_eq!(kernel.install_date, "20240920");
+	assert_eq!(kernel.architecture, "aarch64");
+	assert_eq!(kernel.package_manager, "pacman");
+	assert_eq!(programs[2].architecture, "any");
+}
+
+#[test]
+fn systemd_service_units() {
+	let workstation = Captured {
+		tree: source("workstation"),
+		commands: vec![
+			(
+				"systemctl list-units --type=service --all --plain --no-legend --no-pager",
+				Ok("cups.service loaded active running CUPS Scheduler
+fwupd-refresh.service loaded active exited Refresh fwupd metadata and update motd
+getty@tty1.service loaded active running Getty on tty1
+ssh.service loaded failed failed OpenBSD Secure Shell server
+systemd-journald.service loaded active running Journal Service
+"),
+			),
+			("systemctl --user list-units --type=service --all --plain --no-legend --no-pager", Err("Failed to connect to bus: No medium found")),
+		],
+		..Default::default()
+	};
+	let services = ServiceInfo::fetch_from(&workstation).unwrap().services;
+	let names: Vec<(&str, &str)> = services.iter().map(|s| (s.name.as_str(), s.scope.as_str())).collect();
+
+	// Aliases and uninstantiated templates are not services of their own
+	assert_eq!(
+		names,
+		[
+			("backup.service", "system"),
+			("bluetooth.service", "system"),
+			("cups.service", "system"),
+			("fwupd-refresh.service", "system"),
+			("gdm3.service", "system"),
+			("getty@tty1.service", "system"),
+			("ssh.service", "system"),
+			("systemd-journald.service", "system"),
+			("pipewire.service", "user"),
+			("syncthing.service", "user"),
+			("documents-backup.service", "user"),
+		]
+	);
+
+	// The drop-in in /etc replaces the packaged ExecStart
+	let ssh = &services[6];
+	assert_eq!(ssh.display_name, "OpenBSD Secure Shell server");
+	assert_eq!(ssh.path, "/usr/sbin/sshd -D -o ListenAddress=10.0.0.5");
+	assert_eq!(ssh.account, "root");
+	assert_eq!(ssh.start_mode, "Auto");
+	assert_eq!(ssh.state, "Failed");
+	assert_eq!(ssh.description, "");
+
+	let backup = &services[0];
+	assert_eq!(backup.account, "alex");
+	assert_eq!(backup.start_mode, "Manual");
+	assert_eq!(backup.state, "Stopped");
+
+	let bluetooth = &services[1];
+	assert_eq!(bluetooth.start_mode, "Disabled");
+	assert_eq!(bluetooth.display_name, "bluetooth.service");
+
+	let fwupd = &services[3];
+	assert_eq!(fwupd.account, "fwupd-refresh");
+	assert_eq!(fwupd.state, "Exited");
+	assert_eq!(fwupd.path, "/usr/bin/fwupdmgr refresh --no-metadata-check");
+
+	let getty = &services[5];
+	assert_eq!(getty.display_name, "Getty on tty1");
+	assert_eq!(getty.path, r"/sbin/agetty -o '-p -- \\u' --noclear - $TERM");
+	assert_eq!(getty.start_mode, "Auto");
+	assert_eq!(getty.state, "Running");
+
+	// Vendor links in /usr/lib start the journal too
+	assert_eq!(services[7].start_mode, "Auto");
+
+	// Without a user session the user units' state is unknown
+	let syncthing = &services[9];
+	assert_eq!(syncthing.start_mode, "Auto");
+	assert_eq!(syncthing.state, "Unknown");
+	assert_eq!(syncthing.account, "User session");
+	assert_eq!(services[8].start_mode, "Manual");
+
+	// A unit in alex's own directory runs as alex, in a manager that may not be the one queried
+	let documents = &services[10];
+	assert_eq!(documents.display_name, "Back up documents");
+	assert_eq!(documents.account, "alex");
+	assert_eq!(documents.start_mode, "Manual");
+	assert_eq!(documents.state, "Unknown");
+}
+
+#[test]
+fn startup_items_from_autostart_units_and_shell_init() {
+	let items = StartupInfo::fetch_from(&source("workstation")).unwrap().items;
+	let summary: Vec<(&str, &str, &str)> = items
+		.iter()
+		.map(|item| (item.name.as_str(), item.location.as_str(), item.user.as_str()))
+		.collect();
+
+	// Disabled and hidden autostart entries are left out, and a user's own entry replaces or hides
+	// the system one of the same name for them
+	assert_eq!(
+		summary,
+		[
+			("Input Method Starter", "/etc/xdg/autostart/im-launch.desktop", "All users except sam"),
+			("Evolution Alarm Notify", "/etc/xdg/autostart/org.gnome.Evolution-alarm-notify.desktop", "All users except alex"),
+			("Discord", "/home/alex/.config/autostart/discord.desktop", "alex"),
+			("Input Method Starter", "/home/sam/.config/autostart/im-launch.desktop", "sam"),
+			("getty@tty1.service", "/etc/systemd/system/getty.target.wants/getty@tty1.service", "root"),
+			("cups.service", "/etc/systemd/system/multi-user.target.wants/cups.service", "root"),
+			("ssh.service", "/etc/systemd/system/multi-user.target.wants/ssh.service", "root"),
+			("syncthing.service", "/etc/systemd/user/default.target.wants/syncthing.service", "All users"),
+			("pipewire.socket", "/etc/systemd/user/sockets.target.wants/pipewire.socket", "All users"),
+			("backup-watch.path", "/home/alex/.config/systemd/user/default.target.wants/backup-watch.path", "alex"),
+			("echo", "/etc/rc.local", "root"),
+			("fan-curve", "/etc/rc.local", "root"),
+			("xbindkeys", "/home/alex/.profile:11", "alex"),
+			("tunnel.sh", "/home/alex/.bashrc:15", "alex"),
+			("fish", "/home/sam/.zshrc:4", "sam"),
+		]
+	);
+
+	assert_eq!(items[2].command, "/usr/bin/discord --start-minimized");
+	assert_eq!(items[3].command, "fcitx5 -d");
+	assert_eq!(items[6].command, "/usr/sbin/sshd -D -o ListenAddress=10.0.0.5");
+	// Sockets and paths show the command of the service they activate
+	assert_eq!(items[8].command, "/usr/bin/pipewire");
+	assert_eq!(items[9].command, "/usr/bin/rsync -a %h/Documents/ nas.lan:backup/documents/");
+	assert_eq!(items[11].command, "/usr/local/bin/fan-curve --profile quiet &");
+	assert_eq!(items[13].command, "nohup ~/bin/tunnel.sh --host nas.lan > /dev/null 2>&1 &");
+}
+
+#[test]
+fn cron_and_calendar_schedules() {
+	let at = |text: &str| NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").unwrap();
+	let next = |schedule: Schedule, after: &str| schedule.next_after(at(after)).unwrap().to_string();
+
+	// Saturday 17 October 2026
+	let now = "2026-10-17 10:07:30";
+	assert_eq!(next(Schedule::from_cron("*/15 9-17 * * mon-fri").unwrap(), now), "2026-10-19 09:00:00");
+	assert_eq!(next(Schedule::from_cron("*/15 * * * *").unwrap(), now), "2026-10-17 10:15:00");
+	assert_eq!(next(Schedule::from_cron("@monthly").unwrap(), now), "2026-11-01 00:00:00");
+	assert_eq!(next(Schedule::from_cron("0 6 * dec 0").unwrap(), now), "2026-12-06 06:00:00");
+	// Sunday is 7 as well as 0
+	assert_eq!(next(Schedule::from_cron("0 6 * * 7").unwrap(), now), "2026-10-18 06:00:00");
+	// With both day fields restricted, either one matching is enough
+	assert_eq!(next(Schedule::from_cron("0 0 13 * 5").unwrap(), now), "2026-10-23 00:00:00");
+	assert_eq!(next(Schedule::from_cron("0 12 29 2 *").unwrap(), now), "2028-02-29 12:00:00");
+	assert!(Schedule::from_cron("0 0 30 2 *").unwrap().next_after(at(now)).is_none());
+	assert!(Schedule::from_cron("@reboot").is_err());
+	assert!(Schedule::from_cron("61 * * * *").is_err());
+
+	assert_eq!(next(Schedule::from_calendar("daily").unwrap(), now), "2026-10-18 00:00:00");
+	assert_eq!(next(Schedule::from_calendar("weekly").unwrap(), now), "2026-10-19 00:00:00");
+	assert_eq!(next(Schedule::from_calendar("quarterly").unwrap(), now), "2027-01-01 00:00:00");
+	assert_eq!(next(Schedule::from_calendar("*:0/20").unwrap(), now), "2026-10-17 10:20:00");
+	assert_eq!(next(Schedule::from_calendar("*-*-* 6,18:00").unwrap(), now), "2026-10-17 18:00:00");
+	assert_eq!(next(Schedule::from_calendar("Mon..Fri *-*-* 01:30:00").unwrap(), now), "2026-10-19 01:30:00");
+	assert_eq!(next(Schedule::from_calendar("Sat,Sun 10:07:45.5").unwrap(), now), "2026-10-17 10:07:45");
+	assert_eq!(next(Schedule::from_calendar("2027-*-01..03 12:00").unwrap(), now), "2027-01-01 12:00:00");
+	assert_eq!(next(Schedule::from_calendar("12-25").unwrap(), now), "2026-12-25 00:00:00");
+	assert!(Schedule::from_calendar("*-*~01").is_err());
+	assert!(Schedule::from_calendar("daily Europe/Berlin").is_err());
+}
+
+#[test]
+fn cron_entries_periodic_scripts_and_timers() {
+	let server = Captured {
+		tree: source("server"),
+		commands: vec![(
+			"systemctl show --property=Id,ActiveState,LastTriggerUSec,Result,ExecMainStatus db-dump.timer db-dump.service \
+			 dnf-makecache.timer dnf-makecache.service fstrim.timer fstrim.service logrotate.timer logrotate.service \
+			 systemd-tmpfiles-clean.timer systemd-tmpfiles-clean.service",
+			Ok("Id=db-dump.timer
+ActiveState=active
+LastTriggerUSec=Fri 2026-10-16 01:30:00 UTC
+Result=success
+
+Id=db-dump.service
+ActiveState=inactive
+Result=exit-code
+ExecMainStatus=2
+LastTriggerUSec=
+
+Id=dnf-makecache.timer
+ActiveState=active
+LastTriggerUSec=Sat 2026-10-17 08:12:44 UTC
+Result=success
+
+Id=dnf-makecache.service
+ActiveState=inactive
+Result=success
+ExecMainStatus=0
+
+Id=fstrim.timer
+ActiveState=inactive
+LastTriggerUSec=n/a
+Result=success
+
+Id=fstrim.service
+ActiveState=inactive
+Result=success
+ExecMainStatus=0
+
+Id=logrotate.timer
+ActiveState=active
+LastTriggerUSec=Sat 2026-10-17 00:00:00 UTC
+Result=success
+
+Id=logrotate.service
+ActiveState=activating
+Result=success
+ExecMainStatus=0
+
+Id=systemd-tmpfiles-clean.timer
+ActiveState=active
+LastTriggerUSec=Fri 2026-10-16 09:40:02 UTC
+Result=success
+
+Id=systemd-tmpfiles-clean.service
+ActiveState=inactive
+Result=success
+ExecMainStatus=0
+"),
+		)],
+		..Default::default()
+	};
+	let tasks = ScheduledTaskInfo::fetch_from(&server).unwrap().tasks;
+	let names: Vec<(&str, &str, &str)> = tasks.iter().map(|t| (t.name.as_str(), t.path.as_str(), t.author.as_str())).collect();
+
+	// raid-check.rpmsave is skipped the way run-parts would skip it
+	assert_eq!(
+		names,
+		[
+			("0hourly", "/etc/cron.d/0hourly:5", "root"),
+			("raid-check", "/etc/cron.d/raid-check:2", "root"),
+			("deploy", "/var/spool/cron/deploy:3", "deploy"),
+			("deploy", "/var/spool/cron/deploy:4", "deploy"),
+			("deploy", "/var/spool/cron/deploy:5", "deploy"),
+			("root", "/var/spool/cron/root:1", "root"),
+			("0anacron", "/etc/cron.hourly/0anacron", "root"),
+			("rkhunter", "/etc/cron.daily/rkhunter", "root"),
+			("aide-check", "/etc/cron.weekly/aide-check", "root"),
+			("db-dump.timer", "/etc/systemd/system/db-dump.timer", "root"),
+			("dnf-makecache.timer", "/usr/lib/systemd/system/dnf-makecache.timer", "root"),
+			("fstrim.timer", "/usr/lib/systemd/system/fstrim.timer", "root"),
+			("logrotate.timer", "/usr/lib/systemd/system/logrotate.timer", "root"),
+			("systemd-tmpfiles-clean.timer", "/usr/lib/systemd/system/systemd-tmpfiles-clean.timer", "root"),
+		]
+	);
+
+	let next_run = |index: usize| NaiveDateTime::parse_from_str(&tasks[index].next_run, "%Y-%m-%d %H:%M:%S").unwrap();
+	// 0 1 * * Sun
+	assert_eq!(next_run(1).weekday(), Weekday::Sun);
+	assert_eq!(next_run(1).time().to_string(), "01:00:00");
+	// 30 2 * * 1-5
+	assert!(!matches!(next_run(3).weekday(), Weekday::Sat | Weekday::Sun));
+	assert_eq!(next_run(3).time().to_string(), "02:30:00");
+	assert_eq!(tasks[3].command, "/opt/app/bin/backup.sh >> /var/log/app-backup.log 2>&1");
+	assert_eq!(tasks[4].next_run, "At system startup");
+	assert_eq!(tasks[5].last_run, "N/A");
+
+	// cron.hourly is run by /etc/cron.d/0hourly at one past, the others by anacron
+	assert_eq!(next_run(6).format("%M:%S").to_string(), "01:00");
+	assert_eq!(tasks[6].last_run, "N/A");
+	assert_eq!(tasks[7].last_run, "2026-10-16");
+	assert_eq!(next_run(7).time().to_string(), "00:00:00");
+	assert_eq!(tasks[8].last_run, "2026-10-11");
+	assert_eq!(tasks[8].command, "/etc/cron.weekly/aide-check");
+
+	let db_dump = &tasks[9];
+	assert_eq!(db_dump.state, "Ready");
+	assert_eq!(db_dump.last_run, "2026-10-16 01:30:00");
+	assert_eq!(db_dump.result, "exit-code (2)");
+	assert_eq!(db_dump.command, "/usr/local/bin/db-dump --all --output /srv/backup");
+	assert!(["01:30:00", "03:00:00"].contains(&next_run(9).time().to_string().as_str()));
+
+	// OnUnitInactiveSec= counts from the last time the timer elapsed
+	let makecache = &tasks[10];
+	assert_eq!(makecache.next_run, "2026-10-17 09:12:44");
+	assert_eq!(makecache.result, "0");
+	assert_eq!(makecache.command, "/usr/bin/dnf makecache --timer");
+
+	let fstrim = &tasks[11];
+	assert_eq!(fstrim.state, "Disabled");
+	assert_eq!(fstrim.last_run, "N/A");
+	assert_eq!(fstrim.next_run, "N/A");
+	assert_eq!(fstrim.result, "N/A");
+
+	assert_eq!(tasks[12].state, "Running");
+	// Enabled by the vendor link in /usr/lib
+	assert_eq!(tasks[13].state, "Ready");
+	assert_eq!(tasks[13].next_run, "2026-10-17 09:40:02");
+}
+
+#[test]
+fn timer_last_run_falls_back_to_persistent_stamps() {
+	// Without a system manager only timers with Persistent=true leave a record of their last run
+	let tasks = ScheduledTaskInfo::fetch_from(&source("server")).unwrap().tasks;
+	let last_run = |name: &str| tasks.iter().find(|t| t.name == name).unwrap().last_run.clone();
+	assert_ne!(last_run("db-dump.timer"), "N/A");
+	assert_ne!(last_run("logrotate.timer"), "N/A");
+	assert_eq!(last_run("dnf-makecache.timer"), "N/A");
+	assert_eq!(tasks.iter().find(|t| t.name == "dnf-makecache.timer").unwrap().next_run, "N/A");
+	assert!(NaiveDate::parse_from_str(&last_run("logrotate.timer")[..10], "%Y-%m-%d").is_ok());
+}
+
+#[test]
+fn journal_events_from_captured_files() {
+	let at = |text: &str| DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc);
+	// The capture has a CRON error a few seconds before this and the rest after it
+	let events = EventLogInfo::fetch_since(&source("workstation"), at("2026-10-17T10:45:52Z")).unwrap();
+	let summary = |events: &[cirg::event_log_info::EventEntry]| {
+		events.iter().map(|e| (e.level.clone(), e.source.clone())).collect::<Vec<_>>()
+	};
+	let expected = |pairs: &[(&str, &str)]| pairs.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect::<Vec<_>>();
+
+	// Newest first; informational entries and those of uid 1000 in the system log are left out
+	assert_eq!(
+		summary(&events.system_events),
+		expected(&[
+			("Error", "backup.sh"),
+			("Critical", "systemd-coredump"),
+			("Critical", "smartd"),
+			("Error", "sshd"),
+			("Warning", "systemd"),
+		])
+	);
+	assert_eq!(summary(&events.application_events), expected(&[("Error", "syncthing"), ("Warning", "gnome-shell")]));
+
+	// The long rsync message is stored zstd-compressed
+	let backup = &events.system_events[0];
+	assert_eq!(backup.time_created, "2026-10-17T10:45:54.528337Z");
+	assert!(backup.message.starts_with("rsync: [sender] send_files failed to open: Permission denied (13): "));
+	assert!(backup.message.ends_with("/home/alex/Documents/projects/report-02/figures/plot-023.svg"));
+	assert!(backup.message.len() > 512);
+
+	let coredump = &events.system_events[1];
+	assert_eq!(coredump.event_id, "fc2e22bc6ee647b6b90729ab34a250b1");
+	assert_eq!(coredump.message.lines().next(), Some("Process 4211 (nautilus) of user 1000 dumped core."));
+	assert_eq!(events.system_events[4].message, "cups.service: Start request repeated too quickly.");
+
+	let earlier = EventLogInfo::fetch_since(&source("workstation"), at("2026-10-17T09:00:00Z")).unwrap();
+	assert_eq!(earlier.system_events.len(), 6);
+	assert_eq!(earlier.system_events[5].source, "CRON");
+	let later = EventLogInfo::fetch_since(&source("workstation"), at("2026-10-18T00:00:00Z")).unwrap();
+	assert!(later.system_events.is_empty() && later.application_events.is_empty());
+}
+
+#[test]
+fn journal_export_when_files_cannot_be_read() {
+	// The journal file on the Pi is cut short, so the collector asks journalctl instead
+	let pi = Captured {
+		tree: source("raspberry-pi"),
+		commands: vec![(
+			"journalctl --output=export --priority=warning --since=@1792224000 --no-pager",
+			Ok("__CURSOR=s=7d2c;i=1a2
+__REALTIME_TIMESTAMP=1792224000123456
+PRIORITY=3
+_TRANSPORT=kernel
+SYSLOG_IDENTIFIER=kernel
+MESSAGE=mmc0: Timeout waiting for hardware interrupt.
+
+__CURSOR=s=7d2c;i=1a3
+__REALTIME_TIMESTAMP=1792227600000000
+PRIORITY=4
+_TRANSPORT=kernel
+SYSLOG_IDENTIFIER=kernel
+MESSAGE
+\x44\0\0\0\0\0\0\0Under-voltage detected! (0x00050005)
+Voltage normalised (0x00000000)
+_BOOT_ID=0b1e6a55c4f84e0d9f3c2a1b0e9d8c7f
+
+__CURSOR=s=7d2c;i=1a4
+__REALTIME_TIMESTAMP=1792229400000000
+_UID=1000
+PRIORITY=4
+SYSLOG_IDENTIFIER=pipewire
+MESSAGE=spa.alsa: hw:0: snd_pcm_avail after recover: Broken pipe
+"),
+		)],
+		..Default::default()
+	};
+	let since = DateTime::from_timestamp(1792224000, 0).unwrap();
+	let events = EventLogInfo::fetch_since(&pi, since).unwrap();
+
+	assert_eq!(events.system_events.len(), 2);
+	let voltage = &events.system_events[0];
+	assert_eq!(voltage.level, "Warning");
+	assert_eq!(voltage.message, "Under-voltage detected! (0x00050005)\nVoltage normalised (0x00000000)");
+	assert_eq!(voltage.time_created, "2026-10-17T09:00:00.000000Z");
+	assert_eq!(events.system_events[1].message, "mmc0: Timeout waiting for hardware interrupt.");
+	assert_eq!(events.application_events[0].source, "pipewire");
+}
+
+#[test]
+fn syslog_events_without_a_journal() {
+	let since = DateTime::parse_from_rfc3339("2026-10-16T12:00:00Z").unwrap().with_timezone(&Utc);
+	let events = EventLogInfo::fetch_since(&source("server"), since).unwrap();
+	let levels: Vec<(&str, &str)> = events.system_events.iter().map(|e| (e.level.as_str(), e.source.as_str())).collect();
+
+	// Without a priority in the file, lines are graded by their wording; the rest are left out
+	assert_eq!(
+		levels,
+		[
+			("Critical", "kernel"),
+			("Warning", "postgres"),
+			("Error", "smartd"),
+			("Error", "sshd"),
+			("Error", "kernel"),
+		]
+	);
+	assert_eq!(events.system_events[2].message, "Device: /dev/sdb [SAT], FAILED SMART self-check. BACK UP DATA NOW!");
+	assert!(events.application_events.is_empty());
+}
+
+#[test]
+fn users_and_groups_with_shadow_sudoers_and_last_logins() {
+	let source = Captured {
+		tree: source("workstation"),
+		commands: vec![(
+			"lastlog",
+			Ok("Username         Port     From             Latest\n\
+				root             tty1                      Sat Oct 17 06:05:00 +0200 2026\n\
+				daemon                                     **Never logged in**\n\
+				alex             pts/0    192.168.1.20     Thu Oct  1 11:00:00 +0200 2026\n"),
+		)],
+		..Captured::default()
+	};
+	let info = UsersGroupsInfo::fetch_from(&source).unwrap();
+	let user = |name: &str| info.users.iter().find(|u| u.name == name).unwrap();
+	let group = |name: &str| info.groups.iter().find(|g| g.name == name).unwrap();
+
+	assert_eq!(info.users.len(), 14);
+	let root = user("root");
+	assert!(root.system_account && root.locked && root.disabled && root.sudo);
+	assert_eq!(root.last_login, "2026-10-17T04:05:00Z");
+
+	// Newest of wtmp, lastlog2 and the lastlog command
+	let alex = user("alex");
+	assert_eq!(alex.description, "Alex Moreau");
+	assert_eq!((alex.uid, alex.home.as_str(), alex.shell.as_str()), (Some(1000), "/home/alex", "/bin/bash"));
+	assert!(!alex.system_account && !alex.locked && !alex.disabled && !alex.password_expired);
+	assert_eq!(alex.password_last_set, "2026-02-16");
+	assert_eq!(alex.account_expires, "");
+	assert!(alex.sudo);
+	assert_eq!(alex.last_login, "2026-10-16T21:44:10Z");
+
+	// Granted through a User_Alias in sudoers.d; 90 days after 2024-10-04 and past its expiry day
+	let sam = user("sam");
+	assert!(sam.sudo && sam.password_expired && sam.disabled && !sam.locked);
+	assert_eq!(sam.account_expires, "2025-07-31");
+	assert_eq!(sam.last_login, "2025-07-28T19:30:45Z");
+
+	// gdm's rule is in a file sudo skips; nobody sits above UID_MAX
+	assert!(!user("gdm").sudo && user("gdm").last_login.is_empty());
+	assert!(user("nobody").system_account);
+	assert!(user("systemd-network").locked);
+
+	assert_eq!(group("sudo").members, ["alex"]);
+	assert_eq!(group("alex").members, ["alex"]);
+	assert!(!group("alex").system_group && group("docker").system_group);
+	let developers = group("developers");
+	assert_eq!(developers.gid, Some(1002));
+	assert_eq!(developers.members, ["sam", "alex"]);
+	assert_eq!(developers.administrators, ["alex"]);
+	assert_eq!(group("nogroup").members, ["sync", "sshd", "nobody"]);
+}
+
+#[test]
+fn update_history_from_package_manager_logs_and_kernels() {
+	// Kernel images first, then apt transactions newest first, including the gzipped rotation; the
+	// apt-mark run changed no packages
+	let hotfixes = HotfixInfo::fetch_from(&source("workstation")).unwrap().hotfixes;
+	let mut kernels: Vec<_> = hotfixes[..2].iter().map(|h| (h.hotfix_id.as_str(), h.description.as_str())).collect();
+	kernels.sort();
+	assert_eq!(kernels, [("6.8.0-41-generic", "Kernel image"), ("6.8.0-45-generic", "Kernel image, running")]);
+	assert!(NaiveDateTime::parse_from_str(&hotfixes[0].installed_on, "%Y-%m-%d %H:%M:%S").is_ok());
+	let transactions: Vec<_> = hotfixes[2..]
+		.iter()
+		.map(|h| (h.hotfix_id.as_str(), h.description.as_str(), h.installed_by.as_str(), h.installed_on.as_str()))
+		.collect();
+	assert_eq!(
+		transactions,
+		[
+			("apt purge thunderbird", "Remove, failed", "sam", "2026-10-14 18:40:02"),
+			("apt-get -y upgrade", "Install, Upgrade", "alex", "2026-10-12 06:25:31"),
+			("/usr/bin/unattended-upgrade", "Upgrade", "", "2026-10-01 07:00:12"),
+			("apt install htop", "Install", "alex", "2026-09-20 12:05:10"),
+		]
+	);
+	assert_eq!(
+		hotfixes[3].packages,
+		[
+			"linux-image-6.8.0-45-generic:amd64 6.8.0-45.45",
+			"linux-modules-6.8.0-45-generic:amd64 6.8.0-45.45",
+			"linux-image-generic:amd64 6.8.0.41.41 -> 6.8.0.45.45",
+			"curl:amd64 8.5.0-2ubuntu10.3 -> 8.5.0-2ubuntu10.4",
+		]
+	);
+
+	// dnf's database holds UTC timestamps and user IDs
+	let hotfixes = HotfixInfo::fetch_from(&source("server")).unwrap().hotfixes;
+	let local = |time: &str| {
+		let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").unwrap().and_utc();
+		time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string()
+	};
+	assert_eq!(hotfixes.len(), 5);
+	assert_eq!(hotfixes[0].hotfix_id, "5.14.0-427.13.1.el9_4.x86_64");
+	assert_eq!(hotfixes[0].description, "Kernel image, running");
+	let transactions: Vec<_> = hotfixes[1..]
+		.iter()
+		.map(|h| (h.hotfix_id.as_str(), h.description.as_str(), h.installed_by.as_str(), h.installed_on.clone()))
+		.collect();
+	assert_eq!(
+		transactions,
+		[
+			("dnf remove nano", "Remove", "root", local("2026-10-15 11:00:00")),
+			("dnf downgrade postgresql-server", "Downgrade, failed", "deploy", local("2026-10-14 11:30:00")),
+			("dnf upgrade -y", "Install, Upgrade", "deploy", local("2026-10-13 02:15:00")),
+			("dnf install tmux nano", "Install", "root", local("2026-08-03 12:00:05")),
+		]
+	);
+	assert_eq!(hotfixes[2].packages, ["postgresql-server.x86_64 13.14-1.el9_3 -> 13.11-1.el9_2"]);
+	assert_eq!(
+		hotfixes[3].packages,
+		[
+			"kernel.x86_64 5.14.0-427.13.1.el9_4",
+			"kernel-core.x86_64 5.14.0-427.13.1.el9_4",
+			"openssl.x86_64 1:3.0.7-25.el9_3 -> 1:3.0.7-27.el9",
+		]
+	);
+
+	// pacman logs wall times and no user; the 2018 entry predates transaction lines
+	let hotfixes = HotfixInfo::fetch_from(&source("raspberry-pi")).unwrap().hotfixes;
+	let transactions: Vec<_> = hotfixes
+		.iter()
+		.map(|h| (h.hotfix_id.as_str(), h.description.as_str(), h.installed_on.as_str(), h.packages.len()))
+		.collect();
+	assert_eq!(
+		transactions,
+		[
+			("pacman -Rs cups", "Remove, failed", "2026-10-16 08:45:04", 1),
+			("pacman -Syu", "Install, Upgrade", "2026-10-10 19:02:31", 3),
+			("pacman -S vim", "Install", "2018-05-14 09:12:00", 1),
+		]
+	);
+	assert_eq!(hotfixes[1].packages[1], "linux-rpi 6.6.51-1 -> 6.6.56-1");
+}
+
+#[test]
+fn environment_sources_in_the_order_they_apply() {
+	let info = EnvironmentInfo::fetch_from(&source("workstation")).unwrap();
+	let entries: Vec<_> = info
+		.entries
+		.iter()
+		.map(|e| (e.name.as_str(), e.value.as_str(), e.source.as_str(), e.overridden))
+		.collect();
+	let path = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin:/usr/games:/usr/local/games:/snap/bin";
+	let java_path = format!("{}:/usr/lib/jvm/java-21-openjdk-amd64/bin", path);
+	// The packaged qt-a11y file is masked by the empty one in /etc and the /etc editor file by
+	// sam's own. Files apply in name order, so the /etc EDITOR replaces alex's, and pam_env's PATH
+	// replaces the one alex's session started with
+	assert_eq!(
+		entries,
+		[
+			("PATH", "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin", "systemd manager", true),
+			("LANG", "en_US.UTF-8", "/etc/systemd/system.conf", true),
+			("MOZ_ENABLE_WAYLAND", "1", "/etc/systemd/system.conf", false),
+			(
+				"PATH",
+				"/home/alex/.local/bin:/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin",
+				"/home/alex/.config/environment.d/10-paths.conf",
+				true,
+			),
+			("EDITOR", "nvim", "/home/alex/.config/environment.d/10-paths.conf", true),
+			("EDITOR", "nano", "/etc/environment.d/50-editor.conf", false),
+			("VISUAL", "nano", "/etc/environment.d/50-editor.conf", false),
+			("EDITOR", "micro", "/home/sam/.config/environment.d/50-editor.conf", false),
+			("VISUAL", "micro", "/home/sam/.config/environment.d/50-editor.conf", false),
+			("PATH", path, "/etc/environment", true),
+			("JAVA_HOME", "/usr/lib/jvm/java-21-openjdk-amd64", "/etc/profile.d/jdk.sh", false),
+			("PATH", java_path.as_str(), "/etc/profile.d/jdk.sh", false),
+			("LANG", "C.UTF-8", "/etc/profile.d/locale.sh", false),
+			("PROMPT_COMMAND", r#"printf "\033]0;%s\007" "$PWD""#, "/etc/profile.d/locale.sh", false),
+		]
+	);
+	assert_eq!(info.variables["EDITOR"], "nano");
+	assert_eq!(info.variables["PATH"], java_path);
+	assert_eq!(info.variables.len(), 7);
+
+	// The running manager replaces DefaultEnvironment=
+	let source = Captured {
+		tree: source("workstation"),
+		commands: vec![(
+			"systemctl show-environment",
+			Ok("LANG=de_DE.UTF-8\nPATH=/usr/local/bin:/usr/bin:/bin\nBANNER=$'Line one\\nIt\\'s two'\n"),
+		)],
+		..Captured::default()
+	};
+	let info = EnvironmentInfo::fetch_from(&source).unwrap();
+	let manager: Vec<_> = info.entries.iter().take_while(|e| e.source == "systemd manager").map(|e| e.name.as_str()).collect();
+	assert_eq!(manager, ["LANG", "PATH", "BANNER"]);
+	assert_eq!(info.variables["BANNER"], "Line one\nIt's two");
+	assert!(!info.variables.contains_key("MOZ_ENABLE_WAYLAND"));
+}
+
+#[test]
+fn live_processes_come_with_their_ancestors() {
+	let processes = ProcessInfo::fetch().unwrap().processes;
+	let pids: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+
+	for process in &processes {
+		// Unless it exited since, a parent is listed and started no later than its child
+		if let Some(parent) = process.parent_pid
+			&& std::path::Path::new(&format!("/proc/{}", parent)).exists()
+		{
+			assert!(pids.contains(&parent), "parent {} of {} is missing", parent, process.pid);
+			let parent = processes.iter().find(|p| p.pid == parent).unwrap();
+			assert!(parent.start_time <= process.start_time);
+		}
+		assert!(process.threads.is_some_and(|threads| threads >= 1));
+	}
+	// Every chain ends at a root such as init
+	assert!(processes.iter().any(|p| p.parent_pid.is_none()));
+	assert!(processes.iter().any(|p| !p.user.is_empty()));
+}
+
+#[test]
+fn process_options_rank_and_limit_the_live_process_table() {
+	assert_eq!("start-time".parse::<ProcessSort>().unwrap(), ProcessSort::StartTime);
+	assert_eq!("IO".parse::<ProcessSort>().unwrap(), ProcessSort::Io);
+	assert!("disk".parse::<ProcessSort>().is_err());
+	assert_eq!("all".parse::<ProcessCount>().unwrap(), ProcessCount::All);
+	assert_eq!(" 10 ".parse::<ProcessCount>().unwrap(), ProcessCount::Top(10));
+	assert!("-1".parse::<ProcessCount>().is_err());
+
+	let all = ProcessInfo::fetch_with_options(ProcessOptions { sort: ProcessSort::StartTime, count: ProcessCount::All }).unwrap();
+	let me = all.processes.iter().find(|p| p.pid == std::process::id()).expect("the test process is listed");
+	assert!(me.threads.unwrap() >= 1);
+	assert!(all.processes.windows(2).all(|pair| pair[0].start_time >= pair[1].start_time));
+	assert!(all.processes.iter().all(|p| p.cpu_percent >= 0.0 && p.read_bytes_per_sec >= 0.0 && p.write_bytes_per_sec >= 0.0));
+
+	// Only the newest process, followed by its ancestors up to the root
+	let newest = ProcessInfo::fetch_with_options(ProcessOptions { sort: ProcessSort::StartTime, count: ProcessCount::Top(1) }).unwrap();
+	let (first, rest) = newest.processes.split_first().unwrap();
+	let mut chain = 0;
+	let mut parent = first.parent_pid;
+	while let Some(process) = parent.and_then(|pid| rest.iter().find(|p| p.pid == pid)) {
+		chain += 1;
+		parent = process.parent_pid;
+	}
+	assert_eq!(chain, rest.len());
+	assert!(rest.len() < all.processes.len());
+}
+
+#[test]
+fn sections_without_a_backend_are_unsupported() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let report = runtime.block_on(cirg::collect_from(Arc::new(source("workstation")), &[Section::Computer, Section::Environment]));
+	assert_eq!(report.computer.status, SectionStatus::Ok);
+	assert_eq!(report.environment.status, SectionStatus::Ok);
+	assert_eq!(report.outcome(Section::Cpu).status, SectionStatus::Skipped);
+
+	let other = ReplaySource::new(Fixture { platform: Platform::Other, ..Fixture::default() });
+	let report = runtime.block_on(cirg::collect_from(Arc::new(other), &[Section::Environment, Section::Hotfix]));
+	assert_eq!(report.environment.status, SectionStatus::Unsupported);
+	assert_eq!(report.hotfix.status, SectionStatus::Unsupported);
+}
+
+#[test]
+fn cpu_topology_with_smt() {
+	let cpu = CpuInfo::fetch_from(&source("workstation")).unwrap();
+
+	assert_eq!(cpu.name, "AMD Ryzen 7 5800X 8-Core Processor");
+	assert_eq!(cpu.socket, "AM4");
+	assert_eq!(cpu.cores, 8);
+	assert_eq!(cpu.logical_processors, 16);
+	assert_eq!(cpu.max_clock_mhz, 4850);
+	assert_eq!(cpu.current_clock_mhz, 3000);
+	assert_eq!(cpu.l2_cache_kb, 8 * 512);
+	assert_eq!(cpu.l3_cache_kb, 32 * 1024);
+	assert_eq!(cpu.architecture, "x64");
+	assert!(cpu.virtualization);
+	// A captured /proc/stat is a single snapshot, so there is no load to take from it
+	assert_eq!(cpu.load_pct, None);
+
+	let live = CpuInfo::fetch().unwrap();
+	assert!(live.load_pct.is_some_and(|load| (0.0..=100.0).contains(&load)));
+}
+
+#[test]
+fn cpu_packages_without_cpufreq() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let report = runtime.block_on(cirg::collect_from(Arc::new(source("server")), &[Section::Cpu]));
+	let cpu = report.cpu.data.unwrap();
+
+	assert_eq!(cpu.cores, 4);
+	assert_eq!(cpu.logical_processors, 4);
+	assert_eq!(cpu.max_clock_mhz, 2100);
+	assert_eq!(cpu.current_clock_mhz, 2100);
+	assert_eq!(cpu.l2_cache_kb, 4 * 1024);
+	assert_eq!(cpu.l3_cache_kb, 2 * 36608);
+	assert!(cpu.virtualization);
+	assert_eq!(report.cpu.warnings.len(), 2);
+	assert_eq!(report.cpu.warnings[1], "CPU load is only sampled on the live machine");
+}
+
+#[test]
+fn cpu_on_arm_without_cache_info() {
+	let cpu = CpuInfo::fetch_from(&source("raspberry-pi")).unwrap();
+
+	assert_eq!(cpu.name, "BCM2835");
+	assert_eq!(cpu.cores, 4);
+	assert_eq!(cpu.logical_processors, 4);
+	assert_eq!(cpu.max_clock_mhz, 1800);
+	assert_eq!(cpu.current_clock_mhz, 600);
+	assert_eq!(cpu.l2_cache_kb, 0);
+	assert_eq!(cpu.architecture, "ARM64");
+	assert!(!cpu.virtualization);
+}
@@ -0,0 +1,150 @@
+use cirg::{ComputerInfoExt, Section, SectionStatus};
+use cirg::cpu_info::CpuInfo;
+use cirg::gpu_info::GpuInfo;
+use cirg::memory_info::MemoryInfo;
+use cirg::power_info::PowerInfo;
+use cirg::security_info::SecurityInfo;
+use cirg::source::ReplaySource;
+use cirg::usb_info::UsbInfo;
+use std::sync::Arc;
+
+fn source() -> ReplaySource {
+	ReplaySource::load(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/windows-desktop.json"))
+		.expect("fixture should load")
+}
+
+#[test]
+fn cpu_load_from_the_recorded_counter() {
+	let cpu = CpuInfo::fetch_from(&source()).unwrap();
+
+	assert_eq!(cpu.name, "11th Gen Intel(R) Core(TM) i7-1185G7 @ 3.00GHz");
+	assert_eq!(cpu.logical_processors, 8);
+	assert_eq!(cpu.load_pct, Some(23.0));
+}
+
+#[test]
+fn memory_type_and_form_factor_codes() {
+	let memory = MemoryInfo::fetch_from(&source()).unwrap();
+
+	assert_eq!(memory.total_slots, 4);
+	assert_eq!(memory.max_capacity_gb, 128);
+	assert_eq!(memory.slots.len(), 2);
+	assert_eq!(memory.slots[0].memory_type, "DDR4");
+	assert_eq!(memory.slots[0].form_factor, "DIMM");
+	assert_eq!(memory.slots[0].capacity_gb, 16.0);
+	assert_eq!(memory.slots[0].part_number, "KF432C16BB/16");
+	assert_eq!(memory.slots[1].memory_type, "DDR5");
+	assert_eq!(memory.slots[1].form_factor, "SO-DIMM");
+}
+
+#[test]
+fn memory_rank_voltage_and_ecc() {
+	let memory = MemoryInfo::fetch_from(&source()).unwrap();
+
+	assert_eq!(memory.error_correction, "Multi-bit ECC");
+	assert_eq!(memory.slots[0].serial_number, "1A2B3C4D");
+	assert_eq!(memory.slots[0].rank, 2);
+	assert_eq!(memory.slots[0].configured_speed_mhz, 3200);
+	assert_eq!(memory.slots[0].voltage_mv, 1350);
+	assert!(memory.slots[0].ecc);
+	assert!(!memory.slots[1].ecc);
+}
+
+#[test]
+fn memory_totals_come_from_the_recording() {
+	let usage = MemoryInfo::fetch_from(&source()).unwrap().usage;
+
+	assert_eq!(usage.total_mb, 32608);
+	assert_eq!(usage.available_mb, 18432);
+	assert_eq!(usage.used_mb, 14176);
+	assert_eq!(usage.swap_total_mb, 4864);
+	assert_eq!(usage.swap_used_mb, 312);
+}
+
+#[test]
+fn firewall_profiles_are_decoded() {
+	let security = SecurityInfo::fetch_from(&source()).unwrap();
+	let firewall = security.firewall.expect("firewall profiles recorded");
+
+	assert_eq!(firewall.domain_enabled, Some(true));
+	assert_eq!(firewall.domain_inbound.as_deref(), Some("Block"));
+	assert_eq!(firewall.domain_outbound.as_deref(), Some("Allow"));
+	assert_eq!(firewall.private_enabled, Some(false));
+	assert_eq!(firewall.private_inbound.as_deref(), Some("Allow"));
+	assert_eq!(firewall.private_outbound.as_deref(), Some("Block"));
+	assert_eq!(firewall.public_enabled, None);
+	assert!(security.tpm.is_none());
+	assert!(security.pending_updates.is_none());
+}
+
+#[test]
+fn vram_prefers_registry_and_falls_back_to_adapter_ram() {
+	let gpu = GpuInfo::fetch_from(&source()).unwrap();
+
+	assert_eq!(gpu.adapters[0].adapter_ram_mb, 10240);
+	assert_eq!(gpu.adapters[0].resolution, "2560x1440");
+	assert_eq!(gpu.adapters[0].availability, "Running/Full Power");
+	assert_eq!(gpu.adapters[0].driver_date, "2024-03-01");
+	assert_eq!(gpu.adapters[1].adapter_ram_mb, 1024);
+	assert_eq!(gpu.adapters[1].resolution, "N/A");
+	assert_eq!(gpu.adapters[1].availability, "Off Line");
+}
+
+#[test]
+fn power_plan_and_battery_chemistry() {
+	let power = PowerInfo::fetch_from(&source()).unwrap();
+	let battery = power.battery.expect("battery recorded");
+
+	assert_eq!(power.plan, "Balanced");
+	assert_eq!(battery.chemistry, "Lithium-ion");
+	assert_eq!(battery.charge_pct, "87");
+	assert_eq!(battery.design_capacity, "Unknown");
+	assert_eq!(battery.cycle_count, "Unknown");
+	assert_eq!(power.power_source, "AC");
+}
+
+#[test]
+fn monitors_matched_to_displays_by_device_path() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let report = runtime.block_on(cirg::collect_from(Arc::new(source()), &[Section::Monitor]));
+	let monitors = report.monitor.data.unwrap().monitors;
+
+	// WMI lists the closed laptop panel first; it has no desktop mode and no readable EDID
+	assert_eq!(monitors[0].manufacturer, "SDC");
+	assert_eq!(monitors[0].connection_type, "DisplayPort (embedded)");
+	assert_eq!(monitors[0].resolution, "N/A");
+	assert_eq!(report.monitor.warnings.len(), 1);
+
+	assert_eq!(monitors[1].name, "LG FULL HD");
+	assert_eq!(monitors[1].connector, r"\\.\DISPLAY2");
+	assert_eq!(monitors[1].resolution, "1920x1080");
+	assert_eq!(monitors[1].connection_type, "HDMI");
+	assert_eq!(monitors[1].bit_depth, 12);
+
+	assert_eq!(monitors[2].name, "DELL S2721DGF");
+	assert_eq!(monitors[2].connector, r"\\.\DISPLAY1");
+	assert_eq!(monitors[2].resolution, "2560x1440");
+	assert_eq!(monitors[2].refresh_rate, 165);
+	assert_eq!(monitors[2].native_resolution, "2560x1440");
+	assert_eq!(monitors[2].diagonal_inches, 27.0);
+	assert_eq!(monitors[2].hdr_formats, ["HDR10"]);
+}
+
+#[test]
+fn processes_are_not_taken_from_the_replaying_machine() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let report = runtime.block_on(cirg::collect_from(Arc::new(source()), &[Section::Process]));
+
+	assert_eq!(report.process.status, SectionStatus::Unsupported);
+	assert!(report.process.data.is_none());
+}
+
+#[test]
+fn usb_ids_from_pnp_device_id() {
+	let devices = UsbInfo::fetch_from(&source()).unwrap().devices;
+
+	assert_eq!(devices.len(), 1);
+	assert_eq!(devices[0].name, "Logitech USB Input Device");
+	assert_eq!(devices[0].vendor_id, "046D");
+	assert_eq!(devices[0].product_id, "C52B");
+}
@@ -8,6 +8,10 @@ interface Props {
     data: AllSystemInfo;
 }
 
+function formatMb(mb: number): string {
+    return mb >= 1024 ? `${(mb / 1024).toFixed(1)} GB` : `${mb} MB`;
+}
+
 export default function HardwareSection({data}: Props) {
     const {cpu, gpu, memory} = data;
 
@@ -30,7 +34,7 @@ export default function HardwareSection({data}: Props) {
                         <DataField label="Architecture" value={cpu.architecture}/>
                         <DataField label="Virtualization" value={cpu.virtualization}/>
                         <DataField label="Status" value={cpu.status}/>
-                        <DataField label="Load %" value={`${cpu.load_pct.toFixed(2)}%`}/>
+                        <DataField label="Load %" value={cpu.load_pct !== null ? `${cpu.load_pct.toFixed(2)}%` : "N/A"}/>
                     </div>
                 </GlassCard>
             )}
@@ -43,23 +47,57 @@ export default function HardwareSection({data}: Props) {
                             <GlassCard key={i}>
                                 <h4 className="text-sm font-semibold text-foreground/80 mb-3">{adapter.name}</h4>
                                 <div className="grid grid-cols-2 gap-3">
+                                    <DataField label="Driver" value={adapter.driver}/>
                                     <DataField label="Driver Version" value={adapter.driver_version}/>
                                     <DataField label="Driver Date" value={adapter.driver_date}/>
                                     <DataField label="VRAM (MB)" value={adapter.adapter_ram_mb}/>
                                     <DataField label="Resolution" value={adapter.resolution}/>
                                     <DataField label="Refresh Rate" value={`${adapter.refresh_rate} Hz`}/>
                                     <DataField label="Status" value={adapter.status}/>
+                                    <DataField label="PCI ID" value={adapter.pci_id}/>
                                 </div>
+                                {adapter.outputs.length > 0 && (
+                                    <div className="mt-3 space-y-1">
+                                        {adapter.outputs.map((output, j) => (
+                                            <div key={j} className="flex justify-between text-xs text-foreground/60">
+                                                <span>{output.connector}</span>
+                                                <span>
+                                                    {output.resolution}
+                                                    {output.refresh_rate > 0 && ` @ ${output.refresh_rate} Hz`}
+                                                </span>
+                                            </div>
+                                        ))}
+                                    </div>
+                                )}
                             </GlassCard>
                         ))}
                     </div>
                 </div>
             )}
 
+            {memory && (
+                <GlassCard className="mb-6">
+                    <h3 className="text-sm font-semibold text-foreground/60 mb-3">Memory Usage</h3>
+                    <div className="grid grid-cols-2 sm:grid-cols-3 lg:grid-cols-4 gap-4">
+                        <DataField label="Total" value={formatMb(memory.usage.total_mb)}/>
+                        <DataField label="Available" value={formatMb(memory.usage.available_mb)}/>
+                        <DataField label="Used" value={`${formatMb(memory.usage.used_mb)} (${memory.usage.used_pct.toFixed(1)}%)`}/>
+                        <DataField label="Cached" value={formatMb(memory.usage.cached_mb)}/>
+                        <DataField label="Buffers" value={formatMb(memory.usage.buffers_mb)}/>
+                        <DataField label="Swap / Pagefile"
+                                   value={`${formatMb(memory.usage.swap_used_mb)} / ${formatMb(memory.usage.swap_total_mb)}`}/>
+                        {memory.usage.commit_limit_mb !== null && (
+                            <DataField label="Commit"
+                                       value={`${formatMb(memory.usage.committed_mb ?? 0)} / ${formatMb(memory.usage.commit_limit_mb)}`}/>
+                        )}
+                    </div>
+                </GlassCard>
+            )}
+
             {memory && memory.slots.length > 0 && (
                 <GlassCard>
                     <h3 className="text-sm font-semibold text-foreground/60 mb-3">
-                        Memory ({memory.slots.length}/{memory.total_slots} slots, max {memory.max_capacity_gb} GB)
+                        Memory ({memory.slots.length}/{memory.total_slots} slots, max {memory.max_capacity_gb} GB, {memory.error_correction} error correction)
                     </h3>
                     <Table aria-label="Memory slots" removeWrapper classNames={{
                         th: "bg-[#1f1f1f] text-foreground/60 text-xs font-medium uppercase tracking-wide",
@@ -70,18 +108,22 @@ export default function HardwareSection({data}: Props) {
                             <TableColumn>Capacity</TableColumn>
                             <TableColumn>Speed</TableColumn>
                             <TableColumn>Type</TableColumn>
+                            <TableColumn>Rank</TableColumn>
                             <TableColumn>Manufacturer</TableColumn>
                             <TableColumn>Part Number</TableColumn>
+                            <TableColumn>Serial</TableColumn>
                         </TableHeader>
                         <TableBody>
                             {memory.slots.map((slot, i) => (
                                 <TableRow key={i}>
                                     <TableCell>{slot.bank_label}</TableCell>
                                     <TableCell>{slot.capacity_gb} GB</TableCell>
-                                    <TableCell>{slot.speed_mhz} MHz</TableCell>
-                                    <TableCell>{slot.memory_type}</TableCell>
+                                    <TableCell>{slot.configured_speed_mhz || slot.speed_mhz} / {slot.speed_mhz} MHz</TableCell>
+                                    <TableCell>{slot.memory_type}{slot.ecc ? " ECC" : ""}</TableCell>
+                                    <TableCell>{slot.rank || "-"}</TableCell>
                                     <TableCell>{slot.manufacturer}</TableCell>
                                     <TableCell>{slot.part_number}</TableCell>
+                                    <TableCell>{slot.serial_number}</TableCell>
                                 </TableRow>
                             ))}
                         </TableBody>
@@ -18,7 +18,7 @@ function formatUptime(seconds: number): string {
 export default function OverviewSection({data}: Props) {
     const {computer, cpu, memory, disk} = data;
 
-    const totalMemoryGb = memory?.slots.reduce((sum, s) => sum + s.capacity_gb, 0) ?? 0;
+    const totalMemoryGb = (memory?.usage.total_mb ?? 0) / 1024;
     const totalDiskGb = disk?.logical_disks.reduce((sum, d) => sum + d.total_gb, 0) ?? 0;
     const usedDiskGb = disk?.logical_disks.reduce((sum, d) => sum + d.used_gb, 0) ?? 0;
     const diskUsagePct = totalDiskGb > 0 ? (usedDiskGb / totalDiskGb) * 100 : 0;
@@ -36,7 +36,7 @@ export default function OverviewSection({data}: Props) {
                         {computer?.operating_system.name} {computer?.operating_system.version}
                     </p>
                     <p className="text-foreground/40 text-xs">
-                        {computer?.manufacturer} - {computer?.system_type}
+                        {computer?.manufacturer} {computer?.model} - {computer?.system_type}
                     </p>
                     {computer && (
                         <p className="text-foreground/40 text-xs mt-1">
@@ -59,15 +59,15 @@ export default function OverviewSection({data}: Props) {
                 </GlassCard>
 
                 <GlassCard>
-                    <DataField label="Memory" value={totalMemoryGb > 0 ? `${totalMemoryGb} GB` : undefined}/>
+                    <DataField label="Memory" value={totalMemoryGb > 0 ? `${totalMemoryGb.toFixed(1)} GB` : undefined}/>
                     <div className="mt-3">
                         <div className="flex justify-between text-xs text-foreground/40 mb-1">
-                            <span>Slots Used</span>
-                            <span>{memory?.slots.length ?? 0} / {memory?.total_slots ?? 0}</span>
+                            <span>Used</span>
+                            <span>{(memory?.usage.used_pct ?? 0).toFixed(1)}%</span>
                         </div>
                         <Progress
-                            value={memory ? (memory.slots.length / memory.total_slots) * 100 : 0}
-                            color="primary" size="sm"
+                            value={memory?.usage.used_pct ?? 0}
+                            color={(memory?.usage.used_pct ?? 0) > 90 ? "danger" : "primary"} size="sm"
                         />
                     </div>
                 </GlassCard>
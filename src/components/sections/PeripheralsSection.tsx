@@ -2,12 +2,23 @@ import SectionHeader from "../shared/SectionHeader";
 import GlassCard from "../shared/GlassCard";
 import DataField from "../shared/DataField";
 import {Table, TableHeader, TableColumn, TableBody, TableRow, TableCell} from "@heroui/react";
-import type {AllSystemInfo} from "../../types/system-info";
+import type {AllSystemInfo, UsbDevice} from "../../types/system-info";
 
 interface Props {
     data: AllSystemInfo;
 }
 
+function usbDepth(device: UsbDevice, devices: UsbDevice[]): number {
+    let depth = 0;
+    let parent = devices.find(d => d.device_id === device.parent);
+    while (parent && depth < 8) {
+        depth++;
+        const id = parent.parent;
+        parent = devices.find(d => d.device_id === id);
+    }
+    return depth;
+}
+
 export default function PeripheralsSection({data}: Props) {
     const {monitor, audio, usb} = data;
 
@@ -27,8 +38,18 @@ export default function PeripheralsSection({data}: Props) {
                                     <DataField label="Year" value={m.year_of_manufacture}/>
                                     <DataField label="Resolution" value={m.resolution}/>
                                     <DataField label="Refresh Rate" value={m.refresh_rate ? `${m.refresh_rate} Hz` : "N/A"}/>
+                                    <DataField label="Native" value={m.native_resolution || "N/A"}/>
+                                    <DataField label="Connection" value={m.connection_type || "Unknown"}/>
+                                    <DataField label="Size" value={m.diagonal_inches ? `${m.diagonal_inches}" (${m.width_mm} × ${m.height_mm} mm)` : "N/A"}/>
+                                    <DataField label="Color Depth" value={m.bit_depth ? `${m.bit_depth}-bit` : "N/A"}/>
+                                    <DataField label="HDR" value={m.hdr_formats.length > 0 ? m.hdr_formats.join(", ") : "None"}/>
                                     <DataField label="Serial" value={m.serial_number}/>
                                 </div>
+                                {m.supported_modes.length > 0 && (
+                                    <p className="mt-3 text-xs text-foreground/50">
+                                        {m.supported_modes.length} supported modes, up to {m.supported_modes[0]}
+                                    </p>
+                                )}
                             </GlassCard>
                         ))}
                     </div>
@@ -45,6 +66,11 @@ export default function PeripheralsSection({data}: Props) {
                                 <div className="grid grid-cols-2 gap-3">
                                     <DataField label="Manufacturer" value={d.manufacturer}/>
                                     <DataField label="Status" value={d.status}/>
+                                    {d.driver && <DataField label="Driver" value={d.driver}/>}
+                                    {d.codecs.length > 0 && <DataField label="Codec" value={d.codecs.join(", ")}/>}
+                                    {(d.playback_devices > 0 || d.capture_devices > 0) && (
+                                        <DataField label="PCM Devices" value={`${d.playback_devices} playback, ${d.capture_devices} capture`}/>
+                                    )}
                                 </div>
                             </GlassCard>
                         ))}
@@ -61,14 +87,21 @@ export default function PeripheralsSection({data}: Props) {
                     }}>
                         <TableHeader>
                             <TableColumn>Name</TableColumn>
+                            <TableColumn>ID</TableColumn>
                             <TableColumn>Manufacturer</TableColumn>
+                            <TableColumn>Class</TableColumn>
+                            <TableColumn>Speed</TableColumn>
                             <TableColumn>Status</TableColumn>
                         </TableHeader>
                         <TableBody>
                             {usb.devices.map((d, i) => (
                                 <TableRow key={i}>
-                                    <TableCell>{d.name}</TableCell>
+                                    {/* Devices come in tree order; indent each one under its hub */}
+                                    <TableCell style={{paddingLeft: `${0.75 + usbDepth(d, usb.devices) * 1.25}rem`}}>{d.name}</TableCell>
+                                    <TableCell>{d.vendor_id && `${d.vendor_id}:${d.product_id}`}</TableCell>
                                     <TableCell>{d.manufacturer}</TableCell>
+                                    <TableCell>{d.device_class}</TableCell>
+                                    <TableCell>{d.speed}</TableCell>
                                     <TableCell>{d.status}</TableCell>
                                 </TableRow>
                             ))}
@@ -26,13 +26,19 @@ export default function SecuritySection({data}: Props) {
                             <StatusChip status={security.uac ? "ok" : "warning"}
                                         label={`UAC: ${security.uac ? "ON" : "OFF"}`}/>
                             <StatusChip status={security.bit_locker ? "ok" : "info"}
-                                        label={`BitLocker: ${security.bit_locker ? "ON" : "OFF"}`}/>
+                                        label={`${security.encrypted_volumes.length > 0 ? "LUKS" : "BitLocker"}: ${security.bit_locker ? "ON" : "OFF"}`}/>
                             <StatusChip status={security.rdp_enabled ? "warning" : "ok"}
                                         label={`RDP: ${security.rdp_enabled ? "Enabled" : "Disabled"}`}/>
                         </div>
 
                         <div className="grid grid-cols-2 sm:grid-cols-3 gap-3">
                             <DataField label="Antivirus" value={security.antivirus ?? "Not detected"}/>
+                            {security.mandatory_access_control && (
+                                <DataField label="Access Control" value={security.mandatory_access_control}/>
+                            )}
+                            {security.encrypted_volumes.length > 0 && (
+                                <DataField label="Encrypted Volumes" value={security.encrypted_volumes.join(", ")}/>
+                            )}
                         </div>
                     </GlassCard>
 
@@ -110,26 +116,34 @@ export default function SecuritySection({data}: Props) {
                     {security.firewall && (
                         <GlassCard className="mb-6">
                             <h3 className="text-sm font-semibold text-foreground/60 mb-3">Firewall</h3>
-                            <div className="grid grid-cols-1 sm:grid-cols-3 gap-4">
-                                <div>
-                                    <p className="text-xs font-semibold text-foreground/60 mb-2">Domain</p>
-                                    <DataField label="Enabled" value={security.firewall.domain_enabled}/>
-                                    <DataField label="Inbound" value={security.firewall.domain_inbound}/>
-                                    <DataField label="Outbound" value={security.firewall.domain_outbound}/>
+                            {security.firewall.backend ? (
+                                <div className="grid grid-cols-2 sm:grid-cols-3 gap-3">
+                                    <DataField label="Backend" value={security.firewall.backend}/>
+                                    <DataField label="Inbound" value={security.firewall.inbound}/>
+                                    <DataField label="Outbound" value={security.firewall.outbound}/>
                                 </div>
-                                <div>
-                                    <p className="text-xs font-semibold text-foreground/60 mb-2">Private</p>
-                                    <DataField label="Enabled" value={security.firewall.private_enabled}/>
-                                    <DataField label="Inbound" value={security.firewall.private_inbound}/>
-                                    <DataField label="Outbound" value={security.firewall.private_outbound}/>
+                            ) : (
+                                <div className="grid grid-cols-1 sm:grid-cols-3 gap-4">
+                                    <div>
+                                        <p className="text-xs font-semibold text-foreground/60 mb-2">Domain</p>
+                                        <DataField label="Enabled" value={security.firewall.domain_enabled}/>
+                                        <DataField label="Inbound" value={security.firewall.domain_inbound}/>
+                                        <DataField label="Outbound" value={security.firewall.domain_outbound}/>
+                                    </div>
+                                    <div>
+                                        <p className="text-xs font-semibold text-foreground/60 mb-2">Private</p>
+                                        <DataField label="Enabled" value={security.firewall.private_enabled}/>
+                                        <DataField label="Inbound" value={security.firewall.private_inbound}/>
+                                        <DataField label="Outbound" value={security.firewall.private_outbound}/>
+                                    </div>
+                                    <div>
+                                        <p className="text-xs font-semibold text-foreground/60 mb-2">Public</p>
+                                        <DataField label="Enabled" value={security.firewall.public_enabled}/>
+                                        <DataField label="Inbound" value={security.firewall.public_inbound}/>
+                                        <DataField label="Outbound" value={security.firewall.public_outbound}/>
+                                    </div>
                                 </div>
-                                <div>
-                                    <p className="text-xs font-semibold text-foreground/60 mb-2">Public</p>
-                                    <DataField label="Enabled" value={security.firewall.public_enabled}/>
-                                    <DataField label="Inbound" value={security.firewall.public_inbound}/>
-                                    <DataField label="Outbound" value={security.firewall.public_outbound}/>
-                                </div>
-                            </div>
+                            )}
                         </GlassCard>
                     )}
                 </>
@@ -138,7 +152,10 @@ export default function SecuritySection({data}: Props) {
             {power && (
                 <GlassCard>
                     <h3 className="text-sm font-semibold text-foreground/60 mb-3">Power</h3>
-                    <DataField label="Power Plan" value={power.plan}/>
+                    <div className="grid grid-cols-2 sm:grid-cols-3 gap-3">
+                        <DataField label="Power Plan" value={power.plan}/>
+                        <DataField label="Power Source" value={power.power_source}/>
+                    </div>
                     {power.battery && (
                         <div className="grid grid-cols-2 sm:grid-cols-3 gap-3 mt-3">
                             <DataField label="Battery" value={power.battery.name}/>
@@ -146,6 +163,8 @@ export default function SecuritySection({data}: Props) {
                             <DataField label="Charge" value={power.battery.charge_pct}/>
                             <DataField label="Runtime (min)" value={power.battery.run_time_mins}/>
                             <DataField label="Chemistry" value={power.battery.chemistry}/>
+                            <DataField label="Capacity (mWh)" value={`${power.battery.full_charge_capacity} / ${power.battery.design_capacity}`}/>
+                            <DataField label="Cycle Count" value={power.battery.cycle_count}/>
                         </div>
                     )}
                 </GlassCard>
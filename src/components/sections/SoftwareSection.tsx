@@ -67,8 +67,18 @@ export default function SoftwareSection({data}: Props) {
                             <TableBody>
                                 {pagedPrograms.map((p, i) => (
                                     <TableRow key={i}>
-                                        <TableCell>{p.name}</TableCell>
-                                        <TableCell>{p.version}</TableCell>
+                                        <TableCell>
+                                            {p.name}
+                                            {p.package_manager && (
+                                                <span className="ml-2 text-xs text-foreground/40">{p.package_manager}</span>
+                                            )}
+                                        </TableCell>
+                                        <TableCell>
+                                            {p.version}
+                                            {p.architecture && (
+                                                <span className="ml-2 text-xs text-foreground/40">{p.architecture}</span>
+                                            )}
+                                        </TableCell>
                                         <TableCell>{p.publisher}</TableCell>
                                         <TableCell>{p.install_date}</TableCell>
                                     </TableRow>
@@ -93,6 +103,7 @@ export default function SoftwareSection({data}: Props) {
                                 <TableColumn>Description</TableColumn>
                                 <TableColumn>Installed By</TableColumn>
                                 <TableColumn>Installed On</TableColumn>
+                                <TableColumn>Packages</TableColumn>
                             </TableHeader>
                             <TableBody>
                                 {(hotfix?.hotfixes ?? []).map((h, i) => (
@@ -101,6 +112,9 @@ export default function SoftwareSection({data}: Props) {
                                         <TableCell>{h.description}</TableCell>
                                         <TableCell>{h.installed_by}</TableCell>
                                         <TableCell>{h.installed_on}</TableCell>
+                                        <TableCell className="max-w-xs truncate" title={h.packages.join("\n")}>
+                                            {h.packages.join(", ")}
+                                        </TableCell>
                                     </TableRow>
                                 ))}
                             </TableBody>
@@ -139,6 +153,7 @@ export default function SoftwareSection({data}: Props) {
                             <TableHeader>
                                 <TableColumn>Name</TableColumn>
                                 <TableColumn>State</TableColumn>
+                                <TableColumn>Command</TableColumn>
                                 <TableColumn>Last Run</TableColumn>
                                 <TableColumn>Next Run</TableColumn>
                                 <TableColumn>Author</TableColumn>
@@ -148,6 +163,7 @@ export default function SoftwareSection({data}: Props) {
                                     <TableRow key={i}>
                                         <TableCell>{t.name}</TableCell>
                                         <TableCell>{t.state}</TableCell>
+                                        <TableCell className="max-w-xs truncate">{t.command}</TableCell>
                                         <TableCell>{t.last_run}</TableCell>
                                         <TableCell>{t.next_run}</TableCell>
                                         <TableCell>{t.author}</TableCell>
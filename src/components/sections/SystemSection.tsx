@@ -81,7 +81,12 @@ export default function SystemSection({data}: Props) {
                             <TableBody>
                                 {pagedServices.map((s, i) => (
                                     <TableRow key={i}>
-                                        <TableCell>{s.name}</TableCell>
+                                        <TableCell>
+                                            {s.name}
+                                            {s.scope === "user" && (
+                                                <span className="ml-2 text-xs text-foreground/40">user</span>
+                                            )}
+                                        </TableCell>
                                         <TableCell>{s.display_name}</TableCell>
                                         <TableCell>
                                             <Chip size="sm" variant="flat"
@@ -124,16 +129,32 @@ export default function SystemSection({data}: Props) {
                             <TableHeader>
                                 <TableColumn>Name</TableColumn>
                                 <TableColumn>PID</TableColumn>
+                                <TableColumn>Parent</TableColumn>
+                                <TableColumn>User</TableColumn>
+                                <TableColumn>Status</TableColumn>
+                                <TableColumn>Threads</TableColumn>
+                                <TableColumn>CPU %</TableColumn>
                                 <TableColumn>CPU (s)</TableColumn>
-                                <TableColumn>Memory (MB)</TableColumn>
+                                <TableColumn>Resident (MB)</TableColumn>
+                                <TableColumn>Virtual (MB)</TableColumn>
+                                <TableColumn>Read (KB/s)</TableColumn>
+                                <TableColumn>Write (KB/s)</TableColumn>
                             </TableHeader>
                             <TableBody>
                                 {pagedProcesses.map((p, i) => (
                                     <TableRow key={i}>
                                         <TableCell>{p.name}</TableCell>
                                         <TableCell>{p.pid}</TableCell>
+                                        <TableCell>{p.parent_pid ?? ""}</TableCell>
+                                        <TableCell>{p.user}</TableCell>
+                                        <TableCell>{p.status}</TableCell>
+                                        <TableCell>{p.threads ?? ""}</TableCell>
+                                        <TableCell>{p.cpu_percent.toFixed(1)}</TableCell>
                                         <TableCell>{p.cpu_seconds}</TableCell>
                                         <TableCell>{p.memory_mb.toFixed(1)}</TableCell>
+                                        <TableCell>{p.virtual_memory_mb.toFixed(1)}</TableCell>
+                                        <TableCell>{(p.read_bytes_per_sec / 1024).toFixed(1)}</TableCell>
+                                        <TableCell>{(p.write_bytes_per_sec / 1024).toFixed(1)}</TableCell>
                                     </TableRow>
                                 ))}
                             </TableBody>
@@ -159,7 +180,9 @@ export default function SystemSection({data}: Props) {
                                         <TableHeader>
                                             <TableColumn>Name</TableColumn>
                                             <TableColumn>Disabled</TableColumn>
+                                            <TableColumn>Sudo</TableColumn>
                                             <TableColumn>Description</TableColumn>
+                                            <TableColumn>Last Login</TableColumn>
                                         </TableHeader>
                                         <TableBody>
                                             {users_groups.users.map((u, i) => (
@@ -171,7 +194,13 @@ export default function SystemSection({data}: Props) {
                                                             {u.disabled ? "Yes" : "No"}
                                                         </Chip>
                                                     </TableCell>
+                                                    <TableCell>
+                                                        {u.sudo && (
+                                                            <Chip size="sm" variant="flat" color="warning">Yes</Chip>
+                                                        )}
+                                                    </TableCell>
                                                     <TableCell>{u.description}</TableCell>
+                                                    <TableCell>{u.last_login}</TableCell>
                                                 </TableRow>
                                             ))}
                                         </TableBody>
@@ -214,7 +243,16 @@ export default function SystemSection({data}: Props) {
                     <Tab key="env" title="Environment">
                         {environment && (
                             <div className="grid grid-cols-1 gap-2 max-h-[400px] overflow-y-auto">
-                                {Object.entries(environment.variables).map(([key, value]) => (
+                                {environment.entries?.length ? environment.entries.map((e, i) => (
+                                    <div key={i}
+                                         className={`flex items-start justify-between gap-2 py-1 border-b border-foreground/5 ${e.overridden ? "opacity-50" : ""}`}>
+                                        <DataField label={e.name} value={e.value}/>
+                                        <div className="flex items-center gap-2 shrink-0">
+                                            <span className="text-xs text-foreground/40">{e.source}</span>
+                                            {e.overridden && <Chip size="sm" variant="flat">Overridden</Chip>}
+                                        </div>
+                                    </div>
+                                )) : Object.entries(environment.variables).map(([key, value]) => (
                                     <div key={key} className="flex gap-2 py-1 border-b border-foreground/5">
                                         <DataField label={key} value={value}/>
                                     </div>
@@ -1,6 +1,17 @@
 import {useCallback, useEffect, useRef, useState} from "react";
 import {invoke} from "@tauri-apps/api/core";
-import type {AllSystemInfo} from "../types/system-info";
+import type {AllSystemInfo, SectionKey, SectionOutcome, SystemReport} from "../types/system-info";
+
+function unwrapReport(report: SystemReport): AllSystemInfo {
+    const info: Record<string, unknown> = {};
+    const status: Partial<Record<SectionKey, SectionOutcome>> = {};
+    for (const key of Object.keys(report) as SectionKey[]) {
+        const {data, ...outcome} = report[key];
+        if (data !== null) info[key] = data;
+        status[key] = outcome;
+    }
+    return {...(info as Partial<AllSystemInfo>), status};
+}
 
 interface UseSystemInfoOptions {
     intervalMs?: number;
@@ -37,8 +48,8 @@ export function useSystemInfo({intervalMs = 0}: UseSystemInfoOptions = {}) {
         }
 
         try {
-            const result = await invoke<AllSystemInfo>("get_all_system_info");
-            setData(result);
+            const report = await invoke<SystemReport>("get_all_system_info");
+            setData(unwrapReport(report));
         } catch {
             // keep stale data on error
         }
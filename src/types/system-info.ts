@@ -9,16 +9,34 @@ export interface OSInfo {
     timezone: string;
 }
 
+export interface BoardInfo {
+    manufacturer: string;
+    product: string;
+    version: string;
+    serial_number: string;
+}
+
+export interface ChassisInfo {
+    manufacturer: string;
+    chassis_type: string;
+    serial_number: string;
+}
+
 export interface BIOSInfo {
     manufacturer: string;
     version: string;
     release_date: string;
+    serial_number: string;
+    uuid: string;
+    board: BoardInfo;
+    chassis: ChassisInfo;
 }
 
 export interface ComputerInfo {
     name: string;
     domain: string;
     manufacturer: string;
+    model: string;
     system_type: string;
     operating_system: OSInfo;
     bios: BIOSInfo;
@@ -36,11 +54,19 @@ export interface CpuInfo {
     architecture: string;
     virtualization: boolean;
     status: string;
-    load_pct: number;
+    load_pct: number | null;
+}
+
+export interface GpuOutput {
+    connector: string;
+    resolution: string;
+    refresh_rate: number;
 }
 
 export interface GpuAdapter {
     name: string;
+    pci_id: string;
+    driver: string;
     driver_version: string;
     driver_date: string;
     adapter_ram_mb: number;
@@ -48,6 +74,7 @@ export interface GpuAdapter {
     refresh_rate: number;
     status: string;
     availability: string;
+    outputs: GpuOutput[];
 }
 
 export interface GpuInfo {
@@ -62,12 +89,32 @@ export interface MemorySlot {
     form_factor: string;
     manufacturer: string;
     part_number: string;
+    serial_number: string;
+    rank: number;
+    configured_speed_mhz: number;
+    voltage_mv: number;
+    ecc: boolean;
+}
+
+export interface MemoryUsage {
+    total_mb: number;
+    available_mb: number;
+    used_mb: number;
+    used_pct: number;
+    cached_mb: number;
+    buffers_mb: number;
+    swap_total_mb: number;
+    swap_used_mb: number;
+    commit_limit_mb: number | null;
+    committed_mb: number | null;
 }
 
 export interface MemoryInfo {
     slots: MemorySlot[];
     total_slots: number;
     max_capacity_gb: number;
+    error_correction: string;
+    usage: MemoryUsage;
 }
 
 export interface PhysicalDisk {
@@ -77,6 +124,7 @@ export interface PhysicalDisk {
     disk_type: string;
     size_gb: number;
     status: string;
+    removable: boolean;
 }
 
 export interface LogicalDisk {
@@ -115,8 +163,17 @@ export interface Monitor {
     name: string;
     serial_number: string;
     year_of_manufacture: number;
+    connector: string;
+    connection_type: string;
     resolution: string;
     refresh_rate: number;
+    native_resolution: string;
+    width_mm: number;
+    height_mm: number;
+    diagonal_inches: number;
+    bit_depth: number;
+    hdr_formats: string[];
+    supported_modes: string[];
 }
 
 export interface MonitorInfo {
@@ -128,6 +185,10 @@ export interface AudioDevice {
     manufacturer: string;
     status: string;
     device_id: string;
+    driver: string;
+    codecs: string[];
+    playback_devices: number;
+    capture_devices: number;
 }
 
 export interface AudioInfo {
@@ -139,6 +200,15 @@ export interface UsbDevice {
     device_id: string;
     manufacturer: string;
     status: string;
+    vendor_id: string;
+    product_id: string;
+    bus: number;
+    ports: string;
+    parent: string;
+    speed: string;
+    device_class: string;
+    serial_number: string;
+    max_power_ma: number;
 }
 
 export interface UsbInfo {
@@ -153,10 +223,12 @@ export interface BatteryInfo {
     design_capacity: string;
     full_charge_capacity: string;
     chemistry: string;
+    cycle_count: string;
 }
 
 export interface PowerInfo {
     plan: string;
+    power_source: string;
     battery: BatteryInfo | null;
 }
 
@@ -179,6 +251,9 @@ export interface FirewallInfo {
     public_enabled: boolean | null;
     public_inbound: string | null;
     public_outbound: string | null;
+    backend: string | null;
+    inbound: string | null;
+    outbound: string | null;
 }
 
 export interface UpdateItem {
@@ -198,6 +273,8 @@ export interface SecurityInfo {
     uac: boolean;
     rdp_enabled: boolean;
     bit_locker: boolean;
+    encrypted_volumes: string[];
+    mandatory_access_control: string | null;
     pending_updates: UpdateItem[] | null;
 }
 
@@ -208,6 +285,16 @@ export interface ProcessEntry {
     memory_mb: number;
     exe_path: string;
     command: string;
+    parent_pid: number | null;
+    user: string;
+    start_time: string;
+    status: string;
+    threads: number | null;
+    working_directory: string;
+    virtual_memory_mb: number;
+    cpu_percent: number;
+    read_bytes_per_sec: number;
+    write_bytes_per_sec: number;
 }
 
 export interface ProcessInfo {
@@ -222,6 +309,7 @@ export interface Service {
     account: string;
     path: string;
     description: string;
+    scope: string;
 }
 
 export interface ServiceInfo {
@@ -233,6 +321,8 @@ export interface InstalledProgram {
     version: string;
     publisher: string;
     install_date: string;
+    package_manager: string;
+    architecture: string;
 }
 
 export interface SoftwareInfo {
@@ -244,6 +334,7 @@ export interface Hotfix {
     description: string;
     installed_by: string;
     installed_on: string;
+    packages: string[];
 }
 
 export interface HotfixInfo {
@@ -269,6 +360,7 @@ export interface ScheduledTask {
     next_run: string;
     result: string;
     author: string;
+    command: string;
 }
 
 export interface ScheduledTaskInfo {
@@ -279,12 +371,25 @@ export interface LocalUser {
     name: string;
     disabled: boolean;
     description: string;
+    uid: number | null;
+    home: string;
+    shell: string;
+    system_account: boolean;
+    locked: boolean;
+    password_expired: boolean;
+    password_last_set: string;
+    account_expires: string;
+    sudo: boolean;
+    last_login: string;
 }
 
 export interface LocalGroup {
     name: string;
     description: string;
     members: string[];
+    gid: number | null;
+    system_group: boolean;
+    administrators: string[];
 }
 
 export interface UsersGroupsInfo {
@@ -292,8 +397,16 @@ export interface UsersGroupsInfo {
     groups: LocalGroup[];
 }
 
+export interface EnvironmentEntry {
+    name: string;
+    value: string;
+    source: string;
+    overridden: boolean;
+}
+
 export interface EnvironmentInfo {
     variables: Record<string, string>;
+    entries: EnvironmentEntry[];
 }
 
 export interface EventEntry {
@@ -309,25 +422,48 @@ export interface EventLogInfo {
     application_events: EventEntry[];
 }
 
-export interface AllSystemInfo {
-    computer?: ComputerInfo;
-    cpu?: CpuInfo;
-    gpu?: GpuInfo;
-    memory?: MemoryInfo;
-    disk?: DiskInfo;
-    network?: NetworkInfo;
-    monitor?: MonitorInfo;
-    audio?: AudioInfo;
-    usb?: UsbInfo;
-    power?: PowerInfo;
-    security?: SecurityInfo;
-    process?: ProcessInfo;
-    service?: ServiceInfo;
-    startup?: StartupInfo;
-    software?: SoftwareInfo;
-    hotfix?: HotfixInfo;
-    users_groups?: UsersGroupsInfo;
-    environment?: EnvironmentInfo;
-    event_log?: EventLogInfo;
-    scheduled_task?: ScheduledTaskInfo;
+export interface SectionData {
+    computer: ComputerInfo;
+    cpu: CpuInfo;
+    gpu: GpuInfo;
+    memory: MemoryInfo;
+    disk: DiskInfo;
+    network: NetworkInfo;
+    monitor: MonitorInfo;
+    audio: AudioInfo;
+    usb: UsbInfo;
+    power: PowerInfo;
+    security: SecurityInfo;
+    process: ProcessInfo;
+    service: ServiceInfo;
+    startup: StartupInfo;
+    software: SoftwareInfo;
+    hotfix: HotfixInfo;
+    users_groups: UsersGroupsInfo;
+    environment: EnvironmentInfo;
+    event_log: EventLogInfo;
+    scheduled_task: ScheduledTaskInfo;
+}
+
+export type SectionKey = keyof SectionData;
+
+export type SectionStatus = "ok" | "failed" | "skipped" | "unsupported";
+
+export interface SectionOutcome {
+    status: SectionStatus;
+    errors: string[];
+    warnings: string[];
+    duration_ms: number;
 }
+
+export interface SectionResult<T> extends SectionOutcome {
+    data: T | null;
+}
+
+/** Raw shape returned by the `get_all_system_info` command. */
+export type SystemReport = { [K in SectionKey]: SectionResult<SectionData[K]> };
+
+/** Section data with failed/skipped sections left out, plus each section's outcome. */
+export type AllSystemInfo = Partial<SectionData> & {
+    status?: Partial<Record<SectionKey, SectionOutcome>>;
+};
@@ -25,11 +25,16 @@ export function exportAsHtml(data: AllSystemInfo): string {
                 ${field("Name", c.name)}
                 ${field("Domain", c.domain)}
                 ${field("Manufacturer", c.manufacturer)}
+                ${field("Model", c.model)}
                 ${field("System Type", c.system_type)}
                 ${field("OS", `${c.operating_system.name} ${c.operating_system.version}`)}
                 ${field("Architecture", c.operating_system.architecture)}
                 ${field("Timezone", c.operating_system.timezone)}
                 ${field("BIOS", `${c.bios.manufacturer} ${c.bios.version}`)}
+                ${field("Motherboard", `${c.bios.board.manufacturer} ${c.bios.board.product}`)}
+                ${field("Chassis", c.bios.chassis.chassis_type)}
+                ${field("Serial Number", c.bios.serial_number)}
+                ${field("UUID", c.bios.uuid)}
             </div>
         `);
     }
@@ -54,19 +59,32 @@ export function exportAsHtml(data: AllSystemInfo): string {
         body += section("GPU", data.gpu.adapters.map(a => `
             <div class="fields">
                 ${field("Name", a.name)}
-                ${field("Driver", a.driver_version)}
+                ${field("Driver", `${a.driver} ${a.driver_version}`)}
+                ${field("PCI ID", a.pci_id)}
                 ${field("VRAM (MB)", a.adapter_ram_mb)}
                 ${field("Resolution", a.resolution)}
                 ${field("Refresh Rate", `${a.refresh_rate} Hz`)}
+                ${a.outputs.map(o => field(o.connector, `${o.resolution} @ ${o.refresh_rate} Hz`)).join("")}
             </div>
         `).join("<hr>"));
     }
 
     if (data.memory) {
-        body += section("Memory",
+        const u = data.memory.usage;
+        body += section("Memory", `
+            <div class="fields">
+                ${field("Total", `${u.total_mb} MB`)}
+                ${field("Available", `${u.available_mb} MB`)}
+                ${field("Used", `${u.used_mb} MB (${u.used_pct.toFixed(1)}%)`)}
+                ${field("Cached", `${u.cached_mb} MB`)}
+                ${field("Buffers", `${u.buffers_mb} MB`)}
+                ${field("Swap", `${u.swap_used_mb} / ${u.swap_total_mb} MB`)}
+                ${field("Commit", `${u.committed_mb ?? "N/A"} / ${u.commit_limit_mb ?? "N/A"} MB`)}
+            </div>
+        ` +
             table(
-                ["Bank", "Capacity (GB)", "Speed (MHz)", "Type", "Manufacturer", "Part Number"],
-                data.memory.slots.map(s => [s.bank_label, String(s.capacity_gb), String(s.speed_mhz), s.memory_type, s.manufacturer, s.part_number])
+                ["Bank", "Capacity (GB)", "Speed (MHz)", "Configured (MHz)", "Type", "Rank", "ECC", "Manufacturer", "Part Number", "Serial"],
+                data.memory.slots.map(s => [s.bank_label, String(s.capacity_gb), String(s.speed_mhz), String(s.configured_speed_mhz), s.memory_type, String(s.rank), s.ecc ? "Yes" : "No", s.manufacturer, s.part_number, s.serial_number])
             )
         );
     }
@@ -131,6 +149,8 @@ export function exportAsHtml(data: AllSystemInfo): string {
                 ${field("BitLocker", s.bit_locker)}
                 ${field("RDP Enabled", s.rdp_enabled)}
                 ${field("Antivirus", s.antivirus ?? "Not detected")}
+                ${field("Access Control", s.mandatory_access_control ?? "—")}
+                ${field("Encrypted Volumes", s.encrypted_volumes.join(", ") || "—")}
             </div>
             <h3>Pending Updates</h3>
             ${pendingContent}
@@ -140,8 +160,27 @@ export function exportAsHtml(data: AllSystemInfo): string {
     if (data.software) {
         body += section("Installed Software",
             table(
-                ["Name", "Version", "Publisher", "Install Date"],
-                data.software.programs.map(p => [p.name, p.version, p.publisher, p.install_date])
+                ["Name", "Version", "Publisher", "Install Date", "Source"],
+                data.software.programs.map(p => [
+                    p.name, p.version, p.publisher, p.install_date,
+                    [p.package_manager, p.architecture].filter(Boolean).join(" ") || "—",
+                ])
+            )
+        );
+    }
+
+    const issues = Object.entries(data.status ?? {})
+        .filter(([, o]) => o && (o.status !== "ok" || o.warnings.length > 0));
+    if (issues.length > 0) {
+        body += section("Collection Status",
+            table(
+                ["Section", "Status", "Duration (ms)", "Details"],
+                issues.map(([key, o]) => [
+                    key,
+                    o!.status,
+                    String(o!.duration_ms),
+                    [o!.errors.join(": "), ...o!.warnings].filter(Boolean).join("; ") || "—",
+                ])
             )
         );
     }
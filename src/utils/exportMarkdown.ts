@@ -26,11 +26,16 @@ export function exportAsMarkdown(data: AllSystemInfo): string {
                 ["Name", c.name],
                 ["Domain", c.domain],
                 ["Manufacturer", c.manufacturer],
+                ["Model", c.model],
                 ["System Type", c.system_type],
                 ["OS", `${c.operating_system.name} ${c.operating_system.version}`],
                 ["Architecture", c.operating_system.architecture],
                 ["Timezone", c.operating_system.timezone],
                 ["BIOS", `${c.bios.manufacturer} ${c.bios.version}`],
+                ["Motherboard", `${c.bios.board.manufacturer} ${c.bios.board.product}`],
+                ["Chassis", c.bios.chassis.chassis_type],
+                ["Serial Number", c.bios.serial_number],
+                ["UUID", c.bios.uuid],
             ]
         ));
         lines.push("");
@@ -65,23 +70,45 @@ export function exportAsMarkdown(data: AllSystemInfo): string {
             lines.push(mdTable(
                 ["Property", "Value"],
                 [
+                    ["Driver", a.driver],
                     ["Driver Version", a.driver_version],
+                    ["PCI ID", a.pci_id],
                     ["VRAM (MB)", String(a.adapter_ram_mb)],
                     ["Resolution", a.resolution],
                     ["Refresh Rate", `${a.refresh_rate} Hz`],
                     ["Status", a.status],
+                    ...a.outputs.map(o => [o.connector, `${o.resolution} @ ${o.refresh_rate} Hz`]),
                 ]
             ));
             lines.push("");
         }
     }
 
-    if (data.memory && data.memory.slots.length > 0) {
+    if (data.memory) {
+        const u = data.memory.usage;
         lines.push("## Memory");
         lines.push("");
         lines.push(mdTable(
-            ["Bank", "Capacity (GB)", "Speed (MHz)", "Type", "Manufacturer", "Part Number"],
-            data.memory.slots.map(s => [s.bank_label, String(s.capacity_gb), String(s.speed_mhz), s.memory_type, s.manufacturer, s.part_number])
+            ["Property", "Value"],
+            [
+                ["Total (MB)", String(u.total_mb)],
+                ["Available (MB)", String(u.available_mb)],
+                ["Used", `${u.used_mb} MB (${u.used_pct.toFixed(1)}%)`],
+                ["Cached (MB)", String(u.cached_mb)],
+                ["Buffers (MB)", String(u.buffers_mb)],
+                ["Swap Used / Total (MB)", `${u.swap_used_mb} / ${u.swap_total_mb}`],
+                ["Committed / Limit (MB)", `${u.committed_mb ?? "N/A"} / ${u.commit_limit_mb ?? "N/A"}`],
+            ]
+        ));
+        lines.push("");
+    }
+
+    if (data.memory && data.memory.slots.length > 0) {
+        lines.push("### Memory Slots");
+        lines.push("");
+        lines.push(mdTable(
+            ["Bank", "Capacity (GB)", "Speed (MHz)", "Configured (MHz)", "Type", "Rank", "ECC", "Manufacturer", "Part Number", "Serial"],
+            data.memory.slots.map(s => [s.bank_label, String(s.capacity_gb), String(s.speed_mhz), String(s.configured_speed_mhz), s.memory_type, String(s.rank), s.ecc ? "Yes" : "No", s.manufacturer, s.part_number, s.serial_number])
         ));
         lines.push("");
     }
@@ -143,6 +170,8 @@ export function exportAsMarkdown(data: AllSystemInfo): string {
                 ["BitLocker", String(s.bit_locker)],
                 ["RDP Enabled", String(s.rdp_enabled)],
                 ["Antivirus", s.antivirus ?? "Not detected"],
+                ["Access Control", s.mandatory_access_control ?? "—"],
+                ["Encrypted Volumes", s.encrypted_volumes.join(", ") || "—"],
             ]
         ));
         lines.push("");
@@ -173,8 +202,28 @@ export function exportAsMarkdown(data: AllSystemInfo): string {
         lines.push("## Installed Software");
         lines.push("");
         lines.push(mdTable(
-            ["Name", "Version", "Publisher", "Install Date"],
-            data.software.programs.map(p => [p.name, p.version, p.publisher, p.install_date])
+            ["Name", "Version", "Publisher", "Install Date", "Source"],
+            data.software.programs.map(p => [
+                    p.name, p.version, p.publisher, p.install_date,
+                    [p.package_manager, p.architecture].filter(Boolean).join(" ") || "—",
+                ])
+        ));
+        lines.push("");
+    }
+
+    const issues = Object.entries(data.status ?? {})
+        .filter(([, o]) => o && (o.status !== "ok" || o.warnings.length > 0));
+    if (issues.length > 0) {
+        lines.push("## Collection Status");
+        lines.push("");
+        lines.push(mdTable(
+            ["Section", "Status", "Duration (ms)", "Details"],
+            issues.map(([key, o]) => [
+                key,
+                o!.status,
+                String(o!.duration_ms),
+                [o!.errors.join(": "), ...o!.warnings].filter(Boolean).join("; ") || "—",
+            ])
         ));
         lines.push("");
     }